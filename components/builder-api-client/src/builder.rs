@@ -1116,6 +1116,46 @@ impl BuilderAPIClient {
         Ok(package)
     }
 
+    /// Returns every release of a package in a channel. If the package identifier has a version,
+    /// only releases of that version are returned.
+    ///
+    /// # Failures
+    ///
+    /// * Package cannot be found
+    /// * Remote Builder is not available
+    pub async fn list_channel_package_releases(&self,
+                                               (package, target): (&PackageIdent, PackageTarget),
+                                               channel: &ChannelIdent,
+                                               token: Option<&str>)
+                                               -> Result<Vec<PackageIdent>> {
+        debug!("Listing releases of {} in channel {}, target {}",
+               package, channel, target);
+
+        let path = channel_package_path(channel, package);
+        let mut releases = Vec::new();
+        loop {
+            let range = releases.len();
+            let req = self.0.get_with_custom_url(&path, |u| {
+                                u.set_query(Some(&format!("range={}&target={}", range, target)))
+                            });
+            let resp = self.maybe_add_authz(req, token).send().await?;
+            let resp = response::ok_if(resp, &[StatusCode::OK, StatusCode::PARTIAL_CONTENT]).await?;
+            let more_to_come = resp.status() == StatusCode::PARTIAL_CONTENT;
+
+            let encoded = resp.text().await.map_err(Error::BadResponseBody)?;
+            trace!(target: "habitat_http_client::api_client::list_channel_package_releases",
+                   "{:?}",
+                   encoded);
+
+            let mut results: PackageResults<PackageIdent> = serde_json::from_str(&encoded)?;
+            if results.data.is_empty() || !more_to_come {
+                releases.append(&mut results.data);
+                return Ok(releases);
+            }
+            releases.append(&mut results.data);
+        }
+    }
+
     /// Upload a package to a remote Builder.
     ///
     /// # Failures
@@ -79,7 +79,10 @@ impl From<CService> for Rumor {
                                 pkg:             Some(value.pkg),
                                 pkg_incarnation: Some(value.pkg_incarnation),
                                 cfg:             Some(value.cfg),
-                                sys:             Some(value.sys.into()), };
+                                sys:             Some(value.sys.into()),
+                                ready:           Some(value.ready),
+                                draining:        Some(value.draining),
+                                health:          value.health.map(|health| health as i32), };
         Rumor { r#type:  RumorType::Service as i32,
                 tag:     Vec::default(),
                 from_id: Some(value.member_id),
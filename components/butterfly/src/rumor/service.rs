@@ -2,6 +2,7 @@
 //!
 //! Service rumors declare that a given `Server` is running this Service.
 
+pub use crate::protocol::newscast::service::Health;
 use crate::{error::{Error,
                     Result},
             protocol::{self,
@@ -32,6 +33,16 @@ pub struct Service {
     pub pkg_incarnation: u64,
     pub cfg:             Vec<u8>,
     pub sys:             SysInfo,
+    /// Whether the service is accepting traffic, as reported by its
+    /// `ready` hook. Supervisors from before readiness was gossiped
+    /// don't report it, and their services count as ready.
+    pub ready:           bool,
+    /// Whether the service has been drained, and so should be taken
+    /// out of rotation while it keeps running.
+    pub draining:        bool,
+    /// The result of the service's latest health check. Supervisors
+    /// from before health was gossiped don't report it.
+    pub health:          Option<Health>,
 }
 
 impl fmt::Display for Service {
@@ -47,7 +58,7 @@ impl Serialize for Service {
     fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
         where S: Serializer
     {
-        let mut strukt = serializer.serialize_struct("service", 7)?;
+        let mut strukt = serializer.serialize_struct("service", 10)?;
         let cfg: toml::value::Table =
             toml::from_str(str::from_utf8(&self.cfg).unwrap_or_default()).unwrap_or_default();
         strukt.serialize_field("member_id", &self.member_id)?;
@@ -58,6 +69,9 @@ impl Serialize for Service {
         strukt.serialize_field("cfg", &cfg)?;
         strukt.serialize_field("sys", &self.sys)?;
         strukt.serialize_field("initialized", &self.initialized)?;
+        strukt.serialize_field("ready", &self.ready)?;
+        strukt.serialize_field("draining", &self.draining)?;
+        strukt.serialize_field("health", &self.health)?;
         strukt.end()
     }
 }
@@ -104,6 +118,9 @@ impl Service {
                   pkg: package.to_string(),
                   pkg_incarnation: 0,
                   sys,
+                  ready: false,
+                  draining: false,
+                  health: Some(Health::Unknown),
                   cfg: cfg.map(|v| {
                               // Directly serializing a toml::value::Table can lead to an error
                               // Wrapping it in a toml::value::Value makes this operation safe
@@ -138,7 +155,10 @@ impl FromProto<newscast::Rumor> for Service {
                      cfg:             payload.cfg.unwrap_or_default(),
                      sys:             payload.sys
                                              .ok_or(Error::ProtocolMismatch("sys"))
-                                             .and_then(SysInfo::from_proto)?, })
+                                             .and_then(SysInfo::from_proto)?,
+                     ready:           payload.ready.unwrap_or(true),
+                     draining:        payload.draining.unwrap_or(false),
+                     health:          payload.health.and_then(Health::from_i32), })
     }
 }
 
@@ -151,7 +171,10 @@ impl From<Service> for newscast::Service {
                             pkg:             Some(value.pkg),
                             pkg_incarnation: Some(value.pkg_incarnation),
                             cfg:             Some(value.cfg),
-                            sys:             Some(value.sys.into()), }
+                            sys:             Some(value.sys.into()),
+                            ready:           Some(value.ready),
+                            draining:        Some(value.draining),
+                            health:          value.health.map(|health| health as i32), }
     }
 }
 
@@ -236,8 +259,11 @@ mod tests {
                        service::ServiceGroup};
 
     use super::Service;
-    use crate::rumor::{service::SysInfo,
-                       Rumor};
+    use crate::{protocol::{newscast,
+                           FromProto},
+                rumor::{service::SysInfo,
+                        Rumor,
+                        RumorPayload}};
 
     fn create_service(member_id: &str) -> Service {
         let pkg = PackageIdent::from_str("core/neurosis/1.2.3/20161208121212").unwrap();
@@ -323,6 +349,24 @@ mod tests {
         assert_eq!(s1, s1_check);
     }
 
+    #[test]
+    fn services_gossiped_without_readiness_are_ready() {
+        let mut rumor = newscast::Rumor::from(create_service("adam"));
+        if let Some(RumorPayload::Service(ref mut payload)) = rumor.payload {
+            payload.ready = None;
+        }
+        assert!(Service::from_proto(rumor).unwrap().ready);
+    }
+
+    #[test]
+    fn services_gossiped_without_health_do_not_report_it() {
+        let mut rumor = newscast::Rumor::from(create_service("adam"));
+        if let Some(RumorPayload::Service(ref mut payload)) = rumor.payload {
+            payload.health = None;
+        }
+        assert_eq!(Service::from_proto(rumor).unwrap().health, None);
+    }
+
     #[test]
     #[should_panic]
     fn service_package_name_mismatch() {
@@ -1448,7 +1448,10 @@ mod tests {
                   initialized:     Default::default(),
                   pkg:             Default::default(),
                   cfg:             Default::default(),
-                  sys:             Default::default(), }
+                  sys:             Default::default(),
+                  ready:           Default::default(),
+                  draining:        Default::default(),
+                  health:          Default::default(), }
     }
 
     #[test]
@@ -18,6 +18,7 @@ pub mod liveliness_checker;
 pub mod output;
 pub mod owning_refs;
 pub mod package_graph;
+pub mod service_log;
 pub mod templating;
 pub mod types;
 pub mod ui;
@@ -0,0 +1,322 @@
+//! Persistent capture of a service's output.
+//!
+//! The Launcher appends every line a service writes to standard output
+//! or standard error to `service.log` in the service's `var`
+//! directory, prefixed with a UTC timestamp and the stream it came
+//! from:
+//!
+//! ```text
+//! 2023-10-17T00:45:12.123Z O Ready to accept connections
+//! ```
+//!
+//! Once the file would grow past a size limit it is renamed to
+//! `service.log.1`, older files are shifted up to `service.log.N`, and
+//! the oldest is discarded. The Supervisor reads these files back to
+//! serve `hab svc logs`.
+
+use chrono::{DateTime,
+             SecondsFormat,
+             Utc};
+use std::{fs::{self,
+               File,
+               OpenOptions},
+          io::{self,
+               BufRead,
+               BufReader,
+               Seek,
+               SeekFrom,
+               Write},
+          path::{Path,
+                 PathBuf},
+          time::SystemTime};
+
+pub const FILE_NAME: &str = "service.log";
+
+/// The current log file of the service whose `var` directory is
+/// `svc_var_path`.
+pub fn path(svc_var_path: &Path) -> PathBuf { svc_var_path.join(FILE_NAME) }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn marker(self) -> &'static str {
+        match self {
+            Stream::Stdout => "O",
+            Stream::Stderr => "E",
+        }
+    }
+}
+
+/// Appends timestamped lines to a log file, rotating it by size.
+pub struct LogWriter {
+    path:      PathBuf,
+    file:      Option<File>,
+    size:      u64,
+    max_bytes: u64,
+    max_files: u32,
+}
+
+impl LogWriter {
+    /// Open `path` for appending. At most `max_files` rotated files
+    /// are kept alongside it.
+    pub fn open<P: Into<PathBuf>>(path: P, max_bytes: u64, max_files: u32) -> io::Result<Self> {
+        let mut writer = LogWriter { path: path.into(),
+                                     file: None,
+                                     size: 0,
+                                     max_bytes,
+                                     max_files };
+        writer.file()?;
+        Ok(writer)
+    }
+
+    pub fn write_line(&mut self, stream: Stream, line: &str) -> io::Result<()> {
+        self.write_entry(Utc::now(), stream, line)
+    }
+
+    fn write_entry(&mut self,
+                   timestamp: DateTime<Utc>,
+                   stream: Stream,
+                   line: &str)
+                   -> io::Result<()> {
+        let entry = format!("{} {} {}\n",
+                            timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+                            stream.marker(),
+                            line);
+        if self.size > 0 && self.size + entry.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file()?.write_all(entry.as_bytes())?;
+        self.size += entry.len() as u64;
+        Ok(())
+    }
+
+    /// The open log file, reopening it if a previous rotation failed
+    /// part way through.
+    fn file(&mut self) -> io::Result<&mut File> {
+        if self.file.is_none() {
+            let file = OpenOptions::new().create(true)
+                                         .append(true)
+                                         .open(&self.path)?;
+            self.size = file.metadata()?.len();
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().expect("log file was just opened"))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        // Windows refuses to rename a file that is still open.
+        self.file = None;
+        if self.max_files == 0 {
+            return fs::remove_file(&self.path);
+        }
+        for n in (1..self.max_files).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))
+    }
+}
+
+fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Every line in the log at `path` and its rotated files, oldest
+/// first. When `since` is given, lines logged before it are skipped.
+pub fn read(path: &Path, since: Option<SystemTime>) -> io::Result<Vec<String>> {
+    let mut files = vec![path.to_path_buf()];
+    let mut n = 1;
+    while rotated_path(path, n).exists() {
+        files.push(rotated_path(path, n));
+        n += 1;
+    }
+    let mut lines = Vec::new();
+    for file in files.iter().rev() {
+        let (file_lines, _) = read_from(file, 0)?;
+        match since {
+            Some(since) => {
+                let since = DateTime::<Utc>::from(since);
+                lines.extend(file_lines.into_iter()
+                                       .filter(|line| logged_since(line, since)))
+            }
+            None => lines.extend(file_lines),
+        }
+    }
+    Ok(lines)
+}
+
+/// Lines without a parseable timestamp are kept rather than hidden.
+fn logged_since(line: &str, since: DateTime<Utc>) -> bool {
+    line.split(' ')
+        .next()
+        .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+        .map_or(true, |timestamp| timestamp.with_timezone(&Utc) >= since)
+}
+
+/// Returns the complete lines of `path` after byte `offset`, and the
+/// number of bytes they took up. A missing file has no lines.
+fn read_from(path: &Path, offset: u64) -> io::Result<(Vec<String>, u64)> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+        Err(e) => return Err(e),
+    };
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(file);
+    let mut lines = Vec::new();
+    let mut consumed = 0;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let n = reader.read_until(b'\n', &mut buf)?;
+        // A partial line is still being written; pick it up next time.
+        if n == 0 || buf.last() != Some(&b'\n') {
+            break;
+        }
+        consumed += n as u64;
+        buf.pop();
+        lines.push(String::from_utf8_lossy(&buf).into_owned());
+    }
+    Ok((lines, consumed))
+}
+
+/// Tails a log file, following it across rotations.
+pub struct LogFollower {
+    path:     PathBuf,
+    position: u64,
+    // The first line of the file being followed. Rotation is noticed
+    // by it changing, since the new file may already be as long as
+    // the old one was.
+    head:     Option<String>,
+}
+
+impl LogFollower {
+    /// Start following from the current end of the log at `path`.
+    pub fn new<P: Into<PathBuf>>(path: P) -> io::Result<Self> {
+        let path = path.into();
+        let head = first_line(&path)?;
+        let position = if head.is_some() {
+            fs::metadata(&path)?.len()
+        } else {
+            0
+        };
+        Ok(LogFollower { path,
+                         position,
+                         head })
+    }
+
+    /// Lines appended to the log since the last poll.
+    pub fn poll(&mut self) -> io::Result<Vec<String>> {
+        let head = first_line(&self.path)?;
+        let mut lines = Vec::new();
+        if self.position > 0 && head != self.head {
+            // Finish off the previous file before starting on the
+            // new one.
+            let (rest, _) = read_from(&rotated_path(&self.path, 1), self.position)?;
+            lines.extend(rest);
+            self.position = 0;
+        }
+        let (new, consumed) = read_from(&self.path, self.position)?;
+        self.position += consumed;
+        self.head = head;
+        lines.extend(new);
+        Ok(lines)
+    }
+}
+
+fn first_line(path: &Path) -> io::Result<Option<String>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut buf = Vec::new();
+    BufReader::new(file).read_until(b'\n', &mut buf)?;
+    if buf.pop() == Some(b'\n') {
+        Ok(Some(String::from_utf8_lossy(&buf).into_owned()))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn at(secs: i64) -> DateTime<Utc> { Utc.timestamp_opt(secs, 0).unwrap() }
+
+    #[test]
+    fn write_line_formats_entries() {
+        let dir = TempDir::new().unwrap();
+        let log = path(dir.path());
+        let mut writer = LogWriter::open(&log, 1024, 2).unwrap();
+        writer.write_entry(at(0), Stream::Stdout, "hello").unwrap();
+        writer.write_entry(at(1), Stream::Stderr, "oops").unwrap();
+
+        assert_eq!(read(&log, None).unwrap(),
+                   vec!["1970-01-01T00:00:00.000Z O hello",
+                        "1970-01-01T00:00:01.000Z E oops"]);
+    }
+
+    #[test]
+    fn rotation_keeps_at_most_max_files() {
+        let dir = TempDir::new().unwrap();
+        let log = path(dir.path());
+        // Each entry is 34 bytes, so every file holds two of them.
+        let mut writer = LogWriter::open(&log, 70, 2).unwrap();
+        for i in 0..8 {
+            writer.write_entry(at(i), Stream::Stdout, &format!("line {}", i))
+                  .unwrap();
+        }
+
+        assert!(rotated_path(&log, 2).exists());
+        assert!(!rotated_path(&log, 3).exists());
+        let lines = read(&log, None).unwrap();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].ends_with("line 2"));
+        assert!(lines[5].ends_with("line 7"));
+    }
+
+    #[test]
+    fn read_skips_lines_before_since() {
+        let dir = TempDir::new().unwrap();
+        let log = path(dir.path());
+        let mut writer = LogWriter::open(&log, 1024, 2).unwrap();
+        for i in 0..4 {
+            writer.write_entry(at(i * 60), Stream::Stdout, &format!("line {}", i))
+                  .unwrap();
+        }
+
+        let lines = read(&log, Some(at(120).into())).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("line 2"));
+    }
+
+    #[test]
+    fn follower_picks_up_new_lines_across_rotation() {
+        let dir = TempDir::new().unwrap();
+        let log = path(dir.path());
+        let mut writer = LogWriter::open(&log, 70, 2).unwrap();
+        writer.write_entry(at(0), Stream::Stdout, "before").unwrap();
+
+        let mut follower = LogFollower::new(&log).unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+
+        writer.write_entry(at(1), Stream::Stdout, "line 1").unwrap();
+        writer.write_entry(at(2), Stream::Stdout, "line 2").unwrap();
+        let lines = follower.poll().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("line 1"));
+        assert!(lines[1].ends_with("line 2"));
+    }
+}
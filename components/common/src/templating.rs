@@ -75,6 +75,8 @@ impl TemplateRenderer {
     pub fn new() -> Self {
         let mut handlebars = Handlebars::new();
         handlebars.register_helper("eachAlive", Box::new(helpers::EACH_ALIVE));
+        handlebars.register_helper("eachReady", Box::new(helpers::EACH_READY));
+        handlebars.register_helper("eachHealthy", Box::new(helpers::EACH_HEALTHY));
         handlebars.register_helper("pkgPathFor", Box::new(helpers::PKG_PATH_FOR));
         handlebars.register_helper("strConcat", Box::new(helpers::STR_CONCAT));
         handlebars.register_helper("strJoin", Box::new(helpers::STR_JOIN));
@@ -411,6 +413,47 @@ mod test {
         assert_eq!(each_alive_render, each_if_render);
     }
 
+    #[test]
+    fn each_ready_helper_content() {
+        let mut renderer = TemplateRenderer::new();
+        // template using the new `eachReady` helper
+        renderer.register_template_file("each_ready", templates().join("each_ready.txt"))
+                .unwrap();
+
+        // template using an each block with nested if blocks filtering on `alive` and `ready`
+        renderer.register_template_file("ready_members", templates().join("ready_members.txt"))
+                .unwrap();
+
+        let data = service_config_json_from_toml_file("multiple_supervisors_config.toml");
+
+        let each_ready_render = renderer.render("each_ready", &data).unwrap();
+        let each_if_render = renderer.render("ready_members", &data).unwrap();
+
+        assert_eq!(each_ready_render, each_if_render);
+        assert!(each_ready_render.contains("b162bfc10cf54eb4bce93689a8023eb9"));
+        assert!(!each_ready_render.contains("b42cbf6699ea4f03be68e36ea9a41270"));
+        assert!(!each_ready_render.contains("8325c1d9c12543dc83a99f196500f44c"));
+        assert!(!each_ready_render.contains("5b5e6f4a1c8d4e0f9a3b2c7d8e9f0a1b"));
+    }
+
+    #[test]
+    fn each_healthy_helper_content() {
+        let mut renderer = TemplateRenderer::new();
+        renderer.register_template_file("each_healthy", templates().join("each_healthy.txt"))
+                .unwrap();
+
+        let data = service_config_json_from_toml_file("multiple_supervisors_config.toml");
+
+        let each_healthy_render = renderer.render("each_healthy", &data).unwrap();
+        assert!(each_healthy_render.contains("b162bfc10cf54eb4bce93689a8023eb9"));
+        assert!(!each_healthy_render.contains("b42cbf6699ea4f03be68e36ea9a41270"));
+        // Alive, but its health check is critical
+        assert!(!each_healthy_render.contains("8325c1d9c12543dc83a99f196500f44c"));
+        assert!(!each_healthy_render.contains("5b5e6f4a1c8d4e0f9a3b2c7d8e9f0a1b"));
+        // Its health isn't known yet
+        assert!(each_healthy_render.contains("0d1c9a6e5b2f4c3e8a7b6d5c4e3f2a1b"));
+    }
+
     #[tokio::test]
     async fn render_package_install() {
         let root = TempDir::new().expect("create temp dir").into_path();
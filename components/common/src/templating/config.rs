@@ -78,7 +78,13 @@ impl PackageConfigPaths for Pkg {
 
     fn default_config_dir(&self) -> PathBuf { self.path.clone() }
 
-    fn recommended_user_config_dir(&self) -> PathBuf { fs::user_config_path(&self.name) }
+    // The svc path is named after the service, which is not the package name for named
+    // instances, so each instance gets a user.toml of its own.
+    fn recommended_user_config_dir(&self) -> PathBuf {
+        self.svc_path
+            .file_name()
+            .map_or_else(|| fs::user_config_path(&self.name), fs::user_config_path)
+    }
 
     fn deprecated_user_config_dir(&self) -> PathBuf { self.svc_path.clone() }
 }
@@ -477,6 +483,21 @@ impl CfgRenderer {
         }
         Ok(changed)
     }
+
+    /// Render all configuration files without writing them.
+    ///
+    /// Returns the path of each file relative to the configuration
+    /// directory, in order, along with its content or the error
+    /// rendering it.
+    pub fn render<T>(&self, ctx: &T) -> Vec<(String, Result<String>)>
+        where T: Serialize
+    {
+        let mut templates = self.0.get_templates().keys().collect::<Vec<_>>();
+        templates.sort();
+        templates.into_iter()
+                 .map(|template| (template.clone(), self.0.render(template, ctx)))
+                 .collect()
+    }
 }
 
 // Recursively merges the `other` TOML table into `me`
@@ -1175,4 +1196,47 @@ mod test {
         assert_eq!(file_content(deep_output_dir.join("config.txt")),
                    "config message is Hello");
     }
+
+    #[tokio::test]
+    async fn render_config_files_with_gossiped_config() {
+        let root = TempDir::new().expect("create temp dir").into_path();
+
+        let pkg_dir = root.join("pkg/testing/test");
+        fs::create_dir_all(&pkg_dir).expect("create pkg dir");
+        let pg_id = PackageIdent::new("testing", "test", Some("1.0.0"), Some("20170712000000"));
+        let pkg_install = PackageInstall::new_from_parts(pg_id,
+                                                         pkg_dir.clone(),
+                                                         pkg_dir.clone(),
+                                                         pkg_dir.clone());
+        create_with_content(pkg_dir.join("default.toml"), "message = \"Hello\"");
+
+        let config_dir = pkg_dir.join("config");
+        fs::create_dir_all(config_dir.join("dir_a")).expect("create config/dir_a");
+        create_with_content(config_dir.join("dir_a").join("config.txt"),
+                            "config message is {{cfg.message}}");
+        create_with_content(config_dir.join("app.conf"), "message = {{cfg.message}}");
+
+        // Platforms without standard package support require all packages to be native packages
+        #[cfg(not(any(all(target_os = "linux", any(target_arch = "x86_64")),
+                      all(target_os = "windows", target_arch = "x86_64"))))]
+        {
+            create_with_content(pkg_dir.join(MetaFile::PackageType.to_string()), "native");
+        }
+
+        let pkg = Pkg::from_install(&pkg_install).await.unwrap();
+        let mut cfg = Cfg::new(&pkg, None).unwrap();
+        cfg.set_gossip(1, toml::from_str("message = \"Goodbye\"").unwrap());
+        let ctx = RenderContext::new(&pkg, &cfg);
+
+        let renderer = CfgRenderer::new(&config_dir).expect("create cfg renderer");
+        let rendered = renderer.render(&ctx)
+                               .into_iter()
+                               .map(|(template, content)| (template, content.expect("render")))
+                               .collect::<Vec<_>>();
+        let config_txt = Path::new("dir_a").join("config.txt");
+        assert_eq!(rendered,
+                   vec![("app.conf".to_string(), "message = Goodbye".to_string()),
+                        (config_txt.to_string_lossy().into_owned(),
+                         "config message is Goodbye".to_string()),]);
+    }
 }
@@ -9,7 +9,9 @@ mod to_toml;
 mod to_uppercase;
 mod to_yaml;
 
-pub use self::{each_alive::EACH_ALIVE,
+pub use self::{each_alive::{EACH_ALIVE,
+                            EACH_HEALTHY,
+                            EACH_READY},
                pkg_path_for::PKG_PATH_FOR,
                str_concat::STR_CONCAT,
                str_join::STR_JOIN,
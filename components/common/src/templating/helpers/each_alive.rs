@@ -7,16 +7,39 @@ use handlebars::{Handlebars,
                  RenderContext,
                  RenderError,
                  Renderable};
-use serde_json::Value as Json;
+use serde_json::{Map,
+                 Value as Json};
 use std::collections::BTreeMap;
 
+/// Iterates over the census members that have all of the given
+/// flags set, e.g. only the `alive` ones, and whose latest health
+/// check result isn't one of those skipped. Members that have been
+/// drained are always skipped.
 #[derive(Clone, Copy)]
-pub struct EachAliveHelper;
+pub struct EachAliveHelper {
+    name:           &'static str,
+    flags:          &'static [&'static str],
+    skipped_health: &'static [&'static str],
+}
+
+impl EachAliveHelper {
+    fn selects(&self, member: &Map<String, Json>) -> bool {
+        let flag = |name: &str| member.get(name).and_then(Json::as_bool).unwrap_or(false);
+        let healthy = member.get("health")
+                            .and_then(Json::as_str)
+                            .map_or(true, |health| {
+                                !self.skipped_health.iter().any(|skipped| *skipped == health)
+                            });
+        self.flags.iter().all(|name| flag(name)) && !flag("draining") && healthy
+    }
+}
 
 impl HelperDef for EachAliveHelper {
     fn call(&self, h: &Helper<'_>, r: &Handlebars, rc: &mut RenderContext<'_>) -> RenderResult<()> {
         let value = h.param(0)
-                     .ok_or_else(|| RenderError::new("Param not found for helper \"eachAlive\""))?;
+                     .ok_or_else(|| {
+                         RenderError::new(format!("Param not found for helper \"{}\"", self.name))
+                     })?;
         if let Some(template) = h.template() {
             rc.promote_local_vars();
             let local_path_root = value.path_root()
@@ -24,15 +47,9 @@ impl HelperDef for EachAliveHelper {
             let rendered = match (value.value().is_truthy(), value.value()) {
                 (true, Json::Array(list)) => {
                     let alive_members: Vec<Json> = list.iter()
-                                                       .filter_map(|m| {
-                                                           m.as_object().and_then(|m| {
-                                if m.contains_key("alive") && m["alive"].as_bool().unwrap() {
-                                    Some(to_json(&m))
-                                } else {
-                                    None
-                                }
-                            })
-                                                       })
+                                                       .filter_map(Json::as_object)
+                                                       .filter(|m| self.selects(m))
+                                                       .map(to_json)
                                                        .collect();
                     let len = alive_members.len();
                     for (i, alive_member) in alive_members.iter().enumerate() {
@@ -57,7 +74,7 @@ impl HelperDef for EachAliveHelper {
                 }
                 (true, Json::Object(obj)) => {
                     let mut first: bool = true;
-                    if !obj.contains_key("alive") || !obj["alive"].as_bool().unwrap() {
+                    if !self.selects(obj) {
                         return Ok(());
                     }
                     for k in obj.keys() {
@@ -117,4 +134,18 @@ impl HelperDef for EachAliveHelper {
     }
 }
 
-pub static EACH_ALIVE: EachAliveHelper = EachAliveHelper;
+pub static EACH_ALIVE: EachAliveHelper = EachAliveHelper { name:           "eachAlive",
+                                                           flags:          &["alive"],
+                                                           skipped_health: &[], };
+
+/// Like `eachAlive`, but skips members that are not yet accepting
+/// traffic.
+pub static EACH_READY: EachAliveHelper = EachAliveHelper { name:           "eachReady",
+                                                           flags:          &["alive", "ready"],
+                                                           skipped_health: &[], };
+
+/// Like `eachAlive`, but skips members whose own health check is
+/// critical. Members whose health isn't known yet are kept.
+pub static EACH_HEALTHY: EachAliveHelper = EachAliveHelper { name:           "eachHealthy",
+                                                             flags:          &["alive"],
+                                                             skipped_health: &["Critical"], };
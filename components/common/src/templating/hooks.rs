@@ -31,7 +31,11 @@ use std::{ffi::OsStr,
                BufReader},
           path::{Path,
                  PathBuf},
-          result};
+          result,
+          sync::mpsc::{self,
+                       RecvTimeoutError},
+          thread,
+          time::Duration};
 
 #[cfg(not(windows))]
 pub const HOOK_PERMISSIONS: u32 = 0o755;
@@ -52,6 +56,18 @@ pub fn stderr_log_path<T>(package_name: &str) -> PathBuf
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct ExitCode(pub i32);
 
+/// A finished run of a hook.
+#[derive(Debug)]
+pub struct HookRun<V> {
+    /// What the hook made of its exit status.
+    pub value:     V,
+    /// The exit code of the hook's process, unset if it was terminated
+    /// by a signal.
+    pub exit_code: Option<i32>,
+    /// Set if the hook was killed for running past its timeout.
+    pub timed_out: bool,
+}
+
 impl Default for ExitCode {
     fn default() -> ExitCode { ExitCode(-1) }
 }
@@ -201,14 +217,29 @@ pub trait Hook: fmt::Debug + Sized + Send {
               -> Result<Self::ExitValue>
         where T: ToString
     {
-        self.run_impl(service_group, pkg, svc_encrypted_password)
+        self.run_with_timeout(service_group, pkg, svc_encrypted_password, None)
+            .map(|run| run.value)
+    }
+
+    /// Run a compiled hook, killing it along with any processes it
+    /// started if it is still running after `timeout`.
+    fn run_with_timeout<T>(&self,
+                           service_group: &str,
+                           pkg: &Pkg,
+                           svc_encrypted_password: Option<T>,
+                           timeout: Option<Duration>)
+                           -> Result<HookRun<Self::ExitValue>>
+        where T: ToString
+    {
+        self.run_impl(service_group, pkg, svc_encrypted_password, timeout)
     }
 
     fn run_impl<T>(&self,
                    service_group: &str,
                    pkg: &Pkg,
-                   svc_encrypted_password: Option<T>)
-                   -> Result<Self::ExitValue>
+                   svc_encrypted_password: Option<T>,
+                   timeout: Option<Duration>)
+                   -> Result<HookRun<Self::ExitValue>>
         where T: ToString
     {
         let mut child = Self::exec(self.path(), pkg, svc_encrypted_password).map_err(|err| {
@@ -216,15 +247,20 @@ pub trait Hook: fmt::Debug + Sized + Send {
                                       "Hook failed to run, {}, {}", Self::FILE_NAME, err);
                             err
                         })?;
+        let watchdog =
+            timeout.map(|timeout| Watchdog::start::<Self>(service_group, child.id(), timeout));
         let mut hook_output = HookOutput::new(self.stdout_log_path(), self.stderr_log_path());
         hook_output.output_standard_streams::<Self>(service_group, &mut child);
-        Ok(child.wait()
-                .map_err(|err| {
-                    outputln!(preamble service_group,
-                              "Hook failed to run, {}, {}", Self::FILE_NAME, err);
-                    err
-                })
-                .map(|status| self.handle_exit(pkg, &hook_output, status))?)
+        let status = child.wait().map_err(|err| {
+                                      outputln!(preamble service_group,
+                                               "Hook failed to run, {}, {}", Self::FILE_NAME, err);
+                                      err
+                                  })?;
+        let timed_out = watchdog.map_or(false, Watchdog::finish);
+        let exit_code = status.code();
+        Ok(HookRun { value: self.handle_exit(pkg, &hook_output, status),
+                     exit_code,
+                     timed_out })
     }
 
     #[cfg(windows)]
@@ -246,29 +282,16 @@ pub trait Hook: fmt::Debug + Sized + Send {
         where T: ToString,
               S: AsRef<OsStr>
     {
-        use habitat_core::os::{process,
-                               users};
-        use nix::unistd::{Gid,
-                          Uid};
+        use habitat_core::os::process;
         use std::ops::Deref;
 
-        let ids = if process::can_run_services_as_svc_user() {
-            // If we can SETUID/SETGID, then run the script as the service
-            // user; otherwise, we'll just run it as ourselves.
-            let uid = users::get_uid_by_name(&pkg.svc_user)?
-                .map(Uid::from_raw)
-                .ok_or_else(|| {Error::PermissionFailed(format!("No uid for user '{}' could be found", &pkg.svc_user))})?;
-            let gid = users::get_gid_by_name(&pkg.svc_group)?
-                .map(Gid::from_raw)
-                .ok_or_else(|| {Error::PermissionFailed(format!("No gid for group '{}' could be found", &pkg.svc_group))})?;
-            Some((uid, gid))
-        } else {
+        let ids = svc_user_ids(pkg)?;
+        if ids.is_none() {
             debug!("Current user lacks sufficient capabilites to run {:?} as \"{}\"; running as \
                     self!",
                    path.as_ref(),
                    &pkg.svc_user);
-            None
-        };
+        }
 
         let mut cmd = process::exec::unix::hook_command(path, pkg.env.deref(), ids);
         Ok(cmd.spawn()?)
@@ -288,6 +311,80 @@ pub trait Hook: fmt::Debug + Sized + Send {
     fn stderr_log_path(&self) -> &Path;
 }
 
+/// The user and group to run a service's hooks and commands as. If we can SETUID/SETGID, they
+/// run as the service user; otherwise, `None` is returned and they run as ourselves.
+#[cfg(unix)]
+pub fn svc_user_ids(pkg: &Pkg) -> Result<Option<(nix::unistd::Uid, nix::unistd::Gid)>> {
+    use habitat_core::os::{process,
+                           users};
+    use nix::unistd::{Gid,
+                      Uid};
+
+    if !process::can_run_services_as_svc_user() {
+        return Ok(None);
+    }
+    let uid = users::get_uid_by_name(&pkg.svc_user)?.ok_or_else(|| {
+                  Error::PermissionFailed(format!("No uid for user '{}' could be found",
+                                                  pkg.svc_user))
+              })?;
+    let gid = users::get_gid_by_name(&pkg.svc_group)?.ok_or_else(|| {
+                  Error::PermissionFailed(format!("No gid for group '{}' could be found",
+                                                  pkg.svc_group))
+              })?;
+    Ok(Some((Uid::from_raw(uid), Gid::from_raw(gid))))
+}
+
+/// Kills a hook that is still running after its timeout.
+struct Watchdog {
+    done:   mpsc::Sender<()>,
+    thread: thread::JoinHandle<bool>,
+}
+
+impl Watchdog {
+    fn start<H: Hook>(service_group: &str, pid: u32, timeout: Duration) -> Self {
+        let (done, rx) = mpsc::channel();
+        let service_group = service_group.to_string();
+        let thread = thread::spawn(move || {
+            match rx.recv_timeout(timeout) {
+                Err(RecvTimeoutError::Timeout) => {
+                    outputln!(preamble service_group,
+                              "{} hook still running after {} seconds; killing it",
+                              H::FILE_NAME,
+                              timeout.as_secs());
+                    if let Err(err) = kill_hook(pid) {
+                        error!("Failed to kill {} hook, {}", H::FILE_NAME, err);
+                    }
+                    true
+                }
+                _ => false,
+            }
+        });
+        Self { done, thread }
+    }
+
+    /// Stop watching a hook that has exited. Returns `true` if the hook
+    /// was killed.
+    fn finish(self) -> bool {
+        drop(self.done);
+        self.thread.join().unwrap_or(false)
+    }
+}
+
+/// Hooks lead their own process group, so this takes down any
+/// processes they started along with them.
+#[cfg(unix)]
+fn kill_hook(pid: u32) -> habitat_core::error::Result<()> {
+    use habitat_core::os::process::{signal,
+                                    Signal};
+
+    signal(-(pid as libc::pid_t), Signal::KILL)
+}
+
+#[cfg(windows)]
+fn kill_hook(pid: u32) -> habitat_core::error::Result<()> {
+    habitat_core::os::process::terminate(pid)
+}
+
 /// A trait that adds a convenient method for executing one-off hooks
 ///
 /// This trait unifies the logic the `install` and `uninstall` hooks use to execute. These hooks
@@ -1,12 +1,15 @@
 use crate::{error::{Error,
                     Result},
             hcore::{fs,
-                    os::{process::{ShutdownSignal,
-                                   ShutdownTimeout},
+                    os::{process::{ProcessControls,
+                                   ShutdownSignal,
+                                   ShutdownTimeout,
+                                   Signal},
                          users},
                     package::{FullyQualifiedPackageIdent,
                               PackageIdent,
                               PackageInstall},
+                    service::HealthProbe,
                     util},
             util::path};
 use habitat_core::package::metadata::PackageType;
@@ -38,6 +41,10 @@ impl From<BTreeMap<String, String>> for Env {
     fn from(inner_map: BTreeMap<String, String>) -> Self { Env(inner_map) }
 }
 
+impl Extend<(String, String)> for Env {
+    fn extend<T: IntoIterator<Item = (String, String)>>(&mut self, iter: T) { self.0.extend(iter) }
+}
+
 impl Env {
     /// Modifies PATH env with the full run path for this package. This path is composed of any
     /// binary paths specified by this package, or its TDEPS, plus a path to a BusyBox(non-windows),
@@ -92,22 +99,40 @@ pub struct Pkg {
     pub svc_group:               String,
     pub shutdown_signal:         ShutdownSignal,
     pub shutdown_timeout:        ShutdownTimeout,
+    /// The package's default rlimits, scheduling priorities, and CPU affinity
+    #[serde(default)]
+    pub process_controls:        ProcessControls,
+    /// The signal that makes the service reload its configuration, if the package declares one
+    pub reload_signal:           Option<Signal>,
+    /// How long each hook may run, in seconds, keyed by the hook's file name
+    #[serde(default)]
+    pub hook_timeouts:           BTreeMap<String, u64>,
+    /// The built-in probes the package declares for checking its service's health
+    #[serde(default)]
+    pub health_probes:           Vec<HealthProbe>,
 }
 
 impl Pkg {
     pub async fn from_install(package: &PackageInstall) -> Result<Self> {
+        Self::from_install_for_service(package, &package.ident.name).await
+    }
+
+    /// Like `from_install`, but the svc paths are those of the service named `service_name`,
+    /// which differs from the package name for named instances of a service.
+    pub async fn from_install_for_service(package: &PackageInstall,
+                                          service_name: &str)
+                                          -> Result<Self> {
         let ident = FullyQualifiedPackageIdent::try_from(&package.ident)?;
         let (svc_user, svc_group) = get_user_and_group(package)?;
-        let pkg = Pkg { svc_path: fs::svc_path(&package.ident.name),
-                        svc_config_path: fs::svc_config_path(&package.ident.name),
-                        svc_config_install_path: fs::svc_config_install_path(&package.ident
-                                                                                     .name),
-                        svc_data_path: fs::svc_data_path(&package.ident.name),
-                        svc_files_path: fs::svc_files_path(&package.ident.name),
-                        svc_run: fs::svc_path(&package.ident.name).join("run"),
-                        svc_static_path: fs::svc_static_path(&package.ident.name),
-                        svc_var_path: fs::svc_var_path(&package.ident.name),
-                        svc_pid_file: fs::svc_pid_file(&package.ident.name),
+        let pkg = Pkg { svc_path: fs::svc_path(service_name),
+                        svc_config_path: fs::svc_config_path(service_name),
+                        svc_config_install_path: fs::svc_config_install_path(service_name),
+                        svc_data_path: fs::svc_data_path(service_name),
+                        svc_files_path: fs::svc_files_path(service_name),
+                        svc_run: fs::svc_path(service_name).join("run"),
+                        svc_static_path: fs::svc_static_path(service_name),
+                        svc_var_path: fs::svc_var_path(service_name),
+                        svc_pid_file: fs::svc_pid_file(service_name),
                         svc_user,
                         svc_group,
                         env: Env::new(package).await?,
@@ -121,6 +146,10 @@ impl Pkg {
                         release: String::from(ident.release()),
                         shutdown_signal: package.shutdown_signal()?.unwrap_or_default(),
                         shutdown_timeout: package.shutdown_timeout()?.unwrap_or_default(),
+                        process_controls: package.process_controls()?,
+                        reload_signal: package.reload_signal()?,
+                        hook_timeouts: package.hook_timeouts()?,
+                        health_probes: package.health_probes()?,
                         ident };
         Ok(pkg)
     }
@@ -82,6 +82,22 @@ pub enum Error {
     FullyQualifiedPackageIdentRequired(String),
     /// Occurs when a service binding cannot be successfully parsed.
     InvalidBinding(String),
+    /// Occurs when a canary size cannot be successfully parsed.
+    InvalidCanarySize(String),
+    /// Occurs when a CPU list cannot be successfully parsed.
+    InvalidCpuAffinity(String),
+    /// Occurs when a health probe cannot be successfully parsed.
+    InvalidHealthProbe(String),
+    /// Occurs when the name of a service instance is in an invalid format.
+    InvalidInstanceName(String),
+    /// Occurs when an IO scheduling priority is out of range.
+    InvalidIonice(String),
+    /// Occurs when a job schedule cannot be successfully parsed.
+    InvalidJobSchedule(String),
+    /// Occurs when a maintenance window cannot be successfully parsed.
+    InvalidMaintenanceWindow(String),
+    /// Occurs when a scheduling priority is out of range.
+    InvalidNice(String),
     /// Occurs when an origin is in an invalid format
     InvalidOrigin(String),
     /// Occurs when a package identifier string cannot be successfully parsed.
@@ -98,6 +114,8 @@ pub enum Error {
     InvalidServiceGroup(String),
     /// Occurs when a Url is in an invalid format.
     InvalidUrl(String),
+    /// Occurs when a version constraint cannot be successfully parsed.
+    InvalidVersionConstraint(String),
     /// Occurs when making lower level IO calls.
     IO(io::Error),
     /// Errors when joining paths :)
@@ -275,6 +293,50 @@ impl fmt::Display for Error {
                          <NAME> is a service name, and <SERVICE_GROUP> is a valid service group",
                         binding)
             }
+            Error::InvalidCanarySize(ref size) => {
+                format!("Invalid canary size '{}', must be a number of members or a percentage of \
+                         the group between 1% and 100% (example: 2 or 10%)",
+                        size)
+            }
+            Error::InvalidCpuAffinity(ref cpus) => {
+                format!("Invalid CPU list '{}', must be a comma separated list of CPU numbers or \
+                         ranges between 0 and 63 (example: 0-3,8)",
+                        cpus)
+            }
+            Error::InvalidHealthProbe(ref probe) => {
+                format!("Invalid health probe '{}', must be an http:// URL and an optional \
+                         expected status, a tcp:// address, or exec: followed by a command \
+                         (example: \"http://localhost:8080/healthz 200\")",
+                        probe)
+            }
+            Error::InvalidInstanceName(ref name) => {
+                format!("Invalid instance name '{}', a valid instance name contains a-z, A-Z, \
+                         0-9, _ and - (example: cache)",
+                        name)
+            }
+            Error::InvalidIonice(ref ionice) => {
+                format!("Invalid IO scheduling priority '{}', must be a number between 0 \
+                         (highest) and 7 (lowest)",
+                        ionice)
+            }
+            Error::InvalidJobSchedule(ref schedule) => {
+                format!("Invalid job schedule '{}', must be the minute, hour, day of the month, \
+                         month and day of the week it runs at and an optional time zone (example: \
+                         \"30 2 * * Mon-Fri UTC\")",
+                        schedule)
+            }
+            Error::InvalidMaintenanceWindow(ref window) => {
+                format!("Invalid maintenance window '{}', must be the days it opens on, a time \
+                         range and an optional time zone (example: \"Sat,Sun 01:00-05:00 UTC\"), \
+                         or a cron expression of when it opens followed by how long it stays open \
+                         (example: \"0 1 * * Sat for 4h\")",
+                        window)
+            }
+            Error::InvalidNice(ref nice) => {
+                format!("Invalid scheduling priority '{}', must be a number between -20 (highest) \
+                         and 19 (lowest)",
+                        nice)
+            }
             Error::InvalidOrigin(ref origin) => {
                 format!("Invalid origin: {}. Origins must begin with a lowercase letter or \
                          number. Allowed characters include lowercase letters, numbers, -, and _. \
@@ -302,6 +364,11 @@ impl fmt::Display for Error {
                         e)
             }
             Error::InvalidUrl(ref url) => format!("Invalid url: {}", url),
+            Error::InvalidVersionConstraint(ref constraint) => {
+                format!("Invalid version constraint '{}', must be a version prefix or a \
+                         comma-separated list of comparisons (example: \">=1.2, <2.0\")",
+                        constraint)
+            }
             Error::IO(ref err) => format!("{}", err),
             Error::JoinPathsError(ref err) => format!("{}", err),
             Error::LogonTypeNotGranted => {
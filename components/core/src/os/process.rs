@@ -32,9 +32,13 @@ pub use windows::{handle_from_pid,
 
 use crate::{error::Error,
             util};
-use serde::{Deserialize,
+use serde::{de,
+            Deserialize,
+            Deserializer,
             Serialize};
-use std::{fmt,
+use std::{convert::TryFrom,
+          fmt,
+          ops::RangeInclusive,
           result,
           str::FromStr,
           time::Duration};
@@ -77,7 +81,7 @@ impl From<ShutdownTimeout> for Duration {
 // but we are making it available on Windows as well for situations
 // where a Windows CLI is communicating with a Linux Supervisor.
 #[allow(clippy::upper_case_acronyms)]
-#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Copy)]
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Copy, Hash)]
 pub enum Signal {
     INT,
     ILL,
@@ -169,6 +173,216 @@ impl From<ShutdownSignal> for Signal {
     fn from(shutdown_signal: ShutdownSignal) -> Self { shutdown_signal.0 }
 }
 
+/// Resource limits applied to a service's process tree.
+///
+/// On Linux these are enforced by the Launcher through a cgroup v2
+/// cgroup created for each service. Any limit that is `None` is left
+/// unset (i.e., "max"). Other platforms ignore these values.
+#[derive(Deserialize,
+         Serialize,
+         Eq,
+         PartialEq,
+         Debug,
+         Clone,
+         Copy,
+         Default,
+         Hash)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Hard memory limit in bytes (`memory.max`).
+    pub memory_max: Option<u64>,
+    /// CPU bandwidth limit in millicpus, where 1000 is one full CPU
+    /// (`cpu.max`).
+    pub cpu_max:    Option<u32>,
+    /// Maximum number of processes and threads (`pids.max`).
+    pub pids_max:   Option<u64>,
+    /// Proportional IO weight between 1 and 10000 (`io.weight`).
+    pub io_weight:  Option<u32>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool { *self == Self::default() }
+}
+
+/// POSIX resource limits, scheduling priorities and CPU affinity applied
+/// to a service's process by the Launcher before it drops privileges.
+///
+/// Packages may declare defaults for any of these in their plan; values
+/// given to `hab svc load` take precedence. Anything left as `None` is
+/// inherited from the Launcher. Other platforms ignore these values.
+#[derive(Deserialize,
+         Serialize,
+         Eq,
+         PartialEq,
+         Debug,
+         Clone,
+         Copy,
+         Default,
+         Hash)]
+#[serde(default)]
+pub struct ProcessControls {
+    /// Maximum number of open file descriptors (`RLIMIT_NOFILE`).
+    pub nofile:       Option<u64>,
+    /// Maximum number of processes for the service user (`RLIMIT_NPROC`).
+    pub nproc:        Option<u64>,
+    /// Maximum size of a core file in bytes (`RLIMIT_CORE`).
+    pub core:         Option<u64>,
+    /// Maximum amount of locked memory in bytes (`RLIMIT_MEMLOCK`).
+    pub memlock:      Option<u64>,
+    /// Scheduling priority, from -20 (highest) to 19 (lowest).
+    #[serde(deserialize_with = "deserialize_nice")]
+    pub nice:         Option<i32>,
+    /// Best-effort IO scheduling priority, from 0 (highest) to 7 (lowest).
+    #[serde(deserialize_with = "deserialize_ionice")]
+    pub ionice:       Option<u32>,
+    /// The CPUs the service may run on.
+    pub cpu_affinity: Option<CpuAffinity>,
+}
+
+impl ProcessControls {
+    /// The best-effort IO scheduling priorities a service may be given.
+    pub const IONICE_RANGE: RangeInclusive<u32> = 0..=7;
+    /// The scheduling priorities a service may be given.
+    pub const NICE_RANGE: RangeInclusive<i32> = -20..=19;
+
+    pub fn is_empty(&self) -> bool { *self == Self::default() }
+
+    /// Check that the scheduling priorities are in range.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.nice.map(validate_nice).transpose()?;
+        self.ionice.map(validate_ionice).transpose()?;
+        Ok(())
+    }
+
+    /// Fill in any value not set here from `defaults`.
+    pub fn or(self, defaults: ProcessControls) -> Self {
+        ProcessControls { nofile:       self.nofile.or(defaults.nofile),
+                          nproc:        self.nproc.or(defaults.nproc),
+                          core:         self.core.or(defaults.core),
+                          memlock:      self.memlock.or(defaults.memlock),
+                          nice:         self.nice.or(defaults.nice),
+                          ionice:       self.ionice.or(defaults.ionice),
+                          cpu_affinity: self.cpu_affinity.or(defaults.cpu_affinity), }
+    }
+}
+
+/// Check that `nice` is within `ProcessControls::NICE_RANGE`.
+pub fn validate_nice(nice: i32) -> Result<(), Error> {
+    if ProcessControls::NICE_RANGE.contains(&nice) {
+        Ok(())
+    } else {
+        Err(Error::InvalidNice(nice.to_string()))
+    }
+}
+
+/// Check that `ionice` is within `ProcessControls::IONICE_RANGE`.
+pub fn validate_ionice(ionice: u32) -> Result<(), Error> {
+    if ProcessControls::IONICE_RANGE.contains(&ionice) {
+        Ok(())
+    } else {
+        Err(Error::InvalidIonice(ionice.to_string()))
+    }
+}
+
+/// Deserialize a scheduling priority, rejecting one out of range.
+fn deserialize_nice<'de, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+    where D: Deserializer<'de>
+{
+    let nice = Option::deserialize(deserializer)?;
+    if let Some(nice) = nice {
+        validate_nice(nice).map_err(de::Error::custom)?;
+    }
+    Ok(nice)
+}
+
+/// Deserialize an IO scheduling priority, rejecting one out of range.
+fn deserialize_ionice<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+    where D: Deserializer<'de>
+{
+    let ionice = Option::deserialize(deserializer)?;
+    if let Some(ionice) = ionice {
+        validate_ionice(ionice).map_err(de::Error::custom)?;
+    }
+    Ok(ionice)
+}
+
+/// A set of CPUs, stored as a mask where bit N selects CPU N.
+///
+/// Renders as, and parses from, the CPU list format used by `taskset
+/// -c` and cpusets (e.g. "0-3,8").
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Copy, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct CpuAffinity(u64);
+
+impl CpuAffinity {
+    /// The number of CPUs that can be addressed.
+    pub const MAX_CPUS: u32 = u64::BITS;
+
+    pub fn mask(self) -> u64 { self.0 }
+
+    pub fn contains(self, cpu: u32) -> bool { cpu < Self::MAX_CPUS && self.0 & (1 << cpu) != 0 }
+}
+
+impl FromStr for CpuAffinity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let invalid = || Error::InvalidCpuAffinity(s.to_string());
+        let mut mask = 0u64;
+        for item in s.split(',').map(str::trim) {
+            let (first, last) = match item.split_once('-') {
+                Some((first, last)) => (first.trim(), last.trim()),
+                None => (item, item),
+            };
+            let first: u32 = first.parse().map_err(|_| invalid())?;
+            let last: u32 = last.parse().map_err(|_| invalid())?;
+            if first > last || last >= Self::MAX_CPUS {
+                return Err(invalid());
+            }
+            for cpu in first..=last {
+                mask |= 1 << cpu;
+            }
+        }
+        Ok(CpuAffinity(mask))
+    }
+}
+
+impl fmt::Display for CpuAffinity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut ranges = Vec::new();
+        let mut cpu = 0;
+        while cpu < Self::MAX_CPUS {
+            if self.contains(cpu) {
+                let first = cpu;
+                while self.contains(cpu + 1) {
+                    cpu += 1;
+                }
+                if first == cpu {
+                    ranges.push(first.to_string());
+                } else {
+                    ranges.push(format!("{}-{}", first, cpu));
+                }
+            }
+            cpu += 1;
+        }
+        write!(f, "{}", ranges.join(","))
+    }
+}
+
+impl From<u64> for CpuAffinity {
+    fn from(mask: u64) -> Self { CpuAffinity(mask) }
+}
+
+impl TryFrom<String> for CpuAffinity {
+    type Error = Error;
+
+    fn try_from(s: String) -> result::Result<Self, Self::Error> { s.parse() }
+}
+
+impl From<CpuAffinity> for String {
+    fn from(affinity: CpuAffinity) -> Self { affinity.to_string() }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -205,4 +419,59 @@ mod test {
                              .expect("Couldn't parse back into a Signal!"));
         }
     }
+
+    #[test]
+    fn cpu_affinity_parses_cpu_lists() {
+        assert_eq!(CpuAffinity::from(0b1_0000_1111),
+                   "0-3,8".parse::<CpuAffinity>().unwrap());
+        assert_eq!(CpuAffinity::from(1 << 63),
+                   "63".parse::<CpuAffinity>().unwrap());
+        assert!("3-1".parse::<CpuAffinity>().is_err());
+        assert!("64".parse::<CpuAffinity>().is_err());
+        assert!("".parse::<CpuAffinity>().is_err());
+        assert!("one".parse::<CpuAffinity>().is_err());
+    }
+
+    #[test]
+    fn cpu_affinity_renders_as_cpu_list() {
+        assert_eq!("0-3,8", CpuAffinity::from(0b1_0000_1111).to_string());
+        assert_eq!("1,3,5", CpuAffinity::from(0b10_1010).to_string());
+        assert_eq!("63", CpuAffinity::from(1 << 63).to_string());
+    }
+
+    #[test]
+    fn scheduling_priorities_must_be_in_range() {
+        assert!(validate_nice(-20).is_ok());
+        assert!(validate_nice(19).is_ok());
+        assert!(validate_nice(-21).is_err());
+        assert!(validate_nice(20).is_err());
+        assert!(validate_ionice(0).is_ok());
+        assert!(validate_ionice(7).is_ok());
+        assert!(validate_ionice(8).is_err());
+        assert!(validate_ionice(u32::MAX).is_err());
+    }
+
+    #[test]
+    fn process_controls_reject_out_of_range_priorities() {
+        let controls: ProcessControls = toml::from_str("nice = -5\nionice = 7").unwrap();
+        assert_eq!(controls,
+                   ProcessControls { nice: Some(-5),
+                                     ionice: Some(7),
+                                     ..Default::default() });
+        assert!(toml::from_str::<ProcessControls>("nice = 20").is_err());
+        assert!(toml::from_str::<ProcessControls>("ionice = 8").is_err());
+    }
+
+    #[test]
+    fn process_controls_fall_back_to_defaults() {
+        let defaults = ProcessControls { nofile: Some(1024),
+                                         nice: Some(5),
+                                         ..Default::default() };
+        let overrides = ProcessControls { nofile: Some(65536),
+                                          ..Default::default() };
+        assert_eq!(overrides.or(defaults),
+                   ProcessControls { nofile: Some(65536),
+                                     nice: Some(5),
+                                     ..Default::default() });
+    }
 }
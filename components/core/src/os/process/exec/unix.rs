@@ -1,4 +1,5 @@
-use crate::os::process::can_run_services_as_svc_user;
+use crate::os::process::{can_run_services_as_svc_user,
+                         ProcessControls};
 #[cfg(not(target_os = "macos"))]
 use log::warn;
 use nix::{sys::signal::{pthread_sigmask,
@@ -24,6 +25,31 @@ pub fn hook_command<X, I, K, V>(executable: X, env: I, ids: Option<(Uid, Gid)>)
           I: IntoIterator<Item = (K, V)>,
           K: AsRef<OsStr>,
           V: AsRef<OsStr>
+{
+    service_command(executable, env, ids, ProcessControls::default(), || Ok(()))
+}
+
+/// Prepare a `Command` to execute a service's `run` hook.
+///
+/// This is the same as `hook_command`, but additionally runs `setup`
+/// and applies `controls` in the child process after it has been
+/// placed in its own process group and *before* it drops privileges to
+/// the service user, so that limits may be raised as well as lowered.
+///
+/// Since `setup` runs between `fork` and `exec`, it must restrict
+/// itself to async-signal-safe operations (in particular, it must not
+/// allocate).
+pub fn service_command<X, I, K, V, F>(executable: X,
+                                      env: I,
+                                      ids: Option<(Uid, Gid)>,
+                                      controls: ProcessControls,
+                                      setup: F)
+                                      -> Command
+    where X: AsRef<OsStr>,
+          I: IntoIterator<Item = (K, V)>,
+          K: AsRef<OsStr>,
+          V: AsRef<OsStr>,
+          F: FnMut() -> io::Result<()> + Send + Sync + 'static
 {
     let mut cmd = Command::new(executable);
 
@@ -36,6 +62,12 @@ pub fn hook_command<X, I, K, V>(executable: X, env: I, ids: Option<(Uid, Gid)>)
        .envs(env);
 
     with_own_process_group(&mut cmd);
+    unsafe {
+        cmd.pre_exec(setup);
+        if !controls.is_empty() {
+            cmd.pre_exec(move || apply_process_controls(&controls));
+        }
+    }
     if let Some((uid, gid)) = ids {
         with_user_and_group_information(&mut cmd, uid, gid);
     }
@@ -88,6 +120,70 @@ fn set_own_process_group() -> result::Result<(), io::Error> {
         }
     }
 }
+/// Apply rlimits, scheduling priorities, and CPU affinity to the
+/// calling process.
+///
+/// Intended for use in a
+/// `std::os::unix::process::CommandExt::pre_exec` callback, so this
+/// only makes system calls and does not allocate.
+fn apply_process_controls(controls: &ProcessControls) -> result::Result<(), io::Error> {
+    let rlimits = [(libc::RLIMIT_NOFILE, controls.nofile),
+                   (libc::RLIMIT_NPROC, controls.nproc),
+                   (libc::RLIMIT_CORE, controls.core),
+                   (libc::RLIMIT_MEMLOCK, controls.memlock)];
+    for (resource, limit) in rlimits.iter() {
+        if let Some(limit) = limit {
+            let limit = libc::rlimit { rlim_cur: *limit as libc::rlim_t,
+                                       rlim_max: *limit as libc::rlim_t, };
+            if unsafe { libc::setrlimit(*resource, &limit) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+    }
+
+    if let Some(nice) = controls.nice {
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    // IO priorities and CPU affinity are Linux-specific; they are
+    // silently ignored elsewhere.
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(level) = controls.ionice {
+            // See ioprio_set(2); these constants are not exposed by libc.
+            const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+            const IOPRIO_CLASS_BE: libc::c_int = 2;
+            const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+            // A level out of range would spill over into the class bits.
+            if !ProcessControls::IONICE_RANGE.contains(&level) {
+                return Err(io::ErrorKind::InvalidInput.into());
+            }
+            let ioprio = (IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | level as libc::c_int;
+            if unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, ioprio) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        if let Some(affinity) = controls.cpu_affinity {
+            unsafe {
+                let mut set: libc::cpu_set_t = std::mem::zeroed();
+                for cpu in 0..crate::os::process::CpuAffinity::MAX_CPUS {
+                    if affinity.contains(cpu) {
+                        libc::CPU_SET(cpu as usize, &mut set);
+                    }
+                }
+                if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Sets uid, gid, and supplementary groups on command.
 ///
 /// DO NOT call `CommandExt#uid` or `CommandExt#gid` on this command,
@@ -0,0 +1,178 @@
+use crate::{error::{Error,
+                    Result},
+            package::ident::version_sort};
+use serde::{Deserialize,
+            Serialize};
+use std::{cmp::Ordering,
+          convert::TryFrom,
+          fmt,
+          result,
+          str::FromStr};
+
+/// Limits the versions of a package a service may be updated to.
+///
+/// A constraint is either a version prefix such as "1.2", which
+/// matches "1.2" and every "1.2.x" version, or a comma separated list
+/// of comparisons such as ">=1.2, <2.0" which must all hold.
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct VersionConstraint(Vec<Requirement>);
+
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+struct Requirement {
+    op:      Op,
+    version: String,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+enum Op {
+    Prefix,
+    Exact,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl Op {
+    // Longer operators come first so that ">=" is not taken for ">"
+    const ALL: [(&str, Op); 5] = [(">=", Op::GreaterOrEqual),
+                                  ("<=", Op::LessOrEqual),
+                                  (">", Op::Greater),
+                                  ("<", Op::Less),
+                                  ("=", Op::Exact)];
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Prefix => "",
+            Op::Exact => "=",
+            Op::Greater => ">",
+            Op::GreaterOrEqual => ">=",
+            Op::Less => "<",
+            Op::LessOrEqual => "<=",
+        }
+    }
+}
+
+impl VersionConstraint {
+    /// Returns `true` if `version` satisfies every requirement of the
+    /// constraint. Versions that cannot be compared never match.
+    pub fn matches(&self, version: &str) -> bool {
+        self.0
+            .iter()
+            .all(|requirement| requirement.matches(version))
+    }
+}
+
+impl Requirement {
+    fn matches(&self, version: &str) -> bool {
+        if self.op == Op::Prefix {
+            return version == self.version
+                   || version.strip_prefix(&self.version)
+                             .map_or(false, |rest| rest.starts_with('.'));
+        }
+        match version_sort(version, &self.version) {
+            Ok(ordering) => {
+                match self.op {
+                    Op::Prefix | Op::Exact => ordering == Ordering::Equal,
+                    Op::Greater => ordering == Ordering::Greater,
+                    Op::GreaterOrEqual => ordering != Ordering::Less,
+                    Op::Less => ordering == Ordering::Less,
+                    Op::LessOrEqual => ordering != Ordering::Greater,
+                }
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl FromStr for VersionConstraint {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidVersionConstraint(s.to_string());
+        let requirements = s.split(',')
+                            .map(|part| {
+                                let part = part.trim();
+                                let (op, version) =
+                                    Op::ALL.iter()
+                                           .find_map(|(symbol, op)| {
+                                               part.strip_prefix(symbol)
+                                                   .map(|version| (*op, version.trim()))
+                                           })
+                                           .unwrap_or((Op::Prefix, part));
+                                // Only accept versions that can be ordered
+                                if version.is_empty() || version_sort(version, version).is_err() {
+                                    return Err(invalid());
+                                }
+                                Ok(Requirement { op,
+                                                 version: version.to_string() })
+                            })
+                            .collect::<Result<Vec<_>>>()?;
+        // A prefix can't be combined with comparisons
+        if requirements.len() > 1 && requirements.iter().any(|r| r.op == Op::Prefix) {
+            return Err(invalid());
+        }
+        Ok(VersionConstraint(requirements))
+    }
+}
+
+impl fmt::Display for VersionConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let requirements = self.0
+                               .iter()
+                               .map(|r| format!("{}{}", r.op.symbol(), r.version))
+                               .collect::<Vec<_>>();
+        write!(f, "{}", requirements.join(", "))
+    }
+}
+
+impl TryFrom<String> for VersionConstraint {
+    type Error = Error;
+
+    fn try_from(s: String) -> result::Result<Self, Self::Error> { s.parse() }
+}
+
+impl From<VersionConstraint> for String {
+    fn from(constraint: VersionConstraint) -> Self { constraint.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_constraint_from_str() {
+        let constraint: VersionConstraint = "1.2".parse().unwrap();
+        assert_eq!(constraint.to_string(), "1.2");
+        let constraint: VersionConstraint = ">=1.2,<2.0".parse().unwrap();
+        assert_eq!(constraint.to_string(), ">=1.2, <2.0");
+        let constraint: VersionConstraint = " = 1.4.2 ".parse().unwrap();
+        assert_eq!(constraint.to_string(), "=1.4.2");
+
+        assert!("".parse::<VersionConstraint>().is_err());
+        assert!(">=".parse::<VersionConstraint>().is_err());
+        assert!("latest".parse::<VersionConstraint>().is_err());
+        assert!("1.2, <2.0".parse::<VersionConstraint>().is_err());
+    }
+
+    #[test]
+    fn version_constraint_matches() {
+        let prefix: VersionConstraint = "1.2".parse().unwrap();
+        assert!(prefix.matches("1.2"));
+        assert!(prefix.matches("1.2.7"));
+        assert!(!prefix.matches("1.20"));
+        assert!(!prefix.matches("1.3.0"));
+
+        let range: VersionConstraint = ">=1.2, <2.0".parse().unwrap();
+        assert!(range.matches("1.2"));
+        assert!(range.matches("1.10.3"));
+        assert!(!range.matches("1.1.9"));
+        assert!(!range.matches("2.0.0"));
+        assert!(!range.matches("master"));
+
+        let exact: VersionConstraint = "=1.4".parse().unwrap();
+        assert!(exact.matches("1.4.0"));
+        assert!(!exact.matches("1.4.1"));
+    }
+}
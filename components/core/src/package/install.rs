@@ -11,8 +11,12 @@ use super::{list::package_list_for_ident,
 use crate::{error::{Error,
                     Result},
             fs,
-            os::process::{ShutdownSignal,
-                          ShutdownTimeout}};
+            os::process::{self,
+                          ProcessControls,
+                          ShutdownSignal,
+                          ShutdownTimeout,
+                          Signal},
+            service::HealthProbe};
 use log::debug;
 use serde::{Deserialize,
             Serialize};
@@ -31,6 +35,18 @@ use toml::{self,
            Value};
 
 pub const DEFAULT_CFG_FILE: &str = "default.toml";
+/// The file names of the hooks that may be given a timeout. The `run` hook is the service itself
+/// and is bounded by the shutdown timeout instead.
+pub const TIMED_HOOKS: &[&str] = &["file-updated",
+                                   "health-check",
+                                   "init",
+                                   "post-run",
+                                   "post-stop",
+                                   "pre-stop",
+                                   "ready",
+                                   "reconfigure",
+                                   "reload",
+                                   "suitability"];
 const PATH_KEY: &str = "PATH";
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -590,6 +606,111 @@ impl PackageInstall {
         }
     }
 
+    /// Returns the signal that the package is specified to reload its configuration on
+    /// or None if the package doesn't contain a RELOAD_SIGNAL Metafile
+    pub fn reload_signal(&self) -> Result<Option<Signal>> {
+        self.parsed_metafile(MetaFile::ReloadSignal)
+    }
+
+    /// Returns the built-in probes the package declares for checking its
+    /// service's health, read from the `HEALTH_PROBES` Metafile, one
+    /// per line.
+    pub fn health_probes(&self) -> Result<Vec<HealthProbe>> {
+        let body = match self.read_metafile(MetaFile::HealthProbes) {
+            Ok(body) => body,
+            Err(Error::MetaFileNotFound(MetaFile::HealthProbes)) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        body.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                line.parse()
+                    .map_err(|_| Error::MetaFileMalformed(MetaFile::HealthProbes))
+            })
+            .collect()
+    }
+
+    /// Returns how long each of the package's hooks may run, in seconds,
+    /// read from the `HOOK_TIMEOUTS` Metafile. Hooks that aren't listed
+    /// have no timeout. Only the `TIMED_HOOKS` may be listed, with a
+    /// timeout of at least a second.
+    pub fn hook_timeouts(&self) -> Result<BTreeMap<String, u64>> {
+        let body = match self.read_metafile(MetaFile::HookTimeouts) {
+            Ok(body) => body,
+            Err(Error::MetaFileNotFound(MetaFile::HookTimeouts)) => return Ok(BTreeMap::new()),
+            Err(e) => return Err(e),
+        };
+        body.lines()
+            .map(|line| {
+                let malformed = || Error::MetaFileMalformed(MetaFile::HookTimeouts);
+                let (hook, secs) = line.split_once('=').ok_or_else(malformed)?;
+                let (hook, secs) = (hook.trim(), secs.trim().parse().map_err(|_| malformed())?);
+                if secs == 0 || !TIMED_HOOKS.contains(&hook) {
+                    return Err(malformed());
+                }
+                Ok((hook.to_string(), secs))
+            })
+            .collect()
+    }
+
+    /// Returns the rlimits, scheduling priorities, and CPU affinity the
+    /// package declares as defaults for its service, read from the
+    /// `SVC_RLIMITS`, `SVC_NICE`, `SVC_IONICE`, and `SVC_CPU_AFFINITY`
+    /// Metafiles. Any of these that are absent are left unset.
+    pub fn process_controls(&self) -> Result<ProcessControls> {
+        let nice = self.validated_metafile(MetaFile::SvcNice, process::validate_nice)?;
+        let ionice = self.validated_metafile(MetaFile::SvcIonice, process::validate_ionice)?;
+        let mut controls = ProcessControls { nice,
+                                             ionice,
+                                             cpu_affinity:
+                                                 self.parsed_metafile(MetaFile::SvcCpuAffinity)?,
+                                             ..Default::default() };
+        let body = match self.read_metafile(MetaFile::SvcRlimits) {
+            Ok(body) => body,
+            Err(Error::MetaFileNotFound(MetaFile::SvcRlimits)) => return Ok(controls),
+            Err(e) => return Err(e),
+        };
+        for line in body.lines() {
+            let malformed = || Error::MetaFileMalformed(MetaFile::SvcRlimits);
+            let (resource, value) = line.split_once('=').ok_or_else(malformed)?;
+            let value = Some(value.trim().parse().map_err(|_| malformed())?);
+            match resource.trim() {
+                "nofile" => controls.nofile = value,
+                "nproc" => controls.nproc = value,
+                "core" => controls.core = value,
+                "memlock" => controls.memlock = value,
+                _ => return Err(malformed()),
+            }
+        }
+        Ok(controls)
+    }
+
+    /// Parse the contents of a metafile holding a single value, or
+    /// return `None` if the package doesn't contain it.
+    fn parsed_metafile<T: FromStr>(&self, file: MetaFile) -> Result<Option<T>> {
+        match self.read_metafile(file) {
+            Ok(body) => {
+                body.parse()
+                    .map(Some)
+                    .map_err(|_| Error::MetaFileMalformed(file))
+            }
+            Err(Error::MetaFileNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Like `parsed_metafile`, but a value that `validate` rejects is
+    /// also treated as malformed.
+    fn validated_metafile<T, F>(&self, file: MetaFile, validate: F) -> Result<Option<T>>
+        where T: FromStr + Copy,
+              F: Fn(T) -> Result<()>
+    {
+        match self.parsed_metafile(file)? {
+            Some(value) if validate(value).is_err() => Err(Error::MetaFileMalformed(file)),
+            value => Ok(value),
+        }
+    }
+
     /// Read the contents of a given metafile.
     ///
     /// # Failures
@@ -1349,4 +1470,107 @@ mod test {
 
         assert_eq!(expected, pkg_install.environment_for_command().unwrap());
     }
+
+    #[test]
+    fn process_controls_are_read_from_metafiles() {
+        let fs_root = Builder::new().prefix("fs-root").tempdir().unwrap();
+        let pkg_install = testing_package_install("acme/database", fs_root.path());
+        assert_eq!(ProcessControls::default(),
+                   pkg_install.process_controls().unwrap());
+
+        write_metafile(&pkg_install, MetaFile::SvcRlimits, "nofile=65536\ncore=0\n");
+        write_metafile(&pkg_install, MetaFile::SvcNice, "-5");
+        write_metafile(&pkg_install, MetaFile::SvcCpuAffinity, "0-1");
+
+        assert_eq!(ProcessControls { nofile: Some(65536),
+                                     core: Some(0),
+                                     nice: Some(-5),
+                                     cpu_affinity: Some("0-1".parse().unwrap()),
+                                     ..Default::default() },
+                   pkg_install.process_controls().unwrap());
+
+        write_metafile(&pkg_install, MetaFile::SvcNice, "20");
+        match pkg_install.process_controls() {
+            Err(Error::MetaFileMalformed(MetaFile::SvcNice)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        write_metafile(&pkg_install, MetaFile::SvcNice, "-5");
+        write_metafile(&pkg_install, MetaFile::SvcIonice, "8");
+        match pkg_install.process_controls() {
+            Err(Error::MetaFileMalformed(MetaFile::SvcIonice)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn health_probes_are_read_from_metafile() {
+        let fs_root = Builder::new().prefix("fs-root").tempdir().unwrap();
+        let pkg_install = testing_package_install("acme/database", fs_root.path());
+        assert!(pkg_install.health_probes().unwrap().is_empty());
+
+        write_metafile(&pkg_install,
+                       MetaFile::HealthProbes,
+                       "tcp://localhost:5432\nexec:pg_isready -q\n");
+        assert_eq!(pkg_install.health_probes().unwrap(),
+                   vec!["tcp://localhost:5432".parse().unwrap(),
+                        "exec:pg_isready -q".parse().unwrap()]);
+
+        write_metafile(&pkg_install, MetaFile::HealthProbes, "ftp://localhost\n");
+        match pkg_install.health_probes() {
+            Err(Error::MetaFileMalformed(MetaFile::HealthProbes)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn hook_timeouts_are_read_from_metafile() {
+        let fs_root = Builder::new().prefix("fs-root").tempdir().unwrap();
+        let pkg_install = testing_package_install("acme/database", fs_root.path());
+        assert!(pkg_install.hook_timeouts().unwrap().is_empty());
+
+        write_metafile(&pkg_install,
+                       MetaFile::HookTimeouts,
+                       "init=300\nreconfigure=30\n");
+        let mut expected = BTreeMap::new();
+        expected.insert("init".to_string(), 300);
+        expected.insert("reconfigure".to_string(), 30);
+        assert_eq!(pkg_install.hook_timeouts().unwrap(), expected);
+
+        for malformed in &["init=forever\n", "post_run=30\n", "run=30\n", "init=0\n"] {
+            write_metafile(&pkg_install, MetaFile::HookTimeouts, malformed);
+            match pkg_install.hook_timeouts() {
+                Err(Error::MetaFileMalformed(MetaFile::HookTimeouts)) => (),
+                other => panic!("Unexpected result for {:?}: {:?}", malformed, other),
+            }
+        }
+    }
+
+    #[test]
+    fn reload_signal_is_read_from_metafile() {
+        let fs_root = Builder::new().prefix("fs-root").tempdir().unwrap();
+        let pkg_install = testing_package_install("acme/database", fs_root.path());
+        assert_eq!(pkg_install.reload_signal().unwrap(), None);
+
+        write_metafile(&pkg_install, MetaFile::ReloadSignal, "HUP");
+        assert_eq!(pkg_install.reload_signal().unwrap(), Some(Signal::HUP));
+
+        write_metafile(&pkg_install, MetaFile::ReloadSignal, "SIGHUP");
+        match pkg_install.reload_signal() {
+            Err(Error::MetaFileMalformed(MetaFile::ReloadSignal)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn process_controls_reject_unknown_rlimits() {
+        let fs_root = Builder::new().prefix("fs-root").tempdir().unwrap();
+        let pkg_install = testing_package_install("acme/database", fs_root.path());
+        write_metafile(&pkg_install, MetaFile::SvcRlimits, "stack=8192\n");
+
+        match pkg_install.process_controls() {
+            Err(Error::MetaFileMalformed(MetaFile::SvcRlimits)) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
 }
@@ -118,17 +118,24 @@ pub enum MetaFile {
     EnvironmentSep,
     Exports,
     Exposes,
+    HealthProbes,
+    HookTimeouts,
     Ident,
     LdFlags,
     LdRunPath,
     Manifest,
     Path,
+    ReloadSignal,
     RuntimeEnvironment,
     RuntimeEnvironmentPaths,
     RuntimePath,
     ShutdownSignal,
     ShutdownTimeout,
+    SvcCpuAffinity,
     SvcGroup,
+    SvcIonice,
+    SvcNice,
+    SvcRlimits,
     SvcUser,
     Target,
     TDeps,
@@ -149,17 +156,24 @@ impl fmt::Display for MetaFile {
             MetaFile::EnvironmentSep => "ENVIRONMENT_SEP",
             MetaFile::Exports => "EXPORTS",
             MetaFile::Exposes => "EXPOSES",
+            MetaFile::HealthProbes => "HEALTH_PROBES",
+            MetaFile::HookTimeouts => "HOOK_TIMEOUTS",
             MetaFile::Ident => "IDENT",
             MetaFile::LdFlags => "LDFLAGS",
             MetaFile::LdRunPath => "LD_RUN_PATH",
             MetaFile::Manifest => "MANIFEST",
             MetaFile::Path => "PATH",
+            MetaFile::ReloadSignal => "RELOAD_SIGNAL",
             MetaFile::RuntimeEnvironment => "RUNTIME_ENVIRONMENT",
             MetaFile::RuntimeEnvironmentPaths => "RUNTIME_ENVIRONMENT_PATHS",
             MetaFile::RuntimePath => "RUNTIME_PATH",
             MetaFile::ShutdownSignal => "SHUTDOWN_SIGNAL",
             MetaFile::ShutdownTimeout => "SHUTDOWN_TIMEOUT",
+            MetaFile::SvcCpuAffinity => "SVC_CPU_AFFINITY",
             MetaFile::SvcGroup => "SVC_GROUP",
+            MetaFile::SvcIonice => "SVC_IONICE",
+            MetaFile::SvcNice => "SVC_NICE",
+            MetaFile::SvcRlimits => "SVC_RLIMITS",
             MetaFile::SvcUser => "SVC_USER",
             MetaFile::Target => "TARGET",
             MetaFile::TDeps => "TDEPS",
@@ -1,9 +1,24 @@
+mod probe;
+mod schedule;
+
+pub use self::{probe::HealthProbe,
+               schedule::JobSchedule};
+
 use crate::error::{Error,
                    Result};
+use chrono::{DateTime,
+             Datelike,
+             FixedOffset,
+             Local,
+             TimeZone,
+             Timelike,
+             Utc,
+             Weekday};
 use regex::Regex;
 use serde::{Deserialize,
             Serialize};
-use std::{fmt,
+use std::{convert::TryFrom,
+          fmt,
           num::ParseIntError,
           ops::{Deref,
                 DerefMut},
@@ -146,6 +161,34 @@ impl serde::Serialize for ServiceBind {
     }
 }
 
+/// Separates the package name from the instance name in the name of a named instance of a
+/// service (example: redis+cache). Package names can't contain it, so the name of an instance
+/// never collides with the name of another package.
+pub const INSTANCE_SEPARATOR: char = '+';
+
+/// Returns the name a service is known by on a Supervisor. This is the package name for the
+/// default instance and the package name followed by the instance name for named instances. It
+/// names the service's spec file, its directory under the svc root and its service group.
+pub fn service_name(package_name: &str, instance: Option<&str>) -> String {
+    match instance {
+        Some(instance) => format!("{}{}{}", package_name, INSTANCE_SEPARATOR, instance),
+        None => package_name.to_string(),
+    }
+}
+
+/// Validates the name of a named instance of a service. Like package names, instance names may
+/// only contain ASCII letters, digits, `_` and `-`.
+pub fn validate_instance_name(name: &str) -> Result<()> {
+    if !name.is_empty()
+       && name.chars()
+              .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        Ok(())
+    } else {
+        Err(Error::InvalidInstanceName(name.to_string()))
+    }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 pub struct ServiceGroup(String);
 
@@ -300,6 +343,464 @@ impl From<Duration> for HealthCheckInterval {
     fn from(d: Duration) -> Self { Self(d) }
 }
 
+/// Governs when the Supervisor restarts a service because its health
+/// checks keep failing.
+///
+/// The service is restarted after `failure_threshold` consecutive
+/// `Critical` health check results. Results within `grace_period`
+/// seconds of the service starting are not counted, so slow services
+/// have time to come up. Without a threshold, health checks never
+/// cause a restart.
+#[derive(Deserialize,
+         Serialize,
+         Eq,
+         PartialEq,
+         Debug,
+         Clone,
+         Copy,
+         Default,
+         Hash)]
+#[serde(default)]
+pub struct LivenessPolicy {
+    /// The number of consecutive `Critical` results that trigger a
+    /// restart.
+    pub failure_threshold: Option<u32>,
+    /// How long, in seconds, to ignore health check results after the
+    /// service starts.
+    pub grace_period:      Option<u64>,
+}
+
+impl LivenessPolicy {
+    pub fn is_empty(&self) -> bool { *self == Self::default() }
+
+    pub fn grace_period(&self) -> Duration {
+        Duration::from_secs(self.grace_period.unwrap_or_default())
+    }
+}
+
+/// How many members of a service group a canary update is rolled out
+/// to before the rest of the group: either a number of members or a
+/// percentage of the group.
+///
+/// Renders as, and parses from, a plain number ("2") or a percentage
+/// ("10%").
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Copy, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub enum CanarySize {
+    Count(u32),
+    Percent(u32),
+}
+
+impl CanarySize {
+    /// The number of canaries in a group of `members`. There is always
+    /// at least one canary, and never more than `members`.
+    pub fn of(self, members: usize) -> usize {
+        let count = match self {
+            CanarySize::Count(count) => count as usize,
+            CanarySize::Percent(percent) => (members * percent as usize + 99) / 100,
+        };
+        count.max(1).min(members)
+    }
+}
+
+impl Default for CanarySize {
+    fn default() -> Self { CanarySize::Count(1) }
+}
+
+impl FromStr for CanarySize {
+    type Err = Error;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let invalid = || Error::InvalidCanarySize(s.to_string());
+        let s = s.trim();
+        let size = match s.strip_suffix('%') {
+            Some(percent) => {
+                let percent = percent.trim().parse().map_err(|_| invalid())?;
+                if percent > 100 {
+                    return Err(invalid());
+                }
+                CanarySize::Percent(percent)
+            }
+            None => CanarySize::Count(s.parse().map_err(|_| invalid())?),
+        };
+        match size {
+            CanarySize::Count(0) | CanarySize::Percent(0) => Err(invalid()),
+            size => Ok(size),
+        }
+    }
+}
+
+impl fmt::Display for CanarySize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanarySize::Count(count) => write!(f, "{}", count),
+            CanarySize::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl TryFrom<String> for CanarySize {
+    type Error = Error;
+
+    fn try_from(s: String) -> result::Result<Self, Self::Error> { s.parse() }
+}
+
+impl From<CanarySize> for String {
+    fn from(size: CanarySize) -> Self { size.to_string() }
+}
+
+/// Governs how a service group rolls out an update with the canary
+/// update strategy.
+///
+/// The update leader and the next members in the rolling update order
+/// are updated first, `size` members in all. The rest of the group
+/// only follows once every canary has been running the update and
+/// ready to accept traffic for `bake_period` seconds.
+#[derive(Deserialize,
+         Serialize,
+         Eq,
+         PartialEq,
+         Debug,
+         Clone,
+         Copy,
+         Default,
+         Hash)]
+#[serde(default)]
+pub struct CanaryPolicy {
+    /// How many members to update first. Defaults to one member.
+    pub size:        Option<CanarySize>,
+    /// How long, in seconds, the canaries must stay ready before the
+    /// rest of the group is updated.
+    pub bake_period: Option<u64>,
+}
+
+impl CanaryPolicy {
+    /// The bake period used when none is configured.
+    pub const DEFAULT_BAKE_PERIOD: u64 = 300;
+
+    pub fn is_empty(&self) -> bool { *self == Self::default() }
+
+    pub fn size(&self) -> CanarySize { self.size.unwrap_or_default() }
+
+    pub fn bake_period(&self) -> Duration {
+        Duration::from_secs(self.bake_period.unwrap_or(Self::DEFAULT_BAKE_PERIOD))
+    }
+}
+
+/// Governs how a rolling or canary update moves through a service
+/// group after the update leader has updated.
+///
+/// Followers update in batches of `batch_size` members. Each batch
+/// waits for the one before it to finish updating. With a
+/// `health_timeout`, the batch before must also report `Ok` health;
+/// if it doesn't within that many seconds, the update halts rather
+/// than spreading a bad release further.
+#[derive(Deserialize,
+         Serialize,
+         Eq,
+         PartialEq,
+         Debug,
+         Clone,
+         Copy,
+         Default,
+         Hash)]
+#[serde(default)]
+pub struct RolloutPolicy {
+    /// How many followers update at the same time. Defaults to one.
+    pub batch_size:     Option<u32>,
+    /// How long, in seconds, to wait for a batch to report `Ok`
+    /// health before halting the update.
+    pub health_timeout: Option<u64>,
+}
+
+impl RolloutPolicy {
+    pub fn is_empty(&self) -> bool { *self == Self::default() }
+
+    pub fn batch_size(&self) -> usize { self.batch_size.unwrap_or(1).max(1) as usize }
+
+    /// The time to wait for a batch to become healthy, if updates are
+    /// gated on health at all.
+    pub fn health_timeout(&self) -> Option<Duration> {
+        self.health_timeout.map(Duration::from_secs)
+    }
+}
+
+/// A recurring period during which updates may be applied.
+///
+/// Renders as, and parses from, either of two forms. The first is the
+/// days of the week the window opens on, a time range and an optional
+/// time zone, for example "Sat,Sun 01:00-05:00 UTC" or
+/// "Mon-Fri 22:00-02:00 -05:00". The days are "*" for every day, or a
+/// comma separated list of days and day ranges. A window that ends
+/// before it starts closes on the following day. The second is a cron
+/// expression of when the window opens, as for a `JobSchedule`, and how
+/// long it stays open, for example "0 1 1 * * for 4h" or
+/// "30 22 * * Fri local for 1h30m".
+///
+/// The time zone is "UTC", which is the default, "local" or a fixed
+/// offset from UTC. A fixed offset doesn't follow daylight saving time,
+/// so a window given in one opens an hour off local time for part of
+/// the year. Use "local" on hosts set to the time zone the window
+/// should follow instead.
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct MaintenanceWindow(WindowKind);
+
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+enum WindowKind {
+    Weekly {
+        /// Bit `n` is set if the window opens on the `n`th day of the
+        /// week, counting from Monday.
+        days:     u8,
+        /// The minute of the day the window opens at.
+        start:    u32,
+        /// The minute of the day the window closes at.
+        end:      u32,
+        timezone: WindowTimezone,
+    },
+    Cron {
+        /// When the window opens.
+        opens:   JobSchedule,
+        /// How many minutes the window stays open for.
+        minutes: u32,
+    },
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+enum WindowTimezone {
+    Utc,
+    Local,
+    /// Seconds east of UTC
+    Offset(i32),
+}
+
+impl MaintenanceWindow {
+    const DAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const EVERY_DAY: u8 = 0b111_1111;
+
+    /// Returns `true` if updates may be applied at `at` under
+    /// `windows`. Updates may always be applied when there are no
+    /// windows.
+    pub fn allows(windows: &[MaintenanceWindow], at: DateTime<Utc>) -> bool {
+        windows.is_empty() || windows.iter().any(|window| window.contains(at))
+    }
+
+    /// Returns `true` if updates may be applied right now under
+    /// `windows`.
+    pub fn allows_now(windows: &[MaintenanceWindow]) -> bool { Self::allows(windows, Utc::now()) }
+
+    /// Returns `true` if the window is open at `at`.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        match &self.0 {
+            WindowKind::Weekly { days,
+                                 start,
+                                 end,
+                                 timezone, } => {
+                let (day, minute) = match timezone {
+                    WindowTimezone::Utc => day_and_minute(&at),
+                    WindowTimezone::Local => day_and_minute(&at.with_timezone(&Local)),
+                    WindowTimezone::Offset(offset) => {
+                        let offset =
+                            FixedOffset::east_opt(*offset).expect("offset is validated on parse");
+                        day_and_minute(&at.with_timezone(&offset))
+                    }
+                };
+                let opens_on = |day: u32| days & (1 << day) != 0;
+                if start < end {
+                    opens_on(day) && (*start..*end).contains(&minute)
+                } else {
+                    (opens_on(day) && minute >= *start)
+                    || (opens_on((day + 6) % 7) && minute < *end)
+                }
+            }
+            WindowKind::Cron { opens, minutes } => {
+                // The window is open if it last opened within its length of `at`
+                let opened_after = at - chrono::Duration::minutes(i64::from(*minutes));
+                opens.next_after(opened_after.into())
+                     .map_or(false, |opened_at| opened_at <= at.into())
+            }
+        }
+    }
+}
+
+/// The day of the week, counting from Monday, and the minute of the
+/// day of a point in time.
+fn day_and_minute<Tz: TimeZone>(at: &DateTime<Tz>) -> (u32, u32) {
+    (at.weekday().num_days_from_monday(), at.hour() * 60 + at.minute())
+}
+
+impl FromStr for MaintenanceWindow {
+    type Err = Error;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let invalid = || Error::InvalidMaintenanceWindow(s.to_string());
+        let parts = s.split_whitespace().collect::<Vec<_>>();
+        if let [opens @ .., "for", length] = parts.as_slice() {
+            let opens = opens.join(" ")
+                             .parse::<JobSchedule>()
+                             .map_err(|_| invalid())?;
+            let minutes = parse_minutes(length).ok_or_else(invalid)?;
+            return Ok(MaintenanceWindow(WindowKind::Cron { opens, minutes }));
+        }
+        let (days, range, timezone) = match parts.as_slice() {
+            [days, range] => (days, range, "UTC"),
+            [days, range, timezone] => (days, range, *timezone),
+            _ => return Err(invalid()),
+        };
+        let days = parse_days(days).ok_or_else(invalid)?;
+        let (start, end) = match range.split('-').collect::<Vec<_>>().as_slice() {
+            [start, end] => {
+                (parse_minute_of_day(start).ok_or_else(invalid)?,
+                 parse_minute_of_day(end).ok_or_else(invalid)?)
+            }
+            _ => return Err(invalid()),
+        };
+        // 24:00 can only end a window
+        if start == end || start == 24 * 60 {
+            return Err(invalid());
+        }
+        let timezone = parse_timezone(timezone).ok_or_else(invalid)?;
+        Ok(MaintenanceWindow(WindowKind::Weekly { days,
+                                                  start,
+                                                  end,
+                                                  timezone }))
+    }
+}
+
+/// Parses a length of time such as "4h", "90m" or "1h30m" into a
+/// non-zero number of minutes.
+fn parse_minutes(s: &str) -> Option<u32> {
+    let (hours, rest) = match s.split_once('h') {
+        Some((hours, rest)) => (hours.parse::<u32>().ok()?, rest),
+        None => (0, s),
+    };
+    let minutes = match rest {
+        "" => 0,
+        rest => rest.strip_suffix('m')?.parse::<u32>().ok()?,
+    };
+    Some(hours.checked_mul(60)?.checked_add(minutes)?).filter(|minutes| *minutes > 0)
+}
+
+fn parse_days(s: &str) -> Option<u8> {
+    if s == "*" {
+        return Some(MaintenanceWindow::EVERY_DAY);
+    }
+    let day = |s: &str| {
+        s.parse::<Weekday>()
+         .ok()
+         .map(|day| day.num_days_from_monday())
+    };
+    let mut days = 0;
+    for item in s.split(',') {
+        match item.split('-').collect::<Vec<_>>().as_slice() {
+            [one] => days |= 1 << day(*one)?,
+            [first, last] => {
+                let (mut current, last) = (day(*first)?, day(*last)?);
+                // Ranges may wrap around the end of the week, as in Sat-Mon
+                loop {
+                    days |= 1 << current;
+                    if current == last {
+                        break;
+                    }
+                    current = (current + 1) % 7;
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(days)
+}
+
+fn parse_minute_of_day(s: &str) -> Option<u32> {
+    let (hour, minute) = s.split_once(':')?;
+    let (hour, minute) = (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?);
+    if minute >= 60 || hour > 24 || (hour == 24 && minute > 0) {
+        return None;
+    }
+    Some(hour * 60 + minute)
+}
+
+fn parse_timezone(s: &str) -> Option<WindowTimezone> {
+    if s.eq_ignore_ascii_case("utc") {
+        return Some(WindowTimezone::Utc);
+    }
+    if s.eq_ignore_ascii_case("local") {
+        return Some(WindowTimezone::Local);
+    }
+    let sign = match s.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = match s[1..].split_once(':') {
+        Some((hours, minutes)) => (hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?),
+        None => (s[1..].parse::<i32>().ok()?, 0),
+    };
+    if hours > 23 || minutes >= 60 {
+        return None;
+    }
+    Some(WindowTimezone::Offset(sign * (hours * 3600 + minutes * 60)))
+}
+
+impl fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            WindowKind::Weekly { days,
+                                 start,
+                                 end,
+                                 timezone, } => {
+                if *days == Self::EVERY_DAY {
+                    write!(f, "*")?;
+                } else {
+                    let days = (0..7).filter(|day| days & (1 << day) != 0)
+                                     .map(|day| Self::DAY_NAMES[day])
+                                     .collect::<Vec<_>>();
+                    write!(f, "{}", days.join(","))?;
+                }
+                write!(f,
+                       " {:02}:{:02}-{:02}:{:02} ",
+                       start / 60,
+                       start % 60,
+                       end / 60,
+                       end % 60)?;
+                write!(f, "{}", timezone)
+            }
+            WindowKind::Cron { opens, minutes } => {
+                write!(f, "{} for ", opens)?;
+                match (minutes / 60, minutes % 60) {
+                    (0, minutes) => write!(f, "{}m", minutes),
+                    (hours, 0) => write!(f, "{}h", hours),
+                    (hours, minutes) => write!(f, "{}h{}m", hours, minutes),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for WindowTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowTimezone::Utc => write!(f, "UTC"),
+            WindowTimezone::Local => write!(f, "local"),
+            WindowTimezone::Offset(offset) => {
+                let sign = if *offset < 0 { '-' } else { '+' };
+                let offset = offset.abs();
+                write!(f, "{}{:02}:{:02}", sign, offset / 3600, offset % 3600 / 60)
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for MaintenanceWindow {
+    type Error = Error;
+
+    fn try_from(s: String) -> result::Result<Self, Self::Error> { s.parse() }
+}
+
+impl From<MaintenanceWindow> for String {
+    fn from(window: MaintenanceWindow) -> Self { window.to_string() }
+}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
@@ -321,6 +822,27 @@ mod test {
         assert!(ServiceGroup::from_str("foo@baz").is_err());
     }
 
+    #[test]
+    fn service_name_of_instance() {
+        assert_eq!(service_name("redis", None), "redis");
+        assert_eq!(service_name("redis", Some("cache")), "redis+cache");
+
+        let group =
+            ServiceGroup::new(service_name("redis", Some("cache")), "default", None).unwrap();
+        assert_eq!(group.service(), "redis+cache");
+        assert_eq!(group.group(), "default");
+    }
+
+    #[test]
+    fn validate_instance_name_rejects_separators() {
+        assert!(validate_instance_name("cache").is_ok());
+        assert!(validate_instance_name("session_store-2").is_ok());
+        assert!(validate_instance_name("").is_err());
+        assert!(validate_instance_name("a.b").is_err());
+        assert!(validate_instance_name("a+b").is_err());
+        assert!(validate_instance_name("a/b").is_err());
+    }
+
     #[test]
     fn service_group_from_str_no_group() {
         let group = "foo@baz";
@@ -545,6 +1067,28 @@ mod test {
                    format!("{}", HealthCheckInterval::from_str("5").unwrap()));
     }
 
+    #[test]
+    fn canary_size_from_str() {
+        assert_eq!(CanarySize::Count(2), "2".parse().unwrap());
+        assert_eq!(CanarySize::Percent(10), "10%".parse().unwrap());
+        assert_eq!(CanarySize::Percent(100), "100%".parse().unwrap());
+        assert!("0".parse::<CanarySize>().is_err());
+        assert!("0%".parse::<CanarySize>().is_err());
+        assert!("101%".parse::<CanarySize>().is_err());
+        assert!("ten".parse::<CanarySize>().is_err());
+        assert!("".parse::<CanarySize>().is_err());
+    }
+
+    #[test]
+    fn canary_size_of_group() {
+        assert_eq!(CanarySize::Count(2).of(5), 2);
+        assert_eq!(CanarySize::Count(10).of(5), 5);
+        assert_eq!(CanarySize::Percent(10).of(60), 6);
+        // Percentages round up so a small group still gets a canary
+        assert_eq!(CanarySize::Percent(10).of(5), 1);
+        assert_eq!(CanarySize::Percent(50).of(3), 2);
+    }
+
     /// This ensures that we can safely transition from the old
     /// application/environment formulation of service group
     /// names. Once this has been in the wild for a while, we can
@@ -594,4 +1138,88 @@ mod test {
                                                                               without app/env");
         assert_eq!(sg, *data.key.service_group());
     }
+
+    #[test]
+    fn maintenance_window_from_str() {
+        let round_trip = |s: &str| s.parse::<MaintenanceWindow>().unwrap().to_string();
+        assert_eq!(round_trip("Sat,Sun 01:00-05:00 UTC"),
+                   "Sat,Sun 01:00-05:00 UTC");
+        assert_eq!(round_trip("mon-fri 22:00-02:00"),
+                   "Mon,Tue,Wed,Thu,Fri 22:00-02:00 UTC");
+        assert_eq!(round_trip("Sat-Mon 00:00-24:00 -05:30"),
+                   "Mon,Sat,Sun 00:00-24:00 -05:30");
+        assert_eq!(round_trip("* 03:00-04:00 local"), "* 03:00-04:00 local");
+        assert_eq!(round_trip("Tuesday 03:00-04:00 +2"),
+                   "Tue 03:00-04:00 +02:00");
+        assert_eq!(round_trip("0 1 1 * * for 4h"), "0 1 1 * * for 4h");
+        assert_eq!(round_trip("30 22 * * Fri local for 90m"),
+                   "30 22 * * Fri local for 1h30m");
+        assert_eq!(round_trip("0 2 * * * UTC for 0h45m"), "0 2 * * * for 45m");
+
+        for invalid in &["",
+                         "Sat",
+                         "Sat 01:00",
+                         "Funday 01:00-02:00",
+                         "Sat 01:00-01:00",
+                         "Sat 24:00-01:00",
+                         "Sat 01:00-25:00",
+                         "Sat 01:60-02:00",
+                         "Sat 01:00-02:00 Mars/Base",
+                         "Sat 01:00-02:00 +24:00",
+                         "Sat 01:00-02:00 UTC extra",
+                         "0 1 * * * for",
+                         "0 1 * * * for 0m",
+                         "0 1 * * * for 4",
+                         "0 1 * * * for 4h30",
+                         "0 1 * * for 4h",
+                         "0 0 30 2 * for 4h"]
+        {
+            assert!(invalid.parse::<MaintenanceWindow>().is_err(),
+                    "'{}' should not parse",
+                    invalid);
+        }
+    }
+
+    #[test]
+    fn maintenance_window_contains() {
+        // 2023-10-14 is a Saturday
+        let at = |day, hour, minute| {
+            Utc.with_ymd_and_hms(2023, 10, day, hour, minute, 0)
+               .unwrap()
+        };
+
+        let window = "Sat 22:00-02:00".parse::<MaintenanceWindow>().unwrap();
+        assert!(!window.contains(at(14, 21, 59)));
+        assert!(window.contains(at(14, 22, 0)));
+        assert!(window.contains(at(15, 1, 59)));
+        assert!(!window.contains(at(15, 2, 0)));
+        assert!(!window.contains(at(15, 22, 0)));
+
+        let window = "Sat 01:00-05:00 -05:00".parse::<MaintenanceWindow>()
+                                             .unwrap();
+        assert!(!window.contains(at(14, 1, 0)));
+        assert!(window.contains(at(14, 6, 0)));
+        assert!(!window.contains(at(14, 10, 0)));
+
+        // Opens on the 15th of the month, or on Saturdays
+        let window = "30 23 15 * Sat for 3h".parse::<MaintenanceWindow>()
+                                            .unwrap();
+        assert!(!window.contains(at(14, 23, 29)));
+        assert!(window.contains(at(14, 23, 30)));
+        assert!(window.contains(at(15, 2, 29)));
+        assert!(!window.contains(at(15, 2, 30)));
+        assert!(window.contains(at(15, 23, 30)));
+        assert!(!window.contains(at(16, 23, 30)));
+    }
+
+    #[test]
+    fn maintenance_windows_allow_updates() {
+        let at = Utc.with_ymd_and_hms(2023, 10, 14, 12, 0, 0).unwrap();
+        assert!(MaintenanceWindow::allows(&[], at));
+
+        let windows = vec!["Mon-Fri 00:00-06:00".parse().unwrap(),
+                           "Sat 11:00-13:00".parse().unwrap()];
+        assert!(MaintenanceWindow::allows(&windows, at));
+        assert!(!MaintenanceWindow::allows(&windows[..1], at));
+    }
 }
@@ -0,0 +1,182 @@
+use crate::error::Error;
+use serde::{Deserialize,
+            Serialize};
+use std::{convert::TryFrom,
+          fmt,
+          result,
+          str::FromStr};
+
+/// A health check the Supervisor runs itself, in place of a service's `health-check` hook.
+///
+/// Renders as, and parses from, one of:
+///
+/// * "http://HOST[:PORT][/PATH]", optionally followed by the status the response must have, which
+///   is 200 if unset (example: "http://localhost:8080/healthz 204"). Only plain HTTP is supported.
+/// * "tcp://HOST:PORT", which passes if a connection can be made.
+/// * "exec:COMMAND [ARGS...]", a command run without a shell whose exit code is read like that of a
+///   `health-check` hook.
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub enum HealthProbe {
+    Http {
+        host:   String,
+        port:   u16,
+        path:   String,
+        status: u16,
+    },
+    Tcp {
+        host: String,
+        port: u16,
+    },
+    Exec {
+        command: Vec<String>,
+    },
+}
+
+impl HealthProbe {
+    const DEFAULT_HTTP_PORT: u16 = 80;
+    const DEFAULT_HTTP_STATUS: u16 = 200;
+}
+
+/// Splits "HOST:PORT" into its parts. IPv6 addresses are written in
+/// brackets, as in "[::1]:8080", and keep them.
+fn host_and_port(s: &str) -> Option<(&str, Option<u16>)> {
+    let (host, port) = match s.rsplit_once(':') {
+        // The colons of an IPv6 address without a port
+        Some((_, rest)) if rest.ends_with(']') => (s, None),
+        Some((host, port)) => (host, Some(port.parse().ok().filter(|port| *port > 0)?)),
+        None => (s, None),
+    };
+    if host.is_empty() {
+        None
+    } else {
+        Some((host, port))
+    }
+}
+
+impl FromStr for HealthProbe {
+    type Err = Error;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let invalid = || Error::InvalidHealthProbe(s.to_string());
+        let s = s.trim();
+        if let Some(command) = s.strip_prefix("exec:") {
+            let command = command.split_whitespace()
+                                 .map(str::to_string)
+                                 .collect::<Vec<_>>();
+            if command.is_empty() {
+                return Err(invalid());
+            }
+            Ok(HealthProbe::Exec { command })
+        } else if let Some(address) = s.strip_prefix("tcp://") {
+            match host_and_port(address) {
+                Some((host, Some(port))) if !address.contains(char::is_whitespace) => {
+                    Ok(HealthProbe::Tcp { host: host.to_string(),
+                                          port })
+                }
+                _ => Err(invalid()),
+            }
+        } else if let Some(rest) = s.strip_prefix("http://") {
+            let mut parts = rest.split_whitespace();
+            let url = parts.next().ok_or_else(invalid)?;
+            let status = match parts.next() {
+                Some(status) => {
+                    status.parse::<u16>()
+                          .ok()
+                          .filter(|status| (100..600).contains(status))
+                          .ok_or_else(invalid)?
+                }
+                None => Self::DEFAULT_HTTP_STATUS,
+            };
+            if parts.next().is_some() {
+                return Err(invalid());
+            }
+            let (address, path) = match url.find('/') {
+                Some(i) => url.split_at(i),
+                None => (url, "/"),
+            };
+            let (host, port) = host_and_port(address).ok_or_else(invalid)?;
+            Ok(HealthProbe::Http { host: host.to_string(),
+                                   port: port.unwrap_or(Self::DEFAULT_HTTP_PORT),
+                                   path: path.to_string(),
+                                   status })
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for HealthProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthProbe::Http { host,
+                                port,
+                                path,
+                                status, } => {
+                write!(f, "http://{}:{}{}", host, port, path)?;
+                // 200 is the default so it's left out
+                if *status != Self::DEFAULT_HTTP_STATUS {
+                    write!(f, " {}", status)?;
+                }
+                Ok(())
+            }
+            HealthProbe::Tcp { host, port } => write!(f, "tcp://{}:{}", host, port),
+            HealthProbe::Exec { command } => write!(f, "exec:{}", command.join(" ")),
+        }
+    }
+}
+
+impl TryFrom<String> for HealthProbe {
+    type Error = Error;
+
+    fn try_from(s: String) -> result::Result<Self, Self::Error> { s.parse() }
+}
+
+impl From<HealthProbe> for String {
+    fn from(probe: HealthProbe) -> Self { probe.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn health_probe_from_str() {
+        let probe: HealthProbe = "http://localhost:8080/healthz".parse().unwrap();
+        assert_eq!(probe,
+                   HealthProbe::Http { host:   "localhost".to_string(),
+                                       port:   8080,
+                                       path:   "/healthz".to_string(),
+                                       status: 200, });
+        assert_eq!(probe.to_string(), "http://localhost:8080/healthz");
+
+        let probe: HealthProbe = "http://127.0.0.1 204".parse().unwrap();
+        assert_eq!(probe.to_string(), "http://127.0.0.1:80/ 204");
+        let probe: HealthProbe = "http://[::1]:9000/status?full=1".parse().unwrap();
+        assert_eq!(probe.to_string(), "http://[::1]:9000/status?full=1");
+
+        let probe: HealthProbe = "tcp://localhost:6379".parse().unwrap();
+        assert_eq!(probe,
+                   HealthProbe::Tcp { host: "localhost".to_string(),
+                                      port: 6379, });
+        assert_eq!(probe.to_string(), "tcp://localhost:6379");
+
+        let probe: HealthProbe = "exec:redis-cli  -p 6379 ping".parse().unwrap();
+        assert_eq!(probe,
+                   HealthProbe::Exec { command: vec!["redis-cli".to_string(),
+                                                     "-p".to_string(),
+                                                     "6379".to_string(),
+                                                     "ping".to_string()], });
+        assert_eq!(probe.to_string(), "exec:redis-cli -p 6379 ping");
+
+        assert!("".parse::<HealthProbe>().is_err());
+        assert!("https://localhost/".parse::<HealthProbe>().is_err());
+        assert!("http://".parse::<HealthProbe>().is_err());
+        assert!("http://localhost:0/".parse::<HealthProbe>().is_err());
+        assert!("http://localhost/ 999".parse::<HealthProbe>().is_err());
+        assert!("http://localhost/ 200 ok".parse::<HealthProbe>().is_err());
+        assert!("tcp://localhost".parse::<HealthProbe>().is_err());
+        assert!("tcp://:6379".parse::<HealthProbe>().is_err());
+        assert!("exec:".parse::<HealthProbe>().is_err());
+    }
+}
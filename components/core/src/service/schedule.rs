@@ -0,0 +1,285 @@
+use super::{parse_timezone,
+            WindowTimezone};
+use crate::error::Error;
+use chrono::{DateTime,
+             Datelike,
+             FixedOffset,
+             Local,
+             NaiveDate,
+             TimeZone,
+             Timelike,
+             Utc,
+             Weekday};
+use serde::{Deserialize,
+            Serialize};
+use std::{convert::TryFrom,
+          fmt,
+          result,
+          str::FromStr,
+          time::SystemTime};
+
+/// When a job service runs.
+///
+/// Renders as, and parses from, a cron expression of five fields, the
+/// minute, hour, day of the month, month and day of the week, followed
+/// by an optional time zone, for example "30 2 * * *" or
+/// "0 */6 * * Mon-Fri local". Each field is "*", a value, a range such
+/// as "1-5", or a comma separated list of these, and a value or range
+/// may be followed by a step such as "/15". Days of the week are
+/// numbered from 0 for Sunday, which is also 7, or named. As in cron,
+/// when both the day of the month and the day of the week are
+/// restricted the job runs on days matching either of them. The time
+/// zone is the same as for a `MaintenanceWindow`.
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct JobSchedule {
+    /// Bit `n` is set if the job runs at minute `n` of the hour.
+    minutes:             u64,
+    hours:               u64,
+    /// Bit `n` is set if the job runs on day `n` of the month.
+    days:                u64,
+    /// Bit `n` is set if the job runs in the `n`th month, counting
+    /// from 1.
+    months:              u64,
+    /// Bit `n` is set if the job runs on the `n`th day of the week,
+    /// counting from Sunday.
+    weekdays:            u64,
+    days_restricted:     bool,
+    weekdays_restricted: bool,
+    timezone:            WindowTimezone,
+    /// The fields of the expression, as given.
+    fields:              String,
+}
+
+impl JobSchedule {
+    /// Every combination of a day of the month and a day of the week
+    /// repeats within this many years, so a job that doesn't run in
+    /// that time never runs.
+    const YEARS_SEARCHED: i64 = 28;
+
+    /// The first time after `after` at which the job runs, or `None`
+    /// if it never runs. Schedules that never run, such as
+    /// "0 0 30 2 *", are rejected when they are parsed.
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let after = DateTime::<Utc>::from(after);
+        let next = match self.timezone {
+            WindowTimezone::Utc => self.next_in(&after),
+            WindowTimezone::Local => {
+                self.next_in(&after.with_timezone(&Local))
+                    .map(|at| at.with_timezone(&Utc))
+            }
+            WindowTimezone::Offset(offset) => {
+                let offset = FixedOffset::east_opt(offset).expect("offset is validated on parse");
+                self.next_in(&after.with_timezone(&offset))
+                    .map(|at| at.with_timezone(&Utc))
+            }
+        };
+        next.map(SystemTime::from)
+    }
+
+    fn next_in<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let timezone = after.timezone();
+        let local = after.naive_local();
+        let mut date = local.date();
+        let last_date = date + chrono::Duration::days(366 * Self::YEARS_SEARCHED);
+        let mut first_minute = local.hour() * 60 + local.minute() + 1;
+        while date <= last_date {
+            if self.runs_on(date) {
+                for minute_of_day in first_minute..24 * 60 {
+                    let (hour, minute) = (minute_of_day / 60, minute_of_day % 60);
+                    if self.hours & (1 << hour) == 0 || self.minutes & (1 << minute) == 0 {
+                        continue;
+                    }
+                    // Local times skipped by a daylight saving time change never come
+                    let at = date.and_hms_opt(hour, minute, 0)
+                                 .and_then(|at| timezone.from_local_datetime(&at).earliest());
+                    match at {
+                        Some(at) if at > *after => return Some(at),
+                        _ => (),
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+            first_minute = 0;
+        }
+        None
+    }
+
+    fn runs_on(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        let day_matches = if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        };
+        self.months & (1 << date.month()) != 0 && day_matches
+    }
+}
+
+impl FromStr for JobSchedule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let invalid = || Error::InvalidJobSchedule(s.to_string());
+        let parts = s.split_whitespace().collect::<Vec<_>>();
+        let (fields, timezone) = match parts.len() {
+            5 => (&parts[..], "UTC"),
+            6 => (&parts[..5], parts[5]),
+            _ => return Err(invalid()),
+        };
+        let number = |s: &str| s.parse::<u32>().ok();
+        let weekday = |s: &str| {
+            number(s).or_else(|| {
+                         s.parse::<Weekday>()
+                          .ok()
+                          .map(|day| day.num_days_from_sunday())
+                     })
+        };
+        let minutes = parse_field(fields[0], 0, 59, number).ok_or_else(invalid)?;
+        let hours = parse_field(fields[1], 0, 23, number).ok_or_else(invalid)?;
+        let days = parse_field(fields[2], 1, 31, number).ok_or_else(invalid)?;
+        let months = parse_field(fields[3], 1, 12, number).ok_or_else(invalid)?;
+        let mut weekdays = parse_field(fields[4], 0, 7, weekday).ok_or_else(invalid)?;
+        // Sunday is both 0 and 7
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        let timezone = parse_timezone(timezone).ok_or_else(invalid)?;
+        let schedule = JobSchedule { minutes,
+                                     hours,
+                                     days,
+                                     months,
+                                     weekdays,
+                                     days_restricted: !fields[2].starts_with('*'),
+                                     weekdays_restricted: !fields[4].starts_with('*'),
+                                     timezone,
+                                     fields: fields.join(" ") };
+        if schedule.next_after(SystemTime::now()).is_none() {
+            return Err(invalid());
+        }
+        Ok(schedule)
+    }
+}
+
+/// Parses a field of a cron expression into a set of bits, one for
+/// each value between `min` and `max` it selects.
+fn parse_field(s: &str, min: u32, max: u32, value: impl Fn(&str) -> Option<u32>) -> Option<u64> {
+    let mut bits = 0;
+    for item in s.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>().ok().filter(|step| *step > 0)?),
+            None => (item, 1),
+        };
+        let (first, last) = if range == "*" {
+            (min, max)
+        } else if let Some((first, last)) = range.split_once('-') {
+            (value(first)?, value(last)?)
+        } else if step > 1 {
+            // As in cron, "5/15" starts at 5 and runs to the end of the range
+            (value(range)?, max)
+        } else {
+            let value = value(range)?;
+            (value, value)
+        };
+        if first < min || last > max || first > last {
+            return None;
+        }
+        for value in (first..=last).step_by(step) {
+            bits |= 1 << value;
+        }
+    }
+    Some(bits)
+}
+
+impl fmt::Display for JobSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.fields)?;
+        // UTC is the default so it's left out
+        match self.timezone {
+            WindowTimezone::Utc => Ok(()),
+            timezone => write!(f, " {}", timezone),
+        }
+    }
+}
+
+impl TryFrom<String> for JobSchedule {
+    type Error = Error;
+
+    fn try_from(s: String) -> result::Result<Self, Self::Error> { s.parse() }
+}
+
+impl From<JobSchedule> for String {
+    fn from(schedule: JobSchedule) -> Self { schedule.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> SystemTime { DateTime::parse_from_rfc3339(s).unwrap().into() }
+
+    fn next(schedule: &str, after: &str) -> Option<SystemTime> {
+        schedule.parse::<JobSchedule>()
+                .unwrap()
+                .next_after(utc(after))
+    }
+
+    #[test]
+    fn job_schedule_from_str() {
+        let schedule: JobSchedule = "30  2 * * *".parse().unwrap();
+        assert_eq!(schedule.to_string(), "30 2 * * *");
+        let schedule: JobSchedule = "*/15 9-17 * * Mon-Fri local".parse().unwrap();
+        assert_eq!(schedule.to_string(), "*/15 9-17 * * Mon-Fri local");
+        let schedule: JobSchedule = "0 0 1 1,7 * -05:00".parse().unwrap();
+        assert_eq!(schedule.to_string(), "0 0 1 1,7 * -05:00");
+        let schedule: JobSchedule = "0 0 * * * UTC".parse().unwrap();
+        assert_eq!(schedule.to_string(), "0 0 * * *");
+
+        assert!("".parse::<JobSchedule>().is_err());
+        assert!("0 0 * *".parse::<JobSchedule>().is_err());
+        assert!("60 0 * * *".parse::<JobSchedule>().is_err());
+        assert!("0 24 * * *".parse::<JobSchedule>().is_err());
+        assert!("0 0 0 * *".parse::<JobSchedule>().is_err());
+        assert!("0 0 * 13 *".parse::<JobSchedule>().is_err());
+        assert!("0 0 * * 8".parse::<JobSchedule>().is_err());
+        assert!("0 0 * * Fri-Mon".parse::<JobSchedule>().is_err());
+        assert!("*/0 0 * * *".parse::<JobSchedule>().is_err());
+        assert!("0 0 * * * Mars".parse::<JobSchedule>().is_err());
+        assert!("0 0 30 2 *".parse::<JobSchedule>().is_err());
+        assert!("0 0 31 4,6,9,11 *".parse::<JobSchedule>().is_err());
+    }
+
+    #[test]
+    fn job_schedule_next_after() {
+        assert_eq!(next("30 2 * * *", "2021-03-10T01:00:00Z"),
+                   Some(utc("2021-03-10T02:30:00Z")));
+        assert_eq!(next("30 2 * * *", "2021-03-10T02:30:00Z"),
+                   Some(utc("2021-03-11T02:30:00Z")));
+        assert_eq!(next("*/15 * * * *", "2021-03-10T02:31:10Z"),
+                   Some(utc("2021-03-10T02:45:00Z")));
+        assert_eq!(next("5/20 * * * *", "2021-03-10T02:50:00Z"),
+                   Some(utc("2021-03-10T03:05:00Z")));
+        // 2021-03-13 is a Saturday
+        assert_eq!(next("0 9 * * Mon-Fri", "2021-03-13T00:00:00Z"),
+                   Some(utc("2021-03-15T09:00:00Z")));
+        assert_eq!(next("0 9 * * 7", "2021-03-13T00:00:00Z"),
+                   Some(utc("2021-03-14T09:00:00Z")));
+        assert_eq!(next("0 0 29 2 *", "2021-03-01T00:00:00Z"),
+                   Some(utc("2024-02-29T00:00:00Z")));
+        assert_eq!(next("0 2 * * * +02:00", "2021-03-10T01:00:00Z"),
+                   Some(utc("2021-03-11T00:00:00Z")));
+    }
+
+    #[test]
+    fn job_schedule_restricted_days_match_either() {
+        // The 15th of the month or any Monday; 2021-03-10 is a Wednesday
+        assert_eq!(next("0 0 15 * Mon", "2021-03-10T01:00:00Z"),
+                   Some(utc("2021-03-15T00:00:00Z")));
+        assert_eq!(next("0 0 12 * Mon", "2021-03-10T01:00:00Z"),
+                   Some(utc("2021-03-12T00:00:00Z")));
+        // Only the day of the month is restricted
+        assert_eq!(next("0 0 15 * *", "2021-03-16T01:00:00Z"),
+                   Some(utc("2021-04-15T00:00:00Z")));
+    }
+}
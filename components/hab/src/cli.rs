@@ -8,9 +8,12 @@ use clap::{App,
 use habitat_common::{cli::{file_into_idents,
                            is_toml_file},
                      FeatureFlag};
-use habitat_core::{origin::Origin as CoreOrigin,
+use habitat_core::{error::Error as CoreError,
+                   origin::Origin as CoreOrigin,
+                   os::process,
                    package::{Identifiable,
-                             PackageIdent}};
+                             PackageIdent},
+                   service};
 use serde::{Deserialize,
             Serialize};
 use std::{fmt,
@@ -130,6 +133,27 @@ fn valid_fully_qualified_ident(val: String) -> result::Result<(), String> {
 #[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
 fn valid_origin(val: String) -> result::Result<(), String> { CoreOrigin::validate(val) }
 
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_instance_name(val: String) -> result::Result<(), String> {
+    service::validate_instance_name(&val).map_err(|e| e.to_string())
+}
+
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_nice(val: String) -> result::Result<(), String> {
+    val.parse()
+       .map_err(|_| CoreError::InvalidNice(val.clone()))
+       .and_then(process::validate_nice)
+       .map_err(|e| e.to_string())
+}
+
+#[allow(clippy::needless_pass_by_value)] // Signature required by CLAP
+fn valid_ionice(val: String) -> result::Result<(), String> {
+    val.parse()
+       .map_err(|_| CoreError::InvalidIonice(val.clone()))
+       .and_then(process::validate_ionice)
+       .map_err(|e| e.to_string())
+}
+
 ////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
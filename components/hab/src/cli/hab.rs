@@ -26,8 +26,12 @@ use self::{bldr::*,
                  ConfigOptCliCompleters,
                  ConfigOptCliSetup},
            config::{ConfigOptServiceConfigApply,
+                    ConfigOptServiceConfigHistory,
+                    ConfigOptServiceConfigRollback,
                     ConfigOptServiceConfigShow,
                     ServiceConfigApply,
+                    ServiceConfigHistory,
+                    ServiceConfigRollback,
                     ServiceConfigShow},
            file::{ConfigOptFileUpload,
                   FileUpload},
@@ -194,6 +198,10 @@ pub enum Cli {
 pub enum ServiceConfig {
     #[structopt(no_version, aliases = &["ap", "app", "appl"])]
     Apply(ServiceConfigApply),
+    #[structopt(no_version, aliases = &["hi", "his", "hist", "histo", "histor"])]
+    History(ServiceConfigHistory),
+    #[structopt(no_version, aliases = &["ro", "rol", "roll", "rollb", "rollba", "rollbac"])]
+    Rollback(ServiceConfigRollback),
     #[structopt(no_version, aliases = &["sh", "sho"])]
     Show(ServiceConfigShow),
 }
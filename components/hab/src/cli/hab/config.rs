@@ -14,6 +14,8 @@ use structopt::StructOpt;
 /// Commands relating to a Service's runtime config
 pub enum ServiceConfig {
     Apply(ServiceConfigApply),
+    History(ServiceConfigHistory),
+    Rollback(ServiceConfigRollback),
     Show(ServiceConfigShow),
 }
 
@@ -34,12 +36,43 @@ pub struct ServiceConfigApply {
     /// Name of a user key to use for encryption
     #[structopt(short = "u", long = "user")]
     user:           Option<String>,
+    /// Show how the service's rendered configuration files and hooks would change, without
+    /// applying the configuration
+    #[structopt(long = "dry-run")]
+    dry_run:        bool,
     #[structopt(flatten)]
     remote_sup:     RemoteSup,
     #[structopt(flatten)]
     cache_key_path: CacheKeyPath,
 }
 
+/// Shows the versions of a Service Group's configuration that the Supervisor has applied
+#[derive(ConfigOpt, StructOpt)]
+#[structopt(name = "history", no_version, rename_all = "screamingsnake")]
+pub struct ServiceConfigHistory {
+    /// Target service group service.group[@organization] (ex: redis.default or
+    /// foo.default@bazcorp)
+    #[structopt()]
+    pub service_group: ServiceGroup,
+    #[structopt(flatten)]
+    pub remote_sup:    RemoteSup,
+}
+
+/// Applies an earlier version of a Service Group's configuration again, as a new version
+#[derive(ConfigOpt, StructOpt)]
+#[structopt(name = "rollback", no_version, rename_all = "screamingsnake")]
+pub struct ServiceConfigRollback {
+    /// Target service group service.group[@organization] (ex: redis.default or
+    /// foo.default@bazcorp)
+    #[structopt()]
+    pub service_group: ServiceGroup,
+    /// The version to roll back to, as shown by `hab config history` (ex: 41)
+    #[structopt(long = "to")]
+    pub to:            u64,
+    #[structopt(flatten)]
+    pub remote_sup:    RemoteSup,
+}
+
 /// Displays the default configuration options for a service
 #[derive(ConfigOpt, StructOpt)]
 #[structopt(name = "show", no_version, rename_all = "screamingsnake")]
@@ -31,6 +31,7 @@ use habitat_common::{cli::{RING_ENVVAR,
 use habitat_core::{env::Config,
                    fs::HAB_CTL_KEYS_CACHE,
                    package::PackageIdent,
+                   service::MaintenanceWindow,
                    util as core_util};
 use rants::{error::Error as RantsError,
             Address as NatsAddress};
@@ -220,6 +221,11 @@ pub struct SupRun {
     /// The period of time in seconds between Supervisor update checks
     #[structopt(long = "auto-update-period", default_value = "60")]
     pub auto_update_period: DurationProxy,
+    /// A window during which Supervisor updates may be applied (ex: "Sat,Sun 01:00-05:00 UTC" or
+    /// "0 1 * * Sat for 4h"). Updates are still found and downloaded outside of it. This can be
+    /// repeated for multiple windows. Updates are applied at any time if no window is given
+    #[structopt(long = "auto-update-window")]
+    pub auto_update_window: Vec<MaintenanceWindow>,
     /// The period of time in seconds between service update checks
     #[structopt(long = "service-update-period", default_value = "60")]
     pub service_update_period: DurationProxy,
@@ -235,6 +241,14 @@ pub struct SupRun {
     /// successfully after a restart
     #[structopt(long = "service-restart-cooldown-period", default_value = "300")]
     pub service_restart_cooldown_period: DurationProxy,
+    /// The number of times a service may be restarted within the restart window before it is
+    /// marked as failed and no longer restarted. By default services are restarted forever
+    #[structopt(long = "service-max-restarts")]
+    pub service_max_restarts: Option<u32>,
+    /// The period of time in seconds over which service restarts are counted against
+    /// `--service-max-restarts`
+    #[structopt(long = "service-restart-window", default_value = "600")]
+    pub service_restart_window: DurationProxy,
     /// The private key for HTTP Gateway TLS encryption
     ///
     /// Read the private key from KEY_FILE. This should be an RSA private key or PKCS8-encoded
@@ -2,19 +2,35 @@ use super::util::{CacheKeyPath,
                   ConfigOptCacheKeyPath,
                   ConfigOptPkgIdent,
                   ConfigOptRemoteSup,
+                  ConfigOptServiceInstance,
+                  DurationProxy,
+                  EnvVar,
+                  HookTimeout,
                   PkgIdent,
-                  RemoteSup};
-use crate::error::{Error,
-                   Result};
+                  PkgIdentStringySerde,
+                  RemoteSup,
+                  ServiceInstance};
+use crate::{cli::{valid_instance_name,
+                  valid_ionice,
+                  valid_nice},
+            error::{Error,
+                    Result}};
 use clap::AppSettings;
 use configopt::{configopt_fields,
                 ConfigOpt};
 use habitat_common::{FeatureFlag,
                      FEATURE_FLAGS};
-use habitat_core::{os::process::ShutdownTimeout,
-                   package::PackageIdent,
+use habitat_core::{os::process::{CpuAffinity,
+                                 ShutdownTimeout,
+                                 Signal},
+                   package::{PackageIdent,
+                             VersionConstraint},
                    service::{BindingMode,
+                             CanarySize,
                              HealthCheckInterval,
+                             HealthProbe,
+                             JobSchedule,
+                             MaintenanceWindow,
                              ServiceBind,
                              ServiceGroup},
                    ChannelIdent};
@@ -40,15 +56,21 @@ pub const DEFAULT_SVC_CONFIG_DIR: &str = "/hab/sup/default/config/svc";
 pub enum Svc {
     #[structopt(name = "bulkload")]
     BulkLoad(BulkLoad),
+    Drain(SvcDrain),
+    Health(SvcHealth),
+    Hooks(SvcHooks),
     Key(Key),
     #[structopt(no_version)]
     Load(Load),
+    Logs(SvcLogs),
     #[structopt(no_version)]
     Update(Update),
     Start(SvcStart),
     #[structopt(aliases = &["stat", "statu"])]
     Status(SvcStatus),
     Stop(SvcStop),
+    Reset(SvcReset),
+    Undrain(SvcUndrain),
     Unload(SvcUnload),
 }
 
@@ -77,9 +99,59 @@ pub struct SvcStart {
     #[structopt(flatten)]
     pkg_ident:  PkgIdent,
     #[structopt(flatten)]
+    instance:   ServiceInstance,
+    #[structopt(flatten)]
     remote_sup: RemoteSup,
 }
 
+/// Show the output of a Habitat service
+#[derive(ConfigOpt, StructOpt)]
+#[structopt(name = "logs", no_version, rename_all = "screamingsnake")]
+pub struct SvcLogs {
+    #[structopt(flatten)]
+    pub pkg_ident:  PkgIdent,
+    #[structopt(flatten)]
+    pub instance:   ServiceInstance,
+    #[structopt(flatten)]
+    pub remote_sup: RemoteSup,
+    /// Keep showing output as the service writes it
+    #[structopt(short = "f", long = "follow")]
+    pub follow:     bool,
+    /// Only show output written within this many seconds
+    #[structopt(long = "since")]
+    pub since:      Option<DurationProxy>,
+}
+
+/// Show the recent health checks of a Habitat service
+#[derive(ConfigOpt, StructOpt)]
+#[structopt(name = "health", no_version, rename_all = "screamingsnake")]
+pub struct SvcHealth {
+    #[structopt(flatten)]
+    pub pkg_ident:  PkgIdent,
+    #[structopt(flatten)]
+    pub instance:   ServiceInstance,
+    #[structopt(flatten)]
+    pub remote_sup: RemoteSup,
+    /// Also show what the health check hook wrote to standard output and standard error
+    #[structopt(long = "output")]
+    pub output:     bool,
+}
+
+/// Show the recent runs of a Habitat service's hooks
+#[derive(ConfigOpt, StructOpt)]
+#[structopt(name = "hooks", no_version, rename_all = "screamingsnake")]
+pub struct SvcHooks {
+    #[structopt(flatten)]
+    pub pkg_ident:  PkgIdent,
+    #[structopt(flatten)]
+    pub instance:   ServiceInstance,
+    #[structopt(flatten)]
+    pub remote_sup: RemoteSup,
+    /// Also show what each run wrote to standard output and standard error
+    #[structopt(long = "output")]
+    pub output:     bool,
+}
+
 /// Query the status of Habitat services
 #[derive(ConfigOpt, StructOpt)]
 #[structopt(name = "status", no_version, rename_all = "screamingsnake")]
@@ -98,6 +170,8 @@ pub struct SvcStop {
     #[structopt(flatten)]
     pkg_ident:        PkgIdent,
     #[structopt(flatten)]
+    instance:         ServiceInstance,
+    #[structopt(flatten)]
     remote_sup:       RemoteSup,
     /// The delay in seconds after sending the shutdown signal to wait before killing the
     /// service process
@@ -107,6 +181,45 @@ pub struct SvcStop {
     shutdown_timeout: Option<ShutdownTimeout>,
 }
 
+/// Restart a Habitat service which failed after too many restarts.
+#[derive(ConfigOpt, StructOpt)]
+#[structopt(name = "reset", no_version, rename_all = "screamingsnake")]
+pub struct SvcReset {
+    #[structopt(flatten)]
+    pub pkg_ident:  PkgIdent,
+    #[structopt(flatten)]
+    pub instance:   ServiceInstance,
+    #[structopt(flatten)]
+    pub remote_sup: RemoteSup,
+}
+
+/// Take a Habitat service out of rotation without stopping it
+///
+/// Drained members are left out of binds and `eachAlive` blocks in the templates of other
+/// services until they are undrained.
+#[derive(ConfigOpt, StructOpt)]
+#[structopt(name = "drain", no_version, rename_all = "screamingsnake")]
+pub struct SvcDrain {
+    #[structopt(flatten)]
+    pub pkg_ident:  PkgIdent,
+    #[structopt(flatten)]
+    pub instance:   ServiceInstance,
+    #[structopt(flatten)]
+    pub remote_sup: RemoteSup,
+}
+
+/// Put a drained Habitat service back into rotation
+#[derive(ConfigOpt, StructOpt)]
+#[structopt(name = "undrain", no_version, rename_all = "screamingsnake")]
+pub struct SvcUndrain {
+    #[structopt(flatten)]
+    pub pkg_ident:  PkgIdent,
+    #[structopt(flatten)]
+    pub instance:   ServiceInstance,
+    #[structopt(flatten)]
+    pub remote_sup: RemoteSup,
+}
+
 #[derive(ConfigOpt, StructOpt)]
 #[structopt(no_version)]
 /// Commands relating to Habitat service keys
@@ -159,6 +272,10 @@ pub struct SharedLoad {
     #[structopt(long = "group", default_value = &*GROUP_DEFAULT)]
     #[serde(default = "GROUP_DEFAULT::get")]
     pub group:                 String,
+    /// Load a named instance of the service, so that several instances of a package can run
+    /// side by side with their own configuration, data, and service group
+    #[structopt(long = "instance", validator = valid_instance_name)]
+    pub instance:              Option<String>,
     /// Service topology
     #[structopt(long = "topology",
             short = "t",
@@ -168,7 +285,7 @@ pub struct SharedLoad {
     #[structopt(long = "strategy",
                 short = "s",
                 default_value = "none",
-                possible_values = &["none", "at-once", "rolling"])]
+                possible_values = &["none", "at-once", "rolling", "canary"])]
     #[serde(default)]
     pub strategy:              habitat_sup_protocol::types::UpdateStrategy,
     /// The condition dictating when this service should update
@@ -231,6 +348,123 @@ pub struct SharedLoad {
     /// Use the package config from this path rather than the package itself
     #[structopt(long = "config-from")]
     pub config_from:           Option<PathBuf>,
+    /// One or more services on this Supervisor that must be up, with an ok or warning health
+    /// check result, before this service is started (ex: core/redis). The service waits for them
+    /// for as long as it takes. Services are stopped in the reverse order.
+    #[structopt(long = "dependency")]
+    #[serde(default)]
+    pub dependency:            Vec<PkgIdentStringySerde>,
+    /// The hard memory limit in bytes for the service's processes (Linux with cgroup v2 only)
+    #[structopt(long = "memory-max")]
+    pub memory_max:            Option<u64>,
+    /// The CPU bandwidth limit for the service's processes in millicpus, where 1000 is one full
+    /// CPU (Linux with cgroup v2 only)
+    #[structopt(long = "cpu-max")]
+    pub cpu_max:               Option<u32>,
+    /// The maximum number of processes and threads the service may run (Linux with cgroup v2
+    /// only)
+    #[structopt(long = "pids-max")]
+    pub pids_max:              Option<u64>,
+    /// The proportional IO weight of the service, between 1 and 10000 (Linux with cgroup v2
+    /// only)
+    #[structopt(long = "io-weight")]
+    pub io_weight:             Option<u32>,
+    /// The maximum number of open file descriptors for the service (overrides the package's
+    /// default)
+    #[structopt(long = "rlimit-nofile")]
+    pub rlimit_nofile:         Option<u64>,
+    /// The maximum number of processes for the service user (overrides the package's default)
+    #[structopt(long = "rlimit-nproc")]
+    pub rlimit_nproc:          Option<u64>,
+    /// The maximum size of a core file in bytes (overrides the package's default)
+    #[structopt(long = "rlimit-core")]
+    pub rlimit_core:           Option<u64>,
+    /// The maximum amount of locked memory in bytes (overrides the package's default)
+    #[structopt(long = "rlimit-memlock")]
+    pub rlimit_memlock:        Option<u64>,
+    /// The scheduling priority of the service, from -20 (highest) to 19 (lowest) (overrides the
+    /// package's default)
+    #[structopt(long = "nice", allow_hyphen_values = true, validator = valid_nice)]
+    pub nice:                  Option<i32>,
+    /// The best-effort IO scheduling priority of the service, from 0 (highest) to 7 (lowest)
+    /// (overrides the package's default; Linux only)
+    #[structopt(long = "ionice", validator = valid_ionice)]
+    pub ionice:                Option<u32>,
+    /// The CPUs the service may run on (ex: 0-3,8) (overrides the package's default; Linux only)
+    #[structopt(long = "cpu-affinity")]
+    pub cpu_affinity:          Option<CpuAffinity>,
+    /// Restart the service after this many consecutive critical health check results
+    #[structopt(long = "liveness-threshold")]
+    pub liveness_threshold:    Option<u32>,
+    /// The period of time in seconds after the service starts during which health check results
+    /// do not count towards `--liveness-threshold`
+    #[structopt(long = "liveness-grace-period")]
+    pub liveness_grace_period: Option<u64>,
+    /// The number (ex: 2) or percentage (ex: 10%) of members to update first when using the
+    /// canary update strategy [default: 1]
+    #[structopt(long = "canary-size")]
+    pub canary_size:           Option<CanarySize>,
+    /// The period of time in seconds the canaries must stay ready before the rest of the group
+    /// is updated [default: 300]
+    #[structopt(long = "canary-bake-period")]
+    pub canary_bake_period:    Option<u64>,
+    /// The number of members that update at the same time during a rolling or canary update
+    /// [default: 1]
+    #[structopt(long = "update-batch-size")]
+    pub update_batch_size:     Option<u32>,
+    /// During a rolling or canary update, the period of time in seconds to wait for updated
+    /// members to report an OK health check before the next batch updates. The update halts if
+    /// they do not.
+    #[structopt(long = "update-health-timeout")]
+    pub update_health_timeout: Option<u64>,
+    /// A window during which updates to this service may be applied (ex: "Sat,Sun 01:00-05:00
+    /// UTC" or "0 1 * * Sat for 4h"). Updates are still found and downloaded outside of it. This
+    /// can be repeated for multiple windows. Updates are applied at any time if no window is
+    /// given.
+    #[structopt(long = "update-window")]
+    #[serde(default)]
+    pub update_window:         Vec<MaintenanceWindow>,
+    /// An environment variable to set for the service and its hooks (ex: KEY=VALUE). This can be
+    /// repeated for multiple variables.
+    #[structopt(long = "env")]
+    #[serde(default)]
+    pub env:                   Vec<EnvVar>,
+    /// A file of KEY=VALUE lines to set as environment variables for the service and its hooks.
+    /// Variables given with `--env` take precedence.
+    #[structopt(long = "env-file")]
+    pub env_file:              Option<PathBuf>,
+    /// Run the service as a job on a cron-style schedule rather than keeping it running (ex: "30
+    /// 2 * * *"). The fields are the minute, hour, day of the month, month and day of the week,
+    /// followed by an optional time zone.
+    #[structopt(long = "schedule")]
+    pub schedule:              Option<JobSchedule>,
+    /// Only run the job on the elected leader of the service group (requires `--topology
+    /// leader`)
+    #[structopt(long = "job-leader-only", requires = "SCHEDULE")]
+    #[serde(default)]
+    pub job_leader_only:       bool,
+    /// A port the package exposes on which the Supervisor holds a listening socket for the
+    /// service, passed to it as with systemd socket activation (Linux only). The socket stays
+    /// open while the service restarts or updates. This can be repeated for multiple ports.
+    #[structopt(long = "listen-port")]
+    #[serde(default)]
+    pub listen_port:           Vec<u16>,
+    /// A signal to send the service when its configuration changes instead of restarting it (ex:
+    /// HUP). Overrides the package's reload signal.
+    #[structopt(long = "reload-signal")]
+    pub reload_signal:         Option<Signal>,
+    /// How long one of the service's hooks may run before it is killed (ex: init=300). This can
+    /// be repeated for multiple hooks. Overrides the package's timeout for the hook.
+    #[structopt(long = "hook-timeout")]
+    #[serde(default)]
+    pub hook_timeout:          Vec<HookTimeout>,
+    /// A check the Supervisor runs itself in place of the health check hook: an http:// URL and
+    /// optional expected status, a tcp:// address, or exec: followed by a command (ex:
+    /// "http://localhost:8080/healthz 200"). This can be repeated for multiple probes. Replaces
+    /// the package's health probes.
+    #[structopt(long = "health-probe")]
+    #[serde(default)]
+    pub health_probe:          Vec<HealthProbe>,
 }
 
 fn load_default_config_files() -> Vec<PathBuf> {
@@ -275,6 +509,8 @@ pub struct SvcUnload {
     #[structopt(flatten)]
     pkg_ident:        PkgIdent,
     #[structopt(flatten)]
+    instance:         ServiceInstance,
+    #[structopt(flatten)]
     remote_sup:       RemoteSup,
     /// The delay in seconds after sending the shutdown signal to wait before killing the
     /// service process
@@ -327,9 +563,19 @@ pub fn shared_load_cli_to_ctl(ident: PackageIdent,
                          ui::UIWriter};
     #[cfg(target_os = "windows")]
     use habitat_core::crypto::dpapi;
-    use habitat_sup_protocol::{ctl::{ServiceBindList,
+    use habitat_sup_protocol::{ctl::{HealthProbeList,
+                                     HookTimeouts,
+                                     MaintenanceWindowList,
+                                     PackageIdentList,
+                                     PortList,
+                                     ServiceBindList,
                                      SvcLoad},
-                               types::{HealthCheckInterval,
+                               types::{CanaryPolicy,
+                                       HealthCheckInterval,
+                                       LivenessPolicy,
+                                       ProcessControls,
+                                       ResourceLimits,
+                                       RolloutPolicy,
                                        ServiceBind}};
 
     // TODO (DM): This check can eventually be removed.
@@ -348,6 +594,97 @@ pub fn shared_load_cli_to_ctl(ident: PackageIdent,
                                                  .collect(), })
     };
 
+    let dependencies = if shared_load.dependency.is_empty() {
+        None
+    } else {
+        Some(shared_load.dependency
+                        .into_iter()
+                        .map(PackageIdent::from)
+                        .collect::<PackageIdentList>())
+    };
+
+    let resource_limits = ResourceLimits { memory_max: shared_load.memory_max,
+                                           cpu_max:    shared_load.cpu_max,
+                                           pids_max:   shared_load.pids_max,
+                                           io_weight:  shared_load.io_weight, };
+    let resource_limits = if resource_limits == ResourceLimits::default() {
+        None
+    } else {
+        Some(resource_limits)
+    };
+
+    let process_controls = ProcessControls { nofile:       shared_load.rlimit_nofile,
+                                             nproc:        shared_load.rlimit_nproc,
+                                             core:         shared_load.rlimit_core,
+                                             memlock:      shared_load.rlimit_memlock,
+                                             nice:         shared_load.nice,
+                                             ionice:       shared_load.ionice,
+                                             cpu_affinity: shared_load.cpu_affinity
+                                                                      .map(CpuAffinity::mask), };
+    let process_controls = if process_controls == ProcessControls::default() {
+        None
+    } else {
+        Some(process_controls)
+    };
+
+    let liveness = LivenessPolicy { failure_threshold: shared_load.liveness_threshold,
+                                    grace_period:      shared_load.liveness_grace_period, };
+    let liveness = if liveness == LivenessPolicy::default() {
+        None
+    } else {
+        Some(liveness)
+    };
+
+    let canary = CanaryPolicy { size:        shared_load.canary_size.map(|size| size.to_string()),
+                                bake_period: shared_load.canary_bake_period, };
+    let canary = if canary == CanaryPolicy::default() {
+        None
+    } else {
+        Some(canary)
+    };
+
+    let rollout = RolloutPolicy { batch_size:     shared_load.update_batch_size,
+                                  health_timeout: shared_load.update_health_timeout, };
+    let rollout = if rollout == RolloutPolicy::default() {
+        None
+    } else {
+        Some(rollout)
+    };
+
+    let update_windows = if shared_load.update_window.is_empty() {
+        None
+    } else {
+        Some(shared_load.update_window
+                        .into_iter()
+                        .collect::<MaintenanceWindowList>())
+    };
+
+    let env = service_env(shared_load.env, shared_load.env_file.as_deref())?;
+    let env = if env.vars.is_empty() { None } else { Some(env) };
+
+    let listen_ports = if shared_load.listen_port.is_empty() {
+        None
+    } else {
+        Some(PortList { ports: shared_load.listen_port.into_iter().map(u32::from).collect(), })
+    };
+
+    let hook_timeouts = if shared_load.hook_timeout.is_empty() {
+        None
+    } else {
+        Some(HookTimeouts { timeouts: shared_load.hook_timeout
+                                                 .into_iter()
+                                                 .map(|timeout| (timeout.hook, timeout.secs))
+                                                 .collect(), })
+    };
+
+    let health_probes = if shared_load.health_probe.is_empty() {
+        None
+    } else {
+        Some(shared_load.health_probe
+                        .into_iter()
+                        .collect::<HealthProbeList>())
+    };
+
     let config_from = if let Some(config_from) = shared_load.config_from {
         warn!("");
         warn!("WARNING: Setting '--config-from' should only be used in development, not \
@@ -368,6 +705,7 @@ pub fn shared_load_cli_to_ctl(ident: PackageIdent,
     let svc_encrypted_password = None;
 
     Ok(SvcLoad { ident: Some(ident.into()),
+                 instance: shared_load.instance,
                  binds,
                  binding_mode: Some(shared_load.binding_mode as i32),
                  bldr_url: Some(habitat_core::url::bldr_url(shared_load.bldr_url)),
@@ -381,7 +719,34 @@ pub fn shared_load_cli_to_ctl(ident: PackageIdent,
                  health_check_interval:
                      Some(HealthCheckInterval { seconds: shared_load.health_check_interval, }),
                  shutdown_timeout: shared_load.shutdown_timeout.map(u32::from),
-                 update_condition: Some(shared_load.update_condition as i32) })
+                 update_condition: Some(shared_load.update_condition as i32),
+                 dependencies,
+                 resource_limits,
+                 process_controls,
+                 liveness,
+                 canary,
+                 rollout,
+                 update_windows,
+                 env,
+                 schedule: shared_load.schedule.map(|schedule| schedule.to_string()),
+                 job_leader_only: Some(shared_load.job_leader_only),
+                 listen_ports,
+                 reload_signal: shared_load.reload_signal.map(|signal| signal.to_string()),
+                 hook_timeouts,
+                 health_probes })
+}
+
+/// Combine the variables from an env file with those given on the command line. Variables given
+/// on the command line take precedence.
+fn service_env(vars: Vec<EnvVar>, env_file: Option<&Path>) -> Result<ctl::ServiceEnv> {
+    let file_vars = match env_file {
+        Some(path) => EnvVar::from_file(path)?,
+        None => Vec::new(),
+    };
+    Ok(file_vars.into_iter()
+                .chain(vars)
+                .map(|var| (var.name, var.value))
+                .collect())
 }
 
 impl TryFrom<Load> for habitat_sup_protocol::ctl::SvcLoad {
@@ -407,6 +772,10 @@ pub struct Update {
     #[serde(flatten)]
     pkg_ident: PkgIdent,
 
+    #[structopt(flatten)]
+    #[serde(flatten)]
+    pub instance: ServiceInstance,
+
     #[structopt(flatten)]
     #[serde(flatten)]
     pub remote_sup: RemoteSup,
@@ -436,7 +805,7 @@ pub struct Update {
     /// The update strategy
     #[structopt(long = "strategy",
                 short = "s",
-                possible_values = &["none", "at-once", "rolling"])]
+                possible_values = &["none", "at-once", "rolling", "canary"])]
     pub strategy: Option<habitat_sup_protocol::types::UpdateStrategy>,
 
     /// The condition dictating when this service should update
@@ -482,13 +851,85 @@ pub struct Update {
     #[cfg(target_os = "windows")]
     #[structopt(long = "password")]
     pub password: Option<String>,
+
+    /// One or more services on this Supervisor that must be up, with an ok or warning health
+    /// check result, before this service is started (ex: core/redis). The service waits for them
+    /// for as long as it takes. Services are stopped in the reverse order.
+    #[structopt(long = "dependency")]
+    #[serde(default)]
+    pub dependency: Option<Vec<PkgIdentStringySerde>>,
+
+    /// An environment variable to set for the service and its hooks (ex: KEY=VALUE). This
+    /// replaces all of the service's variables and can be repeated for multiple variables.
+    #[structopt(long = "env")]
+    #[serde(default)]
+    pub env: Option<Vec<EnvVar>>,
+
+    /// A file of KEY=VALUE lines to set as environment variables for the service and its hooks.
+    /// Variables given with `--env` take precedence.
+    #[structopt(long = "env-file")]
+    pub env_file: Option<PathBuf>,
+
+    /// Keep the service on its current release until `--unhold` is given
+    #[structopt(long = "hold", conflicts_with = "UNHOLD")]
+    #[serde(default)]
+    pub hold: bool,
+
+    /// Allow updates to a service that was held with `--hold`
+    #[structopt(long = "unhold")]
+    #[serde(default)]
+    pub unhold: bool,
+
+    /// Only update the service to versions satisfying this constraint
+    ///
+    /// The constraint is a version prefix (ex: 1.2) or a comma-separated list of comparisons
+    /// (ex: ">=1.2, <2.0").
+    #[structopt(long = "pin", conflicts_with = "UNPIN")]
+    pub pin: Option<VersionConstraint>,
+
+    /// Remove the version constraint set with `--pin`
+    #[structopt(long = "unpin")]
+    #[serde(default)]
+    pub unpin: bool,
+
+    /// Run the service as a job on a cron-style schedule (ex: "30 2 * * *")
+    #[structopt(long = "schedule", conflicts_with = "UNSCHEDULE")]
+    pub schedule: Option<JobSchedule>,
+
+    /// Run a job service as a long-running service again
+    #[structopt(long = "unschedule")]
+    #[serde(default)]
+    pub unschedule: bool,
 }
 
 impl TryFrom<Update> for ctl::SvcUpdate {
     type Error = Error;
 
     fn try_from(u: Update) -> Result<Self> {
+        let hold = match (u.hold, u.unhold) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        };
+        // An empty constraint tells the Supervisor to remove the pin
+        let pin = match (u.pin, u.unpin) {
+            (Some(pin), _) => Some(pin.to_string()),
+            (_, true) => Some(String::new()),
+            _ => None,
+        };
+        // Likewise an empty schedule turns the job back into a long-running service
+        let job_schedule = match (u.schedule, u.unschedule) {
+            (Some(schedule), _) => Some(schedule.to_string()),
+            (_, true) => Some(String::new()),
+            _ => None,
+        };
+        let vars = if u.env.is_some() || u.env_file.is_some() {
+            Some(service_env(u.env.unwrap_or_default(), u.env_file.as_deref())?)
+        } else {
+            None
+        };
         let msg = ctl::SvcUpdate { ident: Some(From::from(u.pkg_ident.pkg_ident())),
+                                   instance: u.instance.value,
                                    // We are explicitly *not* using the environment variable as a
                                    // fallback.
                                    bldr_url: u.bldr_url.map(|u| u.to_string()),
@@ -501,6 +942,15 @@ impl TryFrom<Update> for ctl::SvcUpdate {
                                    update_strategy: u.strategy.map(|v| v as i32),
                                    update_condition: u.update_condition.map(|v| v as i32),
                                    shutdown_timeout: u.shutdown_timeout.map(Into::into),
+                                   dependencies: u.dependency.map(|d| {
+                                                                 d.into_iter()
+                                                                  .map(PackageIdent::from)
+                                                                  .collect()
+                                                             }),
+                                   update_hold: hold,
+                                   update_pin: pin,
+                                   env: vars,
+                                   schedule: job_schedule,
                                    #[cfg(windows)]
                                    svc_encrypted_password: u.password,
                                    #[cfg(not(windows))]
@@ -510,6 +960,7 @@ impl TryFrom<Update> for ctl::SvcUpdate {
         // specified *something* to change. If they didn't, all the
         // fields would end up as `None`, and that would be an error.
         if let ctl::SvcUpdate { ident: _,
+                                instance: _,
                                 binds: None,
                                 binding_mode: None,
                                 bldr_url: None,
@@ -520,7 +971,12 @@ impl TryFrom<Update> for ctl::SvcUpdate {
                                 update_strategy: None,
                                 health_check_interval: None,
                                 shutdown_timeout: None,
-                                update_condition: None, } = &msg
+                                update_condition: None,
+                                dependencies: None,
+                                update_hold: None,
+                                update_pin: None,
+                                env: None,
+                                schedule: None, } = &msg
         {
             Err(Error::ArgumentError("No fields specified for update".to_string()))
         } else {
@@ -1,14 +1,19 @@
 use super::{svc::{Load,
                   Svc,
                   Update},
-            Hab};
+            Hab,
+            ServiceConfig};
 use crate::cli;
 use clap::{App,
            AppSettings,
            ArgSettings};
 use configopt::ConfigOpt;
 use habitat_common::FeatureFlag;
-use std::str;
+use habitat_sup_protocol::ctl;
+use std::{convert::TryFrom,
+          io::Write,
+          str};
+use tempfile::NamedTempFile;
 
 fn feature_flags_for_cli_test() -> FeatureFlag {
     let mut f = FeatureFlag::empty();
@@ -465,3 +470,357 @@ fn test_hab_svc_update_empty_binds() {
     let update = extract_hab_svc_update(hab);
     assert_eq!(update.bind.unwrap().len(), 1);
 }
+
+#[test]
+fn test_hab_svc_update_hold_and_pin() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "update",
+                                                  "core/redis",
+                                                  "--hold"]).unwrap();
+    let msg = ctl::SvcUpdate::try_from(extract_hab_svc_update(hab)).unwrap();
+    assert_eq!(msg.update_hold, Some(true));
+    assert_eq!(msg.update_pin, None);
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "update",
+                                                  "core/redis",
+                                                  "--unhold",
+                                                  "--pin",
+                                                  ">=1.2,<2.0"]).unwrap();
+    let msg = ctl::SvcUpdate::try_from(extract_hab_svc_update(hab)).unwrap();
+    assert_eq!(msg.update_hold, Some(false));
+    assert_eq!(msg.update_pin, Some(String::from(">=1.2, <2.0")));
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "update",
+                                                  "core/redis",
+                                                  "--unpin"]).unwrap();
+    let msg = ctl::SvcUpdate::try_from(extract_hab_svc_update(hab)).unwrap();
+    assert_eq!(msg.update_pin, Some(String::new()));
+
+    assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                "svc",
+                                                "update",
+                                                "core/redis",
+                                                "--hold",
+                                                "--unhold"]).is_err());
+    assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                "svc",
+                                                "update",
+                                                "core/redis",
+                                                "--pin",
+                                                "latest"]).is_err());
+}
+
+#[test]
+fn test_hab_svc_load_env() {
+    let mut env_file = NamedTempFile::new().unwrap();
+    writeln!(env_file,
+             "# comment\n\nLOG_LEVEL=info\nGREETING=\"hello world\"").unwrap();
+    let path = env_file.path().to_str().unwrap();
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "load",
+                                                  "core/redis",
+                                                  "--env-file",
+                                                  path,
+                                                  "--env",
+                                                  "LOG_LEVEL=debug",
+                                                  "--env",
+                                                  "EMPTY="]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    let vars = msg.env.unwrap().vars;
+    assert_eq!(vars.len(), 3);
+    assert_eq!(vars["LOG_LEVEL"], "debug");
+    assert_eq!(vars["GREETING"], "hello world");
+    assert_eq!(vars["EMPTY"], "");
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab", "svc", "load", "core/redis"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.env, None);
+
+    assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                "svc",
+                                                "load",
+                                                "core/redis",
+                                                "--env",
+                                                "=value"]).is_err());
+}
+
+#[test]
+fn test_hab_svc_update_env() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "update",
+                                                  "core/redis",
+                                                  "--env",
+                                                  "LOG_LEVEL=debug"]).unwrap();
+    let msg = ctl::SvcUpdate::try_from(extract_hab_svc_update(hab)).unwrap();
+    assert_eq!(msg.env.unwrap().vars["LOG_LEVEL"], "debug");
+
+    // An empty `--env` clears the service's variables
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "update",
+                                                  "core/redis",
+                                                  "--env"]).unwrap();
+    let msg = ctl::SvcUpdate::try_from(extract_hab_svc_update(hab)).unwrap();
+    assert!(msg.env.unwrap().vars.is_empty());
+}
+
+#[test]
+fn test_hab_svc_load_and_update_instance() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "load",
+                                                  "core/redis",
+                                                  "--instance",
+                                                  "cache"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.instance, Some(String::from("cache")));
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "update",
+                                                  "core/redis",
+                                                  "--instance",
+                                                  "cache",
+                                                  "--shutdown-timeout",
+                                                  "10"]).unwrap();
+    let msg = ctl::SvcUpdate::try_from(extract_hab_svc_update(hab)).unwrap();
+    assert_eq!(msg.instance, Some(String::from("cache")));
+
+    // Naming the instance is not an update by itself
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "update",
+                                                  "core/redis",
+                                                  "--instance",
+                                                  "cache"]).unwrap();
+    assert!(ctl::SvcUpdate::try_from(extract_hab_svc_update(hab)).is_err());
+
+    assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                "svc",
+                                                "load",
+                                                "core/redis",
+                                                "--instance",
+                                                "redis+cache"]).is_err());
+}
+
+#[test]
+fn test_hab_svc_load_and_update_schedule() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "load",
+                                                  "core/backup",
+                                                  "--topology",
+                                                  "leader",
+                                                  "--schedule",
+                                                  "30  2 * * *",
+                                                  "--job-leader-only"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.schedule, Some(String::from("30 2 * * *")));
+    assert_eq!(msg.job_leader_only, Some(true));
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab", "svc", "load", "core/redis"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.schedule, None);
+    assert_eq!(msg.job_leader_only, Some(false));
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "update",
+                                                  "core/backup",
+                                                  "--schedule",
+                                                  "0 * * * *"]).unwrap();
+    let msg = ctl::SvcUpdate::try_from(extract_hab_svc_update(hab)).unwrap();
+    assert_eq!(msg.schedule, Some(String::from("0 * * * *")));
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "update",
+                                                  "core/backup",
+                                                  "--unschedule"]).unwrap();
+    let msg = ctl::SvcUpdate::try_from(extract_hab_svc_update(hab)).unwrap();
+    assert_eq!(msg.schedule, Some(String::new()));
+
+    assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                "svc",
+                                                "load",
+                                                "core/backup",
+                                                "--job-leader-only"]).is_err());
+    assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                "svc",
+                                                "load",
+                                                "core/backup",
+                                                "--schedule",
+                                                "every night"]).is_err());
+}
+
+#[test]
+fn test_hab_svc_load_listen_port() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "load",
+                                                  "core/nginx",
+                                                  "--listen-port",
+                                                  "80",
+                                                  "--listen-port",
+                                                  "443"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.listen_ports.unwrap().ports, vec![80, 443]);
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab", "svc", "load", "core/nginx"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.listen_ports, None);
+
+    assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                "svc",
+                                                "load",
+                                                "core/nginx",
+                                                "--listen-port",
+                                                "70000"]).is_err());
+}
+
+#[test]
+fn test_hab_svc_load_reload_signal() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "load",
+                                                  "core/nginx",
+                                                  "--reload-signal",
+                                                  "HUP"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.reload_signal, Some("HUP".to_string()));
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab", "svc", "load", "core/nginx"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.reload_signal, None);
+
+    assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                "svc",
+                                                "load",
+                                                "core/nginx",
+                                                "--reload-signal",
+                                                "SIGHUP"]).is_err());
+}
+
+#[test]
+fn test_hab_svc_load_scheduling_priorities() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "load",
+                                                  "core/nginx",
+                                                  "--nice",
+                                                  "-20",
+                                                  "--ionice",
+                                                  "7"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    let process_controls = msg.process_controls.unwrap();
+    assert_eq!(process_controls.nice, Some(-20));
+    assert_eq!(process_controls.ionice, Some(7));
+
+    for (flag, invalid) in &[("--nice", "-21"),
+                             ("--nice", "20"),
+                             ("--nice", "low"),
+                             ("--ionice", "8"),
+                             ("--ionice", "-1")]
+    {
+        assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                    "svc",
+                                                    "load",
+                                                    "core/nginx",
+                                                    flag,
+                                                    invalid]).is_err());
+    }
+}
+
+#[test]
+fn test_hab_svc_load_hook_timeout() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "load",
+                                                  "core/nginx",
+                                                  "--hook-timeout",
+                                                  "init=300",
+                                                  "--hook-timeout",
+                                                  "reconfigure = 30"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    let timeouts = msg.hook_timeouts.unwrap().timeouts;
+    assert_eq!(timeouts.len(), 2);
+    assert_eq!(timeouts.get("init"), Some(&300));
+    assert_eq!(timeouts.get("reconfigure"), Some(&30));
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab", "svc", "load", "core/nginx"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.hook_timeouts, None);
+
+    for invalid in &["init",
+                     "init=",
+                     "=300",
+                     "init=forever",
+                     "init=0",
+                     "post_run=30",
+                     "run=30"]
+    {
+        assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                    "svc",
+                                                    "load",
+                                                    "core/nginx",
+                                                    "--hook-timeout",
+                                                    invalid]).is_err());
+    }
+}
+
+#[test]
+fn test_hab_svc_load_health_probe() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "svc",
+                                                  "load",
+                                                  "core/nginx",
+                                                  "--health-probe",
+                                                  "http://localhost:8080/healthz 204",
+                                                  "--health-probe",
+                                                  "tcp://localhost:80"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.health_probes.unwrap().probes,
+               vec!["http://localhost:8080/healthz 204", "tcp://localhost:80"]);
+
+    let hab = Hab::try_from_iter_with_configopt(&["hab", "svc", "load", "core/nginx"]).unwrap();
+    let msg = ctl::SvcLoad::try_from(extract_hab_svc_load(hab)).unwrap();
+    assert_eq!(msg.health_probes, None);
+
+    for invalid in &["https://localhost/", "tcp://localhost", "exec:"] {
+        assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                    "svc",
+                                                    "load",
+                                                    "core/nginx",
+                                                    "--health-probe",
+                                                    invalid]).is_err());
+    }
+}
+
+#[test]
+fn test_hab_config_rollback() {
+    let hab = Hab::try_from_iter_with_configopt(&["hab",
+                                                  "config",
+                                                  "rollback",
+                                                  "redis.default",
+                                                  "--to",
+                                                  "3"]).unwrap();
+    if let Hab::Config(ServiceConfig::Rollback(rollback)) = hab {
+        assert_eq!(rollback.service_group.to_string(), "redis.default");
+        assert_eq!(rollback.to, 3);
+    } else {
+        panic!("expected to find `hab config rollback`")
+    }
+
+    assert!(Hab::try_from_iter_with_configopt(&["hab",
+                                                "config",
+                                                "rollback",
+                                                "redis.default"]).is_err());
+}
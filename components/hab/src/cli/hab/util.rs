@@ -1,6 +1,7 @@
 pub mod tls;
 
-use crate::{cli::valid_fully_qualified_ident,
+use crate::{cli::{valid_fully_qualified_ident,
+                  valid_instance_name},
             error::Error};
 use configopt::{self,
                 ConfigOpt};
@@ -12,7 +13,8 @@ use habitat_core::{crypto::CACHE_KEY_PATH_ENV_VAR,
                    env as henv,
                    fs as hab_core_fs,
                    origin::Origin,
-                   package::PackageIdent,
+                   package::{install::TIMED_HOOKS,
+                             PackageIdent},
                    url::{bldr_url_from_env,
                          DEFAULT_BLDR_URL},
                    AUTH_TOKEN_ENVVAR};
@@ -25,7 +27,8 @@ use std::{convert::TryFrom,
           fmt,
           net::SocketAddr,
           num::ParseIntError,
-          path::PathBuf,
+          path::{Path,
+                 PathBuf},
           result,
           str::FromStr,
           time::Duration};
@@ -143,7 +146,7 @@ pub struct CacheKeyPath {
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(try_from = "String", into = "String")]
-struct PkgIdentStringySerde(PackageIdent);
+pub struct PkgIdentStringySerde(PackageIdent);
 
 impl FromStr for PkgIdentStringySerde {
     type Err = habitat_core::Error;
@@ -165,6 +168,10 @@ impl From<PkgIdentStringySerde> for String {
     fn from(pkg_ident: PkgIdentStringySerde) -> Self { pkg_ident.to_string() }
 }
 
+impl From<PkgIdentStringySerde> for PackageIdent {
+    fn from(pkg_ident: PkgIdentStringySerde) -> Self { pkg_ident.0 }
+}
+
 #[derive(Clone, ConfigOpt, Debug, StructOpt, Deserialize, Serialize)]
 #[configopt(derive(Clone, Serialize, Debug), attrs(serde))]
 #[structopt(no_version)]
@@ -205,6 +212,15 @@ impl RemoteSup {
     pub fn inner(&self) -> Option<&ResolvedListenCtlAddr> { self.remote_sup.as_ref() }
 }
 
+#[derive(Clone, ConfigOpt, StructOpt, Deserialize, Debug)]
+#[configopt(derive(Serialize, Clone, Debug))]
+#[structopt(no_version)]
+pub struct ServiceInstance {
+    /// The name of the instance of the service. Leave unset for the default instance
+    #[structopt(name = "INSTANCE", long = "instance", validator = valid_instance_name)]
+    pub value: Option<String>,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(from = "u64", into = "u64")]
 pub struct DurationProxy(Duration);
@@ -347,3 +363,100 @@ impl SubjectAlternativeName {
 }
 
 habitat_core::impl_try_from_string_and_into_string!(SubjectAlternativeName);
+
+/// An environment variable given as `KEY=VALUE`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct EnvVar {
+    pub name:  String,
+    pub value: String,
+}
+
+impl FromStr for EnvVar {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                Ok(EnvVar { name:  name.trim().to_string(),
+                            value: value.to_string(), })
+            }
+            _ => {
+                let msg = format!("Invalid environment variable '{}', must be KEY=VALUE", s);
+                Err(Error::ArgumentError(msg))
+            }
+        }
+    }
+}
+
+impl fmt::Display for EnvVar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+habitat_core::impl_try_from_string_and_into_string!(EnvVar);
+
+impl EnvVar {
+    /// Read environment variables from a file of `KEY=VALUE` lines. Blank lines and lines
+    /// starting with `#` are skipped, and a value wrapped in matching quotes is unquoted.
+    pub fn from_file(path: &Path) -> Result<Vec<Self>, Error> {
+        let contents = std::fs::read_to_string(path)?;
+        contents.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| {
+                    let mut var = EnvVar::from_str(line)?;
+                    var.value = unquote(&var.value).to_string();
+                    Ok(var)
+                })
+                .collect()
+    }
+}
+
+/// How long a hook may run, given as `HOOK=SECONDS`
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct HookTimeout {
+    pub hook: String,
+    pub secs: u64,
+}
+
+impl FromStr for HookTimeout {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=')
+               .map(|(hook, secs)| (hook.trim(), secs.trim().parse()))
+        {
+            Some((hook, Ok(secs))) if secs > 0 && TIMED_HOOKS.contains(&hook) => {
+                Ok(HookTimeout { hook: hook.to_string(),
+                                 secs })
+            }
+            _ => {
+                let msg = format!("Invalid hook timeout '{}', must be HOOK=SECONDS with SECONDS \
+                                   above 0 and HOOK one of {}",
+                                  s,
+                                  TIMED_HOOKS.join(", "));
+                Err(Error::ArgumentError(msg))
+            }
+        }
+    }
+}
+
+impl fmt::Display for HookTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.hook, self.secs)
+    }
+}
+
+habitat_core::impl_try_from_string_and_into_string!(HookTimeout);
+
+fn unquote(value: &str) -> &str {
+    for quote in &['"', '\''] {
+        if value.len() >= 2 && value.starts_with(*quote) && value.ends_with(*quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
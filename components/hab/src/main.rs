@@ -26,7 +26,8 @@ use hab::{cli::{self,
                              bldr_url_from_args_env_load_or_default},
                       Hab,
                       Origin,
-                      Pkg},
+                      Pkg,
+                      ServiceConfig},
                 parse_optional_arg,
                 KeyType},
           command::{self,
@@ -121,6 +122,12 @@ lazy_static! {
              "pid",
              "group",]
     };
+    static ref HEALTH_HEADER: Vec<&'static str> =
+        { vec!["checked", "result", "duration (ms)", "exit code",] };
+    static ref HOOKS_HEADER: Vec<&'static str> =
+        { vec!["hook", "started", "duration (ms)", "exit code", "timed out",] };
+    static ref CONFIG_HISTORY_HEADER: Vec<&'static str> =
+        { vec!["version", "applied", "encrypted", "hash",] };
 }
 
 #[tokio::main]
@@ -262,6 +269,19 @@ async fn start(ui: &mut UI, feature_flags: FeatureFlag) -> Result<()> {
                         }
                     }
                 }
+                Hab::Config(config) => {
+                    match config {
+                        ServiceConfig::History(history) => {
+                            return sub_svc_config_history(history).await;
+                        }
+                        ServiceConfig::Rollback(rollback) => {
+                            return sub_svc_config_rollback(rollback).await;
+                        }
+                        _ => {
+                            // All other commands will be caught by the CLI parsing logic below.
+                        }
+                    }
+                }
                 Hab::Svc(svc) => {
                     match svc {
                         Svc::BulkLoad(svc_bulk_load) => {
@@ -275,6 +295,12 @@ async fn start(ui: &mut UI, feature_flags: FeatureFlag) -> Result<()> {
                             return sub_svc_load(svc_load).await;
                         }
                         Svc::Update(svc_update) => return sub_svc_update(svc_update).await,
+                        Svc::Drain(svc_drain) => return sub_svc_drain(svc_drain).await,
+                        Svc::Undrain(svc_undrain) => return sub_svc_undrain(svc_undrain).await,
+                        Svc::Logs(svc_logs) => return sub_svc_logs(svc_logs).await,
+                        Svc::Health(svc_health) => return sub_svc_health(svc_health).await,
+                        Svc::Hooks(svc_hooks) => return sub_svc_hooks(svc_hooks).await,
+                        Svc::Reset(svc_reset) => return sub_svc_reset(svc_reset).await,
                         Svc::Status(svc_status) => {
                             return sub_svc_status(svc_status.pkg_ident,
                                                   svc_status.remote_sup.inner()).await;
@@ -1361,6 +1387,9 @@ async fn sub_svc_set(m: &ArgMatches<'_>) -> Result<()> {
                          sup_proto::butterfly::MAX_SVC_CFG_SIZE))?;
         process::exit(1);
     }
+    if m.is_present("DRY_RUN") {
+        return sub_svc_set_dry_run(&mut ui, &remote_sup_addr, service_group, buf).await;
+    }
     validate.cfg = Some(buf.clone());
     let key_cache = key_cache_from_matches(m)?;
 
@@ -1428,6 +1457,95 @@ async fn sub_svc_set(m: &ArgMatches<'_>) -> Result<()> {
     Ok(())
 }
 
+/// Show how applying `cfg` would change the rendered configuration files and hooks of the service
+/// group's service on the Supervisor, without applying it.
+async fn sub_svc_set_dry_run(ui: &mut UI,
+                             remote_sup_addr: &ResolvedListenCtlAddr,
+                             service_group: ServiceGroup,
+                             cfg: Vec<u8>)
+                             -> Result<()> {
+    let msg = sup_proto::ctl::SvcDryRunCfg { service_group: Some(service_group.clone().into()),
+                                             cfg: Some(cfg),
+                                             ..Default::default() };
+    ui.begin(format!("Rendering new configuration for {} via peer {}",
+                     service_group, remote_sup_addr))?;
+    let mut response = SrvClient::request(Some(remote_sup_addr), msg).await?;
+    let mut dry_run = sup_proto::types::CfgDryRun::default();
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        match reply.message_id() {
+            "CfgDryRun" => {
+                dry_run = reply.parse::<sup_proto::types::CfgDryRun>()
+                               .map_err(SrvClientError::Decode)?
+            }
+            "NetErr" => {
+                let m = reply.parse::<sup_proto::net::NetErr>()
+                             .map_err(SrvClientError::Decode)?;
+                return Err(SrvClientError::from(m).into());
+            }
+            _ => warn!("Unexpected dry run message, {:?}", reply),
+        }
+    }
+    match dry_run.diff.as_deref() {
+        Some("") | None => ui.info("No configuration files or hooks would change")?,
+        Some(diff) => print!("{}", diff),
+    }
+    if !dry_run.errors.is_empty() {
+        for error in &dry_run.errors {
+            ui.warn(error)?;
+        }
+        ui.fatal(format!("Failed to render {} configuration files or hooks",
+                         dry_run.errors.len()))?;
+        process::exit(1);
+    }
+    ui.end("Configuration not applied, this was a dry run")?;
+    Ok(())
+}
+
+async fn sub_svc_config_history(h: hab::cli::hab::config::ServiceConfigHistory) -> Result<()> {
+    let msg = sup_proto::ctl::SvcConfigHistory { service_group: Some(h.service_group.into()), };
+    let mut response = SrvClient::request(h.remote_sup.inner(), msg).await?;
+    let mut history = sup_proto::types::ServiceConfigHistory::default();
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        match reply.message_id() {
+            "ServiceConfigHistory" => {
+                history = reply.parse::<sup_proto::types::ServiceConfigHistory>()
+                               .map_err(SrvClientError::Decode)?
+            }
+            "NetErr" => {
+                let err = reply.parse::<sup_proto::net::NetErr>()
+                               .map_err(SrvClientError::Decode)?;
+                return Err(SrvClientError::from(err).into());
+            }
+            _ => warn!("Unexpected config history message, {:?}", reply),
+        }
+    }
+    if history.versions.is_empty() {
+        println!("No configuration has been applied.");
+        return Ok(());
+    }
+
+    let mut out = TabWriter::new(io::stdout());
+    writeln!(out, "{}", CONFIG_HISTORY_HEADER.join("\t"))?;
+    for version in &history.versions {
+        writeln!(out,
+                 "{}\t{}\t{}\t{}",
+                 version.incarnation,
+                 fmt_unix_secs(version.applied_at),
+                 version.encrypted,
+                 version.hash)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+async fn sub_svc_config_rollback(r: hab::cli::hab::config::ServiceConfigRollback) -> Result<()> {
+    let msg = sup_proto::ctl::SvcConfigRollback { service_group: Some(r.service_group.into()),
+                                                  version:       Some(r.to), };
+    gateway_util::send(r.remote_sup.inner(), msg).await
+}
+
 async fn sub_svc_config(m: &ArgMatches<'_>) -> Result<()> {
     let ident = required_pkg_ident_from_input(m)?;
     let remote_sup_addr = remote_sup_from_input(m)?;
@@ -1477,7 +1595,8 @@ async fn sub_svc_unload(m: &ArgMatches<'_>) -> Result<()> {
     let timeout_in_seconds =
         parse_optional_arg::<ShutdownTimeout>("SHUTDOWN_TIMEOUT", m).map(u32::from);
     let msg = sup_proto::ctl::SvcUnload { ident: Some(ident.into()),
-                                          timeout_in_seconds };
+                                          timeout_in_seconds,
+                                          instance: instance_from_input(m) };
     let remote_sup_addr = remote_sup_from_input(m)?;
     gateway_util::send(remote_sup_addr.as_ref(), msg).await
 }
@@ -1490,11 +1609,141 @@ async fn sub_svc_update(u: hab::cli::hab::svc::Update) -> Result<()> {
 
 async fn sub_svc_start(m: &ArgMatches<'_>) -> Result<()> {
     let ident = required_pkg_ident_from_input(m)?;
-    let msg = sup_proto::ctl::SvcStart { ident: Some(ident.into()), };
+    let msg = sup_proto::ctl::SvcStart { ident:    Some(ident.into()),
+                                         instance: instance_from_input(m), };
     let remote_sup_addr = remote_sup_from_input(m)?;
     gateway_util::send(remote_sup_addr.as_ref(), msg).await
 }
 
+async fn sub_svc_drain(d: hab::cli::hab::svc::SvcDrain) -> Result<()> {
+    let msg = sup_proto::ctl::SvcDrain { ident:    Some(d.pkg_ident.pkg_ident().into()),
+                                         instance: d.instance.value, };
+    gateway_util::send(d.remote_sup.inner(), msg).await
+}
+
+async fn sub_svc_undrain(u: hab::cli::hab::svc::SvcUndrain) -> Result<()> {
+    let msg = sup_proto::ctl::SvcUndrain { ident:    Some(u.pkg_ident.pkg_ident().into()),
+                                           instance: u.instance.value, };
+    gateway_util::send(u.remote_sup.inner(), msg).await
+}
+
+async fn sub_svc_logs(l: hab::cli::hab::svc::SvcLogs) -> Result<()> {
+    let msg = sup_proto::ctl::SvcLogs { ident:    Some(l.pkg_ident.pkg_ident().into()),
+                                        instance: l.instance.value,
+                                        follow:   Some(l.follow),
+                                        since:    l.since.map(Into::into), };
+    gateway_util::send(l.remote_sup.inner(), msg).await
+}
+
+async fn sub_svc_health(h: hab::cli::hab::svc::SvcHealth) -> Result<()> {
+    let msg = sup_proto::ctl::SvcHealthHistory { ident:    Some(h.pkg_ident.pkg_ident().into()),
+                                                 instance: h.instance.value, };
+    let mut response = SrvClient::request(h.remote_sup.inner(), msg).await?;
+    let mut history = sup_proto::types::HealthHistory::default();
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        match reply.message_id() {
+            "HealthHistory" => {
+                history = reply.parse::<sup_proto::types::HealthHistory>()
+                               .map_err(SrvClientError::Decode)?
+            }
+            "NetErr" => {
+                let err = reply.parse::<sup_proto::net::NetErr>()
+                               .map_err(SrvClientError::Decode)?;
+                return Err(SrvClientError::from(err).into());
+            }
+            _ => warn!("Unexpected health message, {:?}", reply),
+        }
+    }
+    if history.flapping {
+        println!("The service's health is flapping, changing too often to be trusted.\n");
+    }
+    if history.checks.is_empty() {
+        println!("No health checks have run.");
+        return Ok(());
+    }
+
+    let mut out = TabWriter::new(io::stdout());
+    writeln!(out, "{}", HEALTH_HEADER.join("\t"))?;
+    for check in &history.checks {
+        writeln!(out,
+                 "{}\t{}\t{}\t{}",
+                 fmt_unix_secs(check.checked_at),
+                 check.result,
+                 check.duration_ms
+                      .map_or_else(|| "<none>".to_string(), |ms| ms.to_string()),
+                 check.exit_code
+                      .map_or_else(|| "<none>".to_string(), |code| code.to_string()))?;
+    }
+    out.flush()?;
+    if h.output {
+        for check in &history.checks {
+            println!("\n==> health check finished {} <==",
+                     fmt_unix_secs(check.checked_at));
+            print!("{}", check.stdout.as_deref().unwrap_or_default());
+            eprint!("{}", check.stderr.as_deref().unwrap_or_default());
+        }
+    }
+    Ok(())
+}
+
+async fn sub_svc_hooks(h: hab::cli::hab::svc::SvcHooks) -> Result<()> {
+    let msg = sup_proto::ctl::SvcHooks { ident:    Some(h.pkg_ident.pkg_ident().into()),
+                                         instance: h.instance.value, };
+    let mut response = SrvClient::request(h.remote_sup.inner(), msg).await?;
+    let mut runs = Vec::new();
+    while let Some(message_result) = response.next().await {
+        let reply = message_result?;
+        match reply.message_id() {
+            "HookRun" => {
+                runs.push(reply.parse::<sup_proto::types::HookRun>()
+                               .map_err(SrvClientError::Decode)?)
+            }
+            "NetOk" => (),
+            "NetErr" => {
+                let err = reply.parse::<sup_proto::net::NetErr>()
+                               .map_err(SrvClientError::Decode)?;
+                return Err(SrvClientError::from(err).into());
+            }
+            _ => warn!("Unexpected hooks message, {:?}", reply),
+        }
+    }
+    if runs.is_empty() {
+        println!("No hooks have run.");
+        return Ok(());
+    }
+
+    let mut out = TabWriter::new(io::stdout());
+    writeln!(out, "{}", HOOKS_HEADER.join("\t"))?;
+    for run in &runs {
+        writeln!(out,
+                 "{}\t{}\t{}\t{}\t{}",
+                 run.hook,
+                 fmt_unix_secs(run.started_at),
+                 run.duration_ms,
+                 run.exit_code
+                    .map_or_else(|| "<none>".to_string(), |code| code.to_string()),
+                 run.timed_out)?;
+    }
+    out.flush()?;
+    if h.output {
+        for run in &runs {
+            println!("\n==> {} hook started {} <==",
+                     run.hook,
+                     fmt_unix_secs(run.started_at));
+            print!("{}", run.stdout.as_deref().unwrap_or_default());
+            eprint!("{}", run.stderr.as_deref().unwrap_or_default());
+        }
+    }
+    Ok(())
+}
+
+async fn sub_svc_reset(r: hab::cli::hab::svc::SvcReset) -> Result<()> {
+    let msg = sup_proto::ctl::SvcReset { ident:    Some(r.pkg_ident.pkg_ident().into()),
+                                         instance: r.instance.value, };
+    gateway_util::send(r.remote_sup.inner(), msg).await
+}
+
 async fn sub_svc_status(pkg_ident: Option<PackageIdent>,
                         remote_sup: Option<&ResolvedListenCtlAddr>)
                         -> Result<()> {
@@ -1522,7 +1771,8 @@ async fn sub_svc_stop(m: &ArgMatches<'_>) -> Result<()> {
     let timeout_in_seconds =
         parse_optional_arg::<ShutdownTimeout>("SHUTDOWN_TIMEOUT", m).map(u32::from);
     let msg = sup_proto::ctl::SvcStop { ident: Some(ident.into()),
-                                        timeout_in_seconds };
+                                        timeout_in_seconds,
+                                        instance: instance_from_input(m) };
     let remote_sup_addr = remote_sup_from_input(m)?;
     gateway_util::send(remote_sup_addr.as_ref(), msg).await
 }
@@ -1972,6 +2222,15 @@ fn excludes_from_matches(matches: &ArgMatches<'_>) -> Vec<PackageIdent> {
         .collect()
 }
 
+/// Formats a time given in seconds since the Unix epoch, or a placeholder
+/// if it is out of range.
+fn fmt_unix_secs(secs: i64) -> String {
+    match chrono::NaiveDateTime::from_timestamp_opt(secs, 0) {
+        Some(at) => at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        None => "<unknown>".to_string(),
+    }
+}
+
 fn print_svc_status<T>(out: &mut T,
                        reply: &SrvMessage,
                        print_header: bool)
@@ -2052,6 +2311,10 @@ fn required_pkg_ident_from_input(m: &ArgMatches<'_>) -> Result<PackageIdent> {
         .parse()?)
 }
 
+fn instance_from_input(m: &ArgMatches<'_>) -> Option<String> {
+    m.value_of("INSTANCE").map(str::to_string)
+}
+
 /// Check to see if the user has passed in a USER param.
 /// If not, check the HAB_USER env var. If that's
 /// empty too, then return an error.
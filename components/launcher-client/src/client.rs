@@ -7,7 +7,11 @@ use crate::{error::{ConnectError,
                     TryReceiveError},
             IPCError};
 use habitat_common::types::UserInfo;
-use habitat_core::os::process::Pid;
+use habitat_core::os::process::{CpuAffinity,
+                                Pid,
+                                ProcessControls,
+                                ResourceLimits,
+                                Signal};
 use habitat_launcher_protocol as protocol;
 use ipc_channel::ipc::{IpcError,
                        IpcOneShotServer,
@@ -201,6 +205,17 @@ impl LauncherCli {
     /// `username` and `groupname` are string names, while `uid` and
     /// `gid` are numeric IDs. Newer versions of the Launcher can
     /// accept either, but prefer numeric IDs.
+    ///
+    /// Resource limits are only enforced by Launchers on Linux hosts
+    /// with cgroup v2, and process controls only on Unix hosts; older
+    /// Launchers ignore both.
+    ///
+    /// The service's output is captured to `log_path`, in addition to
+    /// being printed, by Launchers that support it.
+    ///
+    /// The service is handed a listening socket for each of
+    /// `listen_ports` by Launchers on Unix hosts that support it.
+    #[allow(clippy::too_many_arguments)]
     pub fn spawn(&self,
                  id: &str,
                  bin: &Path,
@@ -209,7 +224,14 @@ impl LauncherCli {
                             groupname,
                             gid, }: UserInfo,
                  password: Option<&str>,
-                 env: Env)
+                 env: Env,
+                 ResourceLimits { memory_max,
+                                  cpu_max,
+                                  pids_max,
+                                  io_weight, }: ResourceLimits,
+                 process_controls: ProcessControls,
+                 log_path: &Path,
+                 listen_ports: &[u16])
                  -> Result<Pid, IPCCommandError> {
         // On Windows, we only expect user to be Some.
         //
@@ -217,6 +239,15 @@ impl LauncherCli {
         // user and groupname may be either Some or None. Only the IDs are
         // used; names are only for backward compatibility with older
         // Launchers.
+        let process_controls =
+            protocol::ProcessControls { nofile:       process_controls.nofile,
+                                        nproc:        process_controls.nproc,
+                                        core:         process_controls.core,
+                                        memlock:      process_controls.memlock,
+                                        nice:         process_controls.nice,
+                                        ionice:       process_controls.ionice,
+                                        cpu_affinity: process_controls.cpu_affinity
+                                                                      .map(CpuAffinity::mask), };
         let msg = protocol::Spawn { binary: bin.to_string_lossy().into_owned(),
                                     svc_user: username,
                                     svc_group: groupname,
@@ -224,7 +255,14 @@ impl LauncherCli {
                                     svc_group_id: gid,
                                     svc_password: password.map(str::to_string),
                                     env,
-                                    id: id.to_string() };
+                                    id: id.to_string(),
+                                    resource_limits: protocol::ResourceLimits { memory_max,
+                                                                                cpu_max,
+                                                                                pids_max,
+                                                                                io_weight },
+                                    process_controls,
+                                    log_path: Some(log_path.to_string_lossy().into_owned()),
+                                    listen_ports: listen_ports.to_vec() };
 
         Self::send(&self.tx, &msg).map_err(|err| IPCCommandError::Send("spawn", err))?;
         let reply = Self::recv::<protocol::SpawnOk>(&self.rx).map_err(|err| {
@@ -258,6 +296,40 @@ impl LauncherCli {
         }
     }
 
+    /// Query the launcher for the exit code of the service process
+    /// that ran with `pid`. Older Launchers don't track exit codes, and
+    /// time out.
+    pub fn exit_code_of(&self, pid: Pid) -> Result<Option<i32>, TryIPCCommandError> {
+        let msg = protocol::ExitCodeOf { pid: pid as u32 };
+        Self::send(&self.tx, &msg).map_err(|err| TryIPCCommandError::Send("exit_code_of", err))?;
+        let reply =
+            Self::recv_timeout::<protocol::ExitCodeIs>(&self.rx, self.timeout).map_err(|err| {
+                TryIPCCommandError::TryReceive("exit_code_of", err)
+            })?;
+        Ok(reply.exit_code)
+    }
+
+    /// Have the launcher close the listening sockets it holds for the
+    /// service `id`. Older Launchers don't hold sockets, and time out.
+    pub fn release_sockets(&self, id: &str) -> Result<(), TryIPCCommandError> {
+        let msg = protocol::ReleaseSockets { id: id.to_string() };
+        Self::send(&self.tx, &msg).map_err(|err| TryIPCCommandError::Send("release_sockets", err))?;
+        Self::recv_timeout::<protocol::NetOk>(&self.rx, self.timeout)
+            .map_err(|err| TryIPCCommandError::TryReceive("release_sockets", err))?;
+        Ok(())
+    }
+
+    /// Have the launcher send `signal` to the service process running
+    /// with `pid`. Older Launchers reply that the message is unknown.
+    pub fn signal(&self, pid: Pid, signal: Signal) -> Result<(), TryIPCCommandError> {
+        let msg = protocol::Signal { pid:    pid as u32,
+                                     signal: signal.to_string(), };
+        Self::send(&self.tx, &msg).map_err(|err| TryIPCCommandError::Send("signal", err))?;
+        Self::recv_timeout::<protocol::NetOk>(&self.rx, self.timeout)
+            .map_err(|err| TryIPCCommandError::TryReceive("signal", err))?;
+        Ok(())
+    }
+
     /// Query the launcher for its version. If the
     /// Launcher is aware of it, you'll get `Ok(u32)`
     pub fn version(&self) -> Result<u32, TryIPCCommandError> {
@@ -3,6 +3,7 @@ use crate::{error::{Error,
             generated};
 use prost::Message;
 use std::{collections::BTreeMap,
+          convert::TryFrom,
           fmt};
 
 pub trait LauncherMessage
@@ -124,14 +125,18 @@ impl From<Restart> for generated::Restart {
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Spawn {
-    pub id:           String,
-    pub binary:       String,
-    pub svc_user:     Option<String>,
-    pub svc_group:    Option<String>,
-    pub svc_password: Option<String>,
-    pub env:          BTreeMap<String, String>,
-    pub svc_user_id:  Option<u32>,
-    pub svc_group_id: Option<u32>,
+    pub id:               String,
+    pub binary:           String,
+    pub svc_user:         Option<String>,
+    pub svc_group:        Option<String>,
+    pub svc_password:     Option<String>,
+    pub env:              BTreeMap<String, String>,
+    pub svc_user_id:      Option<u32>,
+    pub svc_group_id:     Option<u32>,
+    pub resource_limits:  ResourceLimits,
+    pub process_controls: ProcessControls,
+    pub log_path:         Option<String>,
+    pub listen_ports:     Vec<u16>,
 }
 
 impl LauncherMessage for Spawn {
@@ -140,27 +145,110 @@ impl LauncherMessage for Spawn {
     const MESSAGE_ID: &'static str = "Spawn";
 
     fn from_proto(proto: generated::Spawn) -> Result<Self> {
-        Ok(Spawn { id:           proto.id.ok_or(Error::ProtocolMismatch("id"))?,
-                   binary:       proto.binary.ok_or(Error::ProtocolMismatch("binary"))?,
-                   svc_user:     proto.svc_user,
-                   svc_group:    proto.svc_group,
-                   svc_password: proto.svc_password,
-                   env:          proto.env.into_iter().collect(),
-                   svc_user_id:  proto.svc_user_id,
-                   svc_group_id: proto.svc_group_id, })
+        Ok(Spawn { id:               proto.id.ok_or(Error::ProtocolMismatch("id"))?,
+                   binary:           proto.binary.ok_or(Error::ProtocolMismatch("binary"))?,
+                   svc_user:         proto.svc_user,
+                   svc_group:        proto.svc_group,
+                   svc_password:     proto.svc_password,
+                   env:              proto.env.into_iter().collect(),
+                   svc_user_id:      proto.svc_user_id,
+                   svc_group_id:     proto.svc_group_id,
+                   resource_limits:  proto.resource_limits.map(Into::into).unwrap_or_default(),
+                   process_controls: proto.process_controls.map(Into::into).unwrap_or_default(),
+                   log_path:         proto.log_path,
+                   listen_ports:     proto.listen_ports
+                                          .into_iter()
+                                          .map(|port| {
+                                              u16::try_from(port).map_err(|_| {
+                                                  Error::ProtocolMismatch("listen_ports")
+                                              })
+                                          })
+                                          .collect::<Result<_>>()?, })
     }
 }
 
 impl From<Spawn> for generated::Spawn {
     fn from(value: Spawn) -> Self {
-        generated::Spawn { id:           Some(value.id),
-                           binary:       Some(value.binary),
-                           svc_user:     value.svc_user,
-                           svc_group:    value.svc_group,
-                           svc_password: value.svc_password,
-                           env:          value.env.into_iter().collect(),
-                           svc_user_id:  value.svc_user_id,
-                           svc_group_id: value.svc_group_id, }
+        generated::Spawn { id:               Some(value.id),
+                           binary:           Some(value.binary),
+                           svc_user:         value.svc_user,
+                           svc_group:        value.svc_group,
+                           svc_password:     value.svc_password,
+                           env:              value.env.into_iter().collect(),
+                           svc_user_id:      value.svc_user_id,
+                           svc_group_id:     value.svc_group_id,
+                           resource_limits:  Some(value.resource_limits.into()),
+                           process_controls: Some(value.process_controls.into()),
+                           log_path:         value.log_path,
+                           listen_ports:     value.listen_ports
+                                                  .into_iter()
+                                                  .map(u32::from)
+                                                  .collect(), }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub memory_max: Option<u64>,
+    pub cpu_max:    Option<u32>,
+    pub pids_max:   Option<u64>,
+    pub io_weight:  Option<u32>,
+}
+
+impl ResourceLimits {
+    pub fn is_empty(&self) -> bool { *self == Self::default() }
+}
+
+impl From<generated::ResourceLimits> for ResourceLimits {
+    fn from(value: generated::ResourceLimits) -> Self {
+        ResourceLimits { memory_max: value.memory_max,
+                         cpu_max:    value.cpu_max,
+                         pids_max:   value.pids_max,
+                         io_weight:  value.io_weight, }
+    }
+}
+
+impl From<ResourceLimits> for generated::ResourceLimits {
+    fn from(value: ResourceLimits) -> Self {
+        generated::ResourceLimits { memory_max: value.memory_max,
+                                    cpu_max:    value.cpu_max,
+                                    pids_max:   value.pids_max,
+                                    io_weight:  value.io_weight, }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProcessControls {
+    pub nofile:       Option<u64>,
+    pub nproc:        Option<u64>,
+    pub core:         Option<u64>,
+    pub memlock:      Option<u64>,
+    pub nice:         Option<i32>,
+    pub ionice:       Option<u32>,
+    pub cpu_affinity: Option<u64>,
+}
+
+impl From<generated::ProcessControls> for ProcessControls {
+    fn from(value: generated::ProcessControls) -> Self {
+        ProcessControls { nofile:       value.nofile,
+                          nproc:        value.nproc,
+                          core:         value.core,
+                          memlock:      value.memlock,
+                          nice:         value.nice,
+                          ionice:       value.ionice,
+                          cpu_affinity: value.cpu_affinity, }
+    }
+}
+
+impl From<ProcessControls> for generated::ProcessControls {
+    fn from(value: ProcessControls) -> Self {
+        generated::ProcessControls { nofile:       value.nofile,
+                                     nproc:        value.nproc,
+                                     core:         value.core,
+                                     memlock:      value.memlock,
+                                     nice:         value.nice,
+                                     ionice:       value.ionice,
+                                     cpu_affinity: value.cpu_affinity, }
     }
 }
 
@@ -319,6 +407,87 @@ impl From<PidIs> for generated::PidIs {
     fn from(value: PidIs) -> Self { generated::PidIs { pid: value.pid } }
 }
 
+#[derive(Clone, Debug)]
+pub struct ExitCodeOf {
+    pub pid: u32,
+}
+
+impl LauncherMessage for ExitCodeOf {
+    type Generated = generated::ExitCodeOf;
+
+    const MESSAGE_ID: &'static str = "ExitCodeOf";
+
+    fn from_proto(proto: generated::ExitCodeOf) -> Result<Self> {
+        Ok(ExitCodeOf { pid: proto.pid.ok_or(Error::ProtocolMismatch("pid"))?, })
+    }
+}
+
+impl From<ExitCodeOf> for generated::ExitCodeOf {
+    fn from(value: ExitCodeOf) -> Self { generated::ExitCodeOf { pid: Some(value.pid), } }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExitCodeIs {
+    pub exit_code: Option<i32>,
+}
+
+impl LauncherMessage for ExitCodeIs {
+    type Generated = generated::ExitCodeIs;
+
+    const MESSAGE_ID: &'static str = "ExitCodeIs";
+
+    fn from_proto(proto: generated::ExitCodeIs) -> Result<Self> {
+        Ok(ExitCodeIs { exit_code: proto.exit_code, })
+    }
+}
+
+impl From<ExitCodeIs> for generated::ExitCodeIs {
+    fn from(value: ExitCodeIs) -> Self { generated::ExitCodeIs { exit_code: value.exit_code, } }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReleaseSockets {
+    pub id: String,
+}
+
+impl LauncherMessage for ReleaseSockets {
+    type Generated = generated::ReleaseSockets;
+
+    const MESSAGE_ID: &'static str = "ReleaseSockets";
+
+    fn from_proto(proto: generated::ReleaseSockets) -> Result<Self> {
+        Ok(ReleaseSockets { id: proto.id.ok_or(Error::ProtocolMismatch("id"))?, })
+    }
+}
+
+impl From<ReleaseSockets> for generated::ReleaseSockets {
+    fn from(value: ReleaseSockets) -> Self { generated::ReleaseSockets { id: Some(value.id) } }
+}
+
+#[derive(Clone, Debug)]
+pub struct Signal {
+    pub pid:    u32,
+    pub signal: String,
+}
+
+impl LauncherMessage for Signal {
+    type Generated = generated::Signal;
+
+    const MESSAGE_ID: &'static str = "Signal";
+
+    fn from_proto(proto: generated::Signal) -> Result<Self> {
+        Ok(Signal { pid:    proto.pid.ok_or(Error::ProtocolMismatch("pid"))?,
+                    signal: proto.signal.ok_or(Error::ProtocolMismatch("signal"))?, })
+    }
+}
+
+impl From<Signal> for generated::Signal {
+    fn from(value: Signal) -> Self {
+        generated::Signal { pid:    Some(value.pid),
+                            signal: Some(value.signal), }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct VersionNumber {
     pub version: u32,
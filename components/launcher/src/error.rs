@@ -5,6 +5,8 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 #[warn(clippy::result_large_err)]
 pub enum ServiceRunError {
+    #[error("Failed to bind a listening socket to port {0}")]
+    Bind(u16, #[source] io::Error),
     #[error("Failed to spawn service process")]
     Spawn(#[source] io::Error),
     #[cfg(unix)]
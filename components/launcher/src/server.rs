@@ -7,6 +7,7 @@ use crate::{core::{self,
                         signals},
                    package::{PackageIdent,
                              PackageInstall}},
+            error::ServiceRunError,
             protocol::{self,
                        ERR_NO_RETRY_EXCODE,
                        OK_NO_RETRY_EXCODE},
@@ -34,9 +35,16 @@ use semver::{Version,
 #[cfg(unix)]
 use std::{cmp::Ordering,
           os::unix::process::ExitStatusExt};
-use std::{collections::HashMap,
+use std::{collections::{btree_map::Entry,
+                        BTreeMap,
+                        HashMap,
+                        VecDeque},
           fs,
-          io::Write,
+          io::{self,
+               Write},
+          net::{Ipv4Addr,
+                Ipv6Addr,
+                TcpListener},
           path::PathBuf,
           process::{Child,
                     Command,
@@ -369,16 +377,81 @@ impl Server {
 }
 
 #[derive(Debug, Default)]
-pub struct ServiceTable(HashMap<u32, Service>);
+pub struct ServiceTable {
+    services:   HashMap<u32, Service>,
+    /// The exit codes of the services that exited most recently, by
+    /// PID, so the Supervisor can learn how a job run ended.
+    exit_codes: VecDeque<(u32, i32)>,
+    /// The listening sockets held for each service, by `Spawn#id`, so
+    /// that they stay open while the service restarts.
+    sockets:    HashMap<String, BTreeMap<u16, TcpListener>>,
+}
 
 impl ServiceTable {
-    pub fn get(&self, pid: u32) -> Option<&Service> { self.0.get(&pid) }
+    /// How many exit codes are kept for the Supervisor to collect.
+    const MAX_EXIT_CODES: usize = 100;
+
+    pub fn get(&self, pid: u32) -> Option<&Service> { self.services.get(&pid) }
 
-    pub fn get_mut(&mut self, pid: u32) -> Option<&mut Service> { self.0.get_mut(&pid) }
+    pub fn get_mut(&mut self, pid: u32) -> Option<&mut Service> { self.services.get_mut(&pid) }
 
-    pub fn insert(&mut self, service: Service) { self.0.insert(service.id(), service); }
+    pub fn insert(&mut self, service: Service) { self.services.insert(service.id(), service); }
 
-    pub fn remove(&mut self, pid: u32) -> Option<Service> { self.0.remove(&pid) }
+    pub fn remove(&mut self, pid: u32) -> Option<Service> { self.services.remove(&pid) }
+
+    /// Returns, and forgets, the exit code of the service that ran
+    /// with `pid`. This is `None` if the process hasn't exited, was
+    /// killed by a signal, or exited too long ago to be remembered.
+    pub fn exit_code_of(&mut self, pid: u32) -> Option<i32> {
+        let index = self.exit_codes.iter().position(|(p, _)| *p == pid)?;
+        self.exit_codes.remove(index).map(|(_, code)| code)
+    }
+
+    /// Returns the listening sockets the service `id` is to be handed,
+    /// one for each of `ports`. Sockets already held for the service
+    /// are reused, the rest are bound now, and any held for ports it no
+    /// longer listens on are closed.
+    pub fn listen_sockets(&mut self,
+                          id: &str,
+                          ports: &[u16])
+                          -> Result<Vec<(u16, TcpListener)>, ServiceRunError> {
+        if ports.is_empty() {
+            self.release_sockets(id);
+            return Ok(Vec::new());
+        }
+        if cfg!(windows) {
+            warn!("Listening sockets can't be passed to services on Windows; starting {} without \
+                   them",
+                  id);
+            return Ok(Vec::new());
+        }
+        let held = self.sockets.entry(id.to_string()).or_default();
+        held.retain(|port, _| ports.contains(port));
+        ports.iter()
+             .map(|&port| {
+                 let listener = match held.entry(port) {
+                     Entry::Occupied(entry) => entry.into_mut(),
+                     Entry::Vacant(entry) => {
+                         debug!("Binding port {} for {}", port, id);
+                         let listener =
+                             bind_any(port).map_err(|err| ServiceRunError::Bind(port, err))?;
+                         entry.insert(listener)
+                     }
+                 };
+                 let listener = listener.try_clone()
+                                        .map_err(|err| ServiceRunError::Bind(port, err))?;
+                 Ok((port, listener))
+             })
+             .collect()
+    }
+
+    /// Closes the listening sockets held for the service `id`. The
+    /// service's own copies stay open until it exits.
+    pub fn release_sockets(&mut self, id: &str) {
+        if self.sockets.remove(id).is_some() {
+            debug!("Released the listening sockets of {}", id);
+        }
+    }
 
     // Obviously this is not the most elegant implementation. However,
     // in practice we don't have a whole lot of processes per
@@ -402,17 +475,17 @@ impl ServiceTable {
     /// figure out if there are currently-running services to which it
     /// needs to re-attach itself.
     pub fn pid_of(&self, service_name: &str) -> Option<u32> {
-        self.0.iter().find_map(|(pid, service)| {
-                         if service_name == service.args().id {
-                             Some(*pid)
-                         } else {
-                             None
-                         }
-                     })
+        self.services.iter().find_map(|(pid, service)| {
+                                if service_name == service.args().id {
+                                    Some(*pid)
+                                } else {
+                                    None
+                                }
+                            })
     }
 
     fn kill_all(&mut self) {
-        for service in self.0.values_mut() {
+        for service in self.services.values_mut() {
             outputln!(preamble service.name(), "Stopping...");
             let shutdown_method = service.kill();
             outputln!(preamble service.name(), "Shutdown OK: {}", shutdown_method);
@@ -421,14 +494,17 @@ impl ServiceTable {
 
     fn reap_services(&mut self) {
         let mut dead: Vec<u32> = vec![];
-        for service in self.0.values_mut() {
+        for service in self.services.values_mut() {
             match service.try_wait() {
                 Ok(None) => (),
-                Ok(Some(code)) => {
+                Ok(Some(status)) => {
                     outputln!("Child for service '{}' with PID {} exited with code {}",
                               service.name(),
                               service.id(),
-                              code);
+                              status);
+                    if let Some(code) = status.code() {
+                        self.exit_codes.push_back((service.id(), code));
+                    }
                     dead.push(service.id());
                 }
                 Err(err) => {
@@ -438,7 +514,10 @@ impl ServiceTable {
             }
         }
         for pid in dead {
-            self.0.remove(&pid);
+            self.services.remove(&pid);
+        }
+        while self.exit_codes.len() > Self::MAX_EXIT_CODES {
+            self.exit_codes.pop_front();
         }
     }
 }
@@ -508,6 +587,9 @@ fn dispatch(tx: &Sender, bytes: &[u8], services: &mut ServiceTable) {
         "Spawn" => handlers::SpawnHandler::run,
         "Terminate" => handlers::TerminateHandler::run,
         "PidOf" => handlers::PidHandler::run,
+        "ExitCodeOf" => handlers::ExitCodeHandler::run,
+        "ReleaseSockets" => handlers::ReleaseSocketsHandler::run,
+        "Signal" => handlers::SignalHandler::run,
         "Version" => handlers::VersionHandler::run,
         unknown => {
             // This sucks a bit because it replicates some code from the
@@ -526,6 +608,18 @@ fn dispatch(tx: &Sender, bytes: &[u8], services: &mut ServiceTable) {
     func(tx, msg, services);
 }
 
+/// Binds a listening socket for `port` on every address. The IPv6 socket also accepts IPv4
+/// connections unless the host sets `net.ipv6.bindv6only`. Hosts without IPv6 get an IPv4 socket.
+fn bind_any(port: u16) -> io::Result<TcpListener> {
+    match TcpListener::bind((Ipv6Addr::UNSPECIFIED, port)) {
+        Err(err) if err.kind() != io::ErrorKind::AddrInUse => {
+            debug!("Binding port {} on IPv4 only, IPv6 failed: {}", port, err);
+            TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))
+        }
+        result => result,
+    }
+}
+
 #[allow(clippy::mutex_atomic)] // A Mutex is required for Condvar::wait_timeout
 fn setup_connection(server: IpcOneShotServer<Vec<u8>>) -> Result<(Receiver, Sender)> {
     let pair = Arc::new((Mutex::new(false), Condvar::new()));
@@ -1,5 +1,8 @@
+mod exit_code;
 mod pid;
+mod release_sockets;
 mod restart;
+mod signal;
 mod spawn;
 mod terminate;
 mod version;
@@ -7,8 +10,11 @@ mod version;
 use log::{error,
           trace};
 
-pub use self::{pid::*,
+pub use self::{exit_code::*,
+               pid::*,
+               release_sockets::*,
                restart::*,
+               signal::*,
                spawn::*,
                terminate::*,
                version::*};
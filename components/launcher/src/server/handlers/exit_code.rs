@@ -0,0 +1,16 @@
+use super::{HandleResult,
+            Handler};
+use crate::{protocol,
+            server::ServiceTable};
+
+pub struct ExitCodeHandler;
+
+impl Handler for ExitCodeHandler {
+    type Message = protocol::ExitCodeOf;
+    type Reply = protocol::ExitCodeIs;
+
+    fn handle(msg: Self::Message, services: &mut ServiceTable) -> HandleResult<Self::Reply> {
+        let exit_code = services.exit_code_of(msg.pid);
+        Ok(protocol::ExitCodeIs { exit_code })
+    }
+}
@@ -0,0 +1,16 @@
+use super::{HandleResult,
+            Handler};
+use crate::{protocol,
+            server::ServiceTable};
+
+pub struct ReleaseSocketsHandler;
+
+impl Handler for ReleaseSocketsHandler {
+    type Message = protocol::ReleaseSockets;
+    type Reply = protocol::NetOk;
+
+    fn handle(msg: Self::Message, services: &mut ServiceTable) -> HandleResult<Self::Reply> {
+        services.release_sockets(&msg.id);
+        Ok(protocol::NetOk {})
+    }
+}
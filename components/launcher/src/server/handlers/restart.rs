@@ -22,7 +22,12 @@ impl Handler for RestartHandler {
         service.kill();
         match service.wait() {
             Ok(_status) => {
-                match service::run(service.take_args()) {
+                let args = service.take_args();
+                let sockets = match services.listen_sockets(&args.id, &args.listen_ports) {
+                    Ok(sockets) => sockets,
+                    Err(err) => return Err(protocol::error(err)),
+                };
+                match service::run(args, sockets) {
                     Ok(new_service) => {
                         let reply = protocol::SpawnOk { pid: new_service.id().into(), };
                         services.insert(new_service);
@@ -0,0 +1,34 @@
+use super::{HandleResult,
+            Handler};
+use crate::{core::os::process::Signal,
+            protocol,
+            server::ServiceTable};
+use log::debug;
+
+pub struct SignalHandler;
+
+impl Handler for SignalHandler {
+    type Message = protocol::Signal;
+    type Reply = protocol::NetOk;
+
+    fn handle(msg: Self::Message, services: &mut ServiceTable) -> HandleResult<Self::Reply> {
+        let signal = msg.signal.parse::<Signal>().map_err(failed)?;
+        match services.get_mut(msg.pid) {
+            Some(service) => {
+                debug!("Sending {} to: {}", signal, service.name());
+                service.signal(signal).map_err(failed)?;
+                Ok(protocol::NetOk {})
+            }
+            None => {
+                let reply = protocol::NetErr { code: protocol::ErrCode::NoPid,
+                                               ..Default::default() };
+                Err(reply)
+            }
+        }
+    }
+}
+
+fn failed(err: impl ToString) -> protocol::NetErr {
+    protocol::NetErr { code: protocol::ErrCode::Unknown,
+                       msg:  err.to_string(), }
+}
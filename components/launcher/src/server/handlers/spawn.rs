@@ -11,7 +11,11 @@ impl Handler for SpawnHandler {
     type Reply = protocol::SpawnOk;
 
     fn handle(msg: Self::Message, services: &mut ServiceTable) -> HandleResult<Self::Reply> {
-        match service::run(msg) {
+        let sockets = match services.listen_sockets(&msg.id, &msg.listen_ports) {
+            Ok(sockets) => sockets,
+            Err(err) => return Err(protocol::error(err)),
+        };
+        match service::run(msg, sockets) {
             Ok(service) => {
                 let reply = protocol::SpawnOk { pid: service.id().into(), };
                 services.insert(service);
@@ -2,10 +2,14 @@
 use crate::core::os::process::windows_child::{ChildStderr,
                                               ChildStdout,
                                               ExitStatus};
-use crate::{core::util::BufReadLossy,
+use crate::{core::{os::process::Signal,
+                   util::BufReadLossy},
             protocol};
-use habitat_common::output::{self,
-                             StructuredOutput};
+use habitat_common::{output::{self,
+                              StructuredOutput},
+                     service_log::{LogWriter,
+                                   Stream}};
+use log::error;
 #[cfg(unix)]
 use std::process::{ChildStderr,
                    ChildStdout,
@@ -14,10 +18,30 @@ use std::{fmt,
           io::{self,
                BufReader,
                Read},
+          sync::{Arc,
+                 Mutex},
           thread};
 
 pub use crate::sys::service::*;
 
+habitat_core::env_config_int!(
+    /// The size, in bytes, past which a service's log file is rotated.
+    SvcLogMaxBytes,
+    u64,
+    HAB_LAUNCH_SVC_LOG_MAX_BYTES,
+    10 * 1024 * 1024
+);
+
+habitat_core::env_config_int!(
+    /// The number of rotated log files kept for each service.
+    SvcLogMaxFiles,
+    u32,
+    HAB_LAUNCH_SVC_LOG_MAX_FILES,
+    5
+);
+
+type SharedLog = Arc<Mutex<LogWriter>>;
+
 pub struct Service {
     args:    protocol::Spawn,
     process: Process,
@@ -29,16 +53,20 @@ impl Service {
                stdout: Option<ChildStdout>,
                stderr: Option<ChildStderr>)
                -> Self {
+        let log = spawn.log_path
+                       .as_ref()
+                       .and_then(|path| open_log(path, &spawn.id));
         if let Some(stdout) = stdout {
             let id = spawn.id.to_string();
+            let log = log.clone();
             thread::Builder::new().name(format!("{}-out", spawn.id))
-                                  .spawn(move || pipe_stdout(stdout, &id))
+                                  .spawn(move || pipe_stdout(stdout, &id, log))
                                   .ok();
         }
         if let Some(stderr) = stderr {
             let id = spawn.id.to_string();
             thread::Builder::new().name(format!("{}-err", spawn.id))
-                                  .spawn(move || pipe_stderr(stderr, &id))
+                                  .spawn(move || pipe_stderr(stderr, &id, log))
                                   .ok();
         }
         Service { args: spawn,
@@ -55,6 +83,8 @@ impl Service {
 
     pub fn name(&self) -> &str { &self.args.id }
 
+    pub fn signal(&self, signal: Signal) -> io::Result<()> { self.process.signal(signal) }
+
     pub fn take_args(self) -> protocol::Spawn { self.args }
 
     pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> { self.process.try_wait() }
@@ -68,13 +98,38 @@ impl fmt::Debug for Service {
     }
 }
 
+/// Open the log file a service's output is captured to. Failing to do
+/// so is logged but doesn't prevent the service from running.
+fn open_log(path: &str, id: &str) -> Option<SharedLog> {
+    match LogWriter::open(path,
+                          SvcLogMaxBytes::configured_value().0,
+                          SvcLogMaxFiles::configured_value().0)
+    {
+        Ok(writer) => Some(Arc::new(Mutex::new(writer))),
+        Err(e) => {
+            error!("Unable to open log file {} for {}: {}", path, id, e);
+            None
+        }
+    }
+}
+
+fn write_log(log: &Option<SharedLog>, stream: Stream, line: &str) {
+    if let Some(log) = log {
+        let mut writer = log.lock().expect("Service log mutex poisoned");
+        if let Err(e) = writer.write_line(stream, line) {
+            error!("Unable to write service log: {}", e);
+        }
+    }
+}
+
 /// Consume output from a child process until EOF, then finish
-fn pipe_stdout<T>(out: T, id: &str)
+fn pipe_stdout<T>(out: T, id: &str, log: Option<SharedLog>)
     where T: Read
 {
     for line in BufReader::new(out).lines_lossy() {
         match line {
             Ok(line) => {
+                write_log(&log, Stream::Stdout, &line);
                 let so = StructuredOutput::succinct(id, "O", output::get_format(), &line);
                 if let Err(e) = so.println() {
                     println!("printing output: '{}' to stdout resulted in error: {}",
@@ -90,12 +145,13 @@ fn pipe_stdout<T>(out: T, id: &str)
 }
 
 /// Consume standard error from a child process until EOF, then finish
-fn pipe_stderr<T>(err: T, id: &str)
+fn pipe_stderr<T>(err: T, id: &str, log: Option<SharedLog>)
     where T: Read
 {
     for line in BufReader::new(err).lines_lossy() {
         match line {
             Ok(line) => {
+                write_log(&log, Stream::Stderr, &line);
                 let so = StructuredOutput::succinct(id, "E", output::get_format(), &line);
                 if let Err(e) = so.eprintln() {
                     println!("printing output: '{}' to stderr resulted in error: {}",
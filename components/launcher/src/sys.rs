@@ -1,3 +1,11 @@
+#[cfg(unix)]
+#[path = "sys/unix/cgroup.rs"]
+pub mod cgroup;
+
+#[cfg(unix)]
+#[path = "sys/unix/socket.rs"]
+pub mod socket;
+
 #[cfg(unix)]
 #[path = "sys/unix/service.rs"]
 pub mod service;
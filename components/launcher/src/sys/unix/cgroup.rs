@@ -0,0 +1,176 @@
+//! Placement of services into their own cgroup v2 cgroups.
+//!
+//! Every service the Launcher spawns gets a cgroup named after its
+//! service group (e.g. "redis.default") beneath a slice owned by the
+//! Supervisor. Any resource limits requested in the `Spawn` message
+//! are written to that cgroup before the service's process joins it,
+//! so a single runaway service can no longer starve the rest of the
+//! host.
+
+use crate::protocol::ResourceLimits;
+use log::debug;
+use std::{ffi::CString,
+          fs,
+          io,
+          os::unix::ffi::OsStrExt,
+          path::{Path,
+                 PathBuf}};
+
+/// Mount point of the unified cgroup v2 hierarchy.
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// The controllers we enable for the slice, and thus for every service.
+const CONTROLLERS: &[&str] = &["cpu", "memory", "pids", "io"];
+
+/// The `cpu.max` period, in microseconds. 1000 millicpus correspond
+/// to a quota of one full period.
+const CPU_MAX_PERIOD: u64 = 100_000;
+
+habitat_core::env_config_string!(
+    /// The cgroup, relative to the root of the cgroup v2 hierarchy,
+    /// beneath which all service cgroups are created.
+    CgroupSlice,
+    HAB_LAUNCH_CGROUP_SLICE,
+    "hab-sup.slice"
+);
+
+/// A cgroup created for a single service.
+///
+/// The cgroup is removed when this is dropped. This only succeeds
+/// once every process in it has exited, which is exactly what we
+/// want; a cgroup that is still populated is left in place.
+#[derive(Debug)]
+pub struct Cgroup {
+    path:  PathBuf,
+    procs: CString,
+}
+
+impl Cgroup {
+    /// Create (or reuse) the cgroup for the service `id` and apply
+    /// `limits` to it.
+    ///
+    /// Returns an error if the host does not use cgroup v2, the
+    /// slice cannot be set up, or any requested limit cannot be
+    /// written.
+    pub fn create(id: &str, limits: &ResourceLimits) -> io::Result<Self> {
+        let root = Path::new(CGROUP_ROOT);
+        if !root.join("cgroup.controllers").is_file() {
+            return Err(io::Error::new(io::ErrorKind::NotFound,
+                                      "cgroup v2 hierarchy is not mounted"));
+        }
+
+        let slice = root.join(CgroupSlice::configured_value().0);
+        create_dir(&slice)?;
+        enable_controllers(root);
+        enable_controllers(&slice);
+
+        let path = slice.join(id);
+        create_dir(&path)?;
+        let procs = CString::new(path.join("cgroup.procs").as_os_str().as_bytes())?;
+        // Constructed up front so that the cgroup is removed again if
+        // any of the limits below cannot be applied.
+        let cgroup = Cgroup { path, procs };
+
+        // A cgroup for this service may be left over from a previous
+        // run, so anything that was not requested is reset to its
+        // default rather than skipped.
+        write_limit(&cgroup.path,
+                    "memory.max",
+                    limits.memory_max.map(|m| m.to_string()),
+                    "max")?;
+        write_limit(&cgroup.path,
+                    "cpu.max",
+                    limits.cpu_max
+                          .map(|m| format!("{} {}", u64::from(m) * 100, CPU_MAX_PERIOD)),
+                    "max")?;
+        write_limit(&cgroup.path,
+                    "pids.max",
+                    limits.pids_max.map(|p| p.to_string()),
+                    "max")?;
+        write_limit(&cgroup.path,
+                    "io.weight",
+                    limits.io_weight.map(|w| format!("default {}", w)),
+                    "default 100")?;
+
+        Ok(cgroup)
+    }
+
+    /// Returns a function, for use as a `pre_exec` callback, that
+    /// moves the calling process into this cgroup.
+    ///
+    /// The callback only makes raw system calls on a path that was
+    /// prepared up front, so it is safe to run between `fork` and
+    /// `exec`.
+    pub fn join_fn(&self) -> impl FnMut() -> io::Result<()> + Send + Sync + 'static {
+        let procs = self.procs.clone();
+        move || {
+            unsafe {
+                let fd = libc::open(procs.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                if fd < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                // Writing "0" moves the writing process itself.
+                let written = libc::write(fd, b"0".as_ptr() as *const libc::c_void, 1);
+                let result = if written < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(())
+                };
+                libc::close(fd);
+                result
+            }
+        }
+    }
+}
+
+impl Drop for Cgroup {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir(&self.path) {
+            debug!("Not removing cgroup {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+fn create_dir(path: &Path) -> io::Result<()> {
+    match fs::create_dir(path) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Delegate our controllers to the children of `cgroup`.
+///
+/// Controllers are enabled one at a time, since the kernel rejects
+/// the entire write if any single controller is unavailable. A
+/// controller that cannot be enabled simply means the corresponding
+/// limit fails to apply later on.
+fn enable_controllers(cgroup: &Path) {
+    let subtree_control = cgroup.join("cgroup.subtree_control");
+    for controller in CONTROLLERS {
+        if let Err(e) = fs::write(&subtree_control, format!("+{}", controller)) {
+            debug!("Unable to enable the {} controller in {}: {}",
+                   controller,
+                   cgroup.display(),
+                   e);
+        }
+    }
+}
+
+/// Write `value` to the interface file `name`, or reset it to
+/// `default` when no value was requested. Failing to reset is not an
+/// error, since the controller may simply not be available.
+fn write_limit(cgroup: &Path, name: &str, value: Option<String>, default: &str) -> io::Result<()> {
+    let file = cgroup.join(name);
+    match value {
+        Some(value) => {
+            fs::write(&file, value).map_err(|e| {
+                                       io::Error::new(e.kind(),
+                                                      format!("writing {}: {}", file.display(), e))
+                                   })
+        }
+        None => {
+            fs::write(&file, default).ok();
+            Ok(())
+        }
+    }
+}
@@ -1,31 +1,41 @@
 use crate::{error::ServiceRunError,
             protocol::{self,
                        ShutdownMethod},
-            service::Service};
+            service::Service,
+            sys::{cgroup::Cgroup,
+                  socket}};
 use anyhow::Result;
 use habitat_core::os::{self,
                        process::{exec,
                                  signal,
+                                 CpuAffinity,
+                                 ProcessControls,
                                  Signal}};
-use log::debug;
+use log::{debug,
+          warn};
 use nix::unistd::{Gid,
                   Uid};
 use std::{io,
+          net::TcpListener,
           ops::Neg,
           process::{Child,
                     ExitStatus},
           time::{Duration,
                  Instant}};
 
-pub struct Process(Child);
+pub struct Process {
+    child:   Child,
+    // Held so that the service's cgroup is cleaned up along with the process
+    _cgroup: Option<Cgroup>,
+}
 
 impl Process {
-    pub fn id(&self) -> u32 { self.0.id() }
+    pub fn id(&self) -> u32 { self.child.id() }
 
     /// Attempt to gracefully terminate a process and then forcefully kill it after
     /// 8 seconds if it has not terminated.
     pub fn kill(&mut self) -> ShutdownMethod {
-        let mut pid_to_kill = self.0.id() as i32;
+        let mut pid_to_kill = self.child.id() as i32;
         // check the group of the process being killed
         // if it is the root process of the process group
         // we send our signals to the entire process group
@@ -64,12 +74,24 @@ impl Process {
         }
     }
 
-    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> { self.0.try_wait() }
+    /// Send `signal` to the process alone, not to the rest of its
+    /// process group, as systemd does for `kill -HUP $MAINPID`.
+    pub fn signal(&self, signal: Signal) -> io::Result<()> {
+        let pid = self.child.id() as i32;
+        match unsafe { libc::kill(pid, signal.into()) } {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
 
-    pub fn wait(&mut self) -> io::Result<ExitStatus> { self.0.wait() }
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> { self.child.try_wait() }
+
+    pub fn wait(&mut self) -> io::Result<ExitStatus> { self.child.wait() }
 }
 
-pub fn run(msg: protocol::Spawn) -> Result<Service, ServiceRunError> {
+pub fn run(msg: protocol::Spawn,
+           sockets: Vec<(u16, TcpListener)>)
+           -> Result<Service, ServiceRunError> {
     debug!("launcher is spawning {}", msg.binary);
 
     // Favor explicitly set UID/GID over names when present
@@ -101,12 +123,46 @@ pub fn run(msg: protocol::Spawn) -> Result<Service, ServiceRunError> {
     };
     let gid = Gid::from_raw(group_id);
 
-    let mut cmd = exec::unix::hook_command(&msg.binary, &msg.env, Some((uid, gid)));
+    // Resource limits are best-effort; failing to set up a cgroup
+    // should not prevent the service from running at all.
+    let cgroup = match Cgroup::create(&msg.id, &msg.resource_limits) {
+        Ok(cgroup) => Some(cgroup),
+        Err(err) if msg.resource_limits.is_empty() => {
+            debug!("Not placing {} in its own cgroup: {}", msg.id, err);
+            None
+        }
+        Err(err) => {
+            warn!("Unable to apply resource limits to {}; starting it without them: {}",
+                  msg.id, err);
+            None
+        }
+    };
+    let controls = &msg.process_controls;
+    let controls = ProcessControls { nofile:       controls.nofile,
+                                     nproc:        controls.nproc,
+                                     core:         controls.core,
+                                     memlock:      controls.memlock,
+                                     nice:         controls.nice,
+                                     ionice:       controls.ionice,
+                                     cpu_affinity: controls.cpu_affinity.map(CpuAffinity::from), };
+    // Unlike resource limits, process controls that cannot be applied
+    // cause the spawn to fail.
+    let ids = Some((uid, gid));
+    let mut cmd = match &cgroup {
+        Some(cgroup) => {
+            exec::unix::service_command(&msg.binary, &msg.env, ids, controls, cgroup.join_fn())
+        }
+        None => exec::unix::service_command(&msg.binary, &msg.env, ids, controls, || Ok(())),
+    };
+    if !sockets.is_empty() {
+        socket::pass_listen_fds(&mut cmd, sockets);
+    }
 
     let mut child = cmd.spawn().map_err(ServiceRunError::Spawn)?;
     let stdout = child.stdout.take();
     let stderr = child.stderr.take();
-    let process = Process(child);
+    let process = Process { child,
+                            _cgroup: cgroup };
     debug!(target: "pidfile_tracing", "Launcher spawned {} with PID = {}", msg.binary, process.id());
     Ok(Service::new(msg, process, stdout, stderr))
 }
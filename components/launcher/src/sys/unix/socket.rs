@@ -0,0 +1,106 @@
+//! Handing listening sockets to services, following the systemd
+//! socket activation convention (see sd_listen_fds(3)).
+//!
+//! The sockets are passed as file descriptors 3 onwards, with
+//! `LISTEN_FDS` holding how many there are, `LISTEN_FDNAMES` the
+//! ports they listen on, and `LISTEN_PID` the PID of the process they
+//! are meant for. Since the Launcher binds the sockets itself, a
+//! service running as an unprivileged user can still listen on a port
+//! below 1024.
+
+use std::{ffi::CStr,
+          io,
+          net::TcpListener,
+          os::unix::{io::AsRawFd,
+                     process::CommandExt},
+          process::Command};
+
+/// The first file descriptor passed, as fixed by the convention.
+const LISTEN_FDS_START: libc::c_int = 3;
+
+const LISTEN_PID: &[u8] = b"LISTEN_PID=";
+
+extern "C" {
+    static mut environ: *const *const libc::c_char;
+}
+
+/// Arrange for the process `cmd` spawns to be handed `sockets`, in
+/// order.
+pub fn pass_listen_fds(cmd: &mut Command, sockets: Vec<(u16, TcpListener)>) {
+    let names = sockets.iter()
+                       .map(|(port, _)| port.to_string())
+                       .collect::<Vec<_>>()
+                       .join(":");
+    // The PID isn't known until the child has been forked, so this is
+    // only a placeholder; see `set_listen_pid`.
+    cmd.env("LISTEN_FDS", sockets.len().to_string())
+       .env("LISTEN_FDNAMES", names)
+       .env("LISTEN_PID", "0");
+
+    let count = sockets.len() as libc::c_int;
+    let mut moved = vec![-1; sockets.len()];
+    // Room for "LISTEN_PID=", the 10 digits of the largest PID, and a
+    // terminating NUL
+    let mut pid_var = [0; 22];
+    pid_var[..LISTEN_PID.len()].copy_from_slice(LISTEN_PID);
+    unsafe {
+        cmd.pre_exec(move || {
+               // Move every socket out of the way first, in case one of
+               // them already sits where another one is going.
+               for (fd, (_, socket)) in moved.iter_mut().zip(&sockets) {
+                   *fd = cvt(libc::fcntl(socket.as_raw_fd(),
+                                         libc::F_DUPFD_CLOEXEC,
+                                         LISTEN_FDS_START + count))?;
+               }
+               // Unlike the descriptors they're copied from, those
+               // created by `dup2` stay open across `exec`.
+               for (i, fd) in moved.iter().enumerate() {
+                   cvt(libc::dup2(*fd, LISTEN_FDS_START + i as libc::c_int))?;
+               }
+               set_listen_pid(&mut pid_var);
+               Ok(())
+           });
+    }
+}
+
+/// Point the `LISTEN_PID` entry of the environment the child is about
+/// to `exec` with at `buf`, completed with the child's PID.
+///
+/// The standard library points `environ` at that environment before
+/// running `pre_exec` callbacks. Since this runs between `fork` and
+/// `exec`, it must not allocate.
+unsafe fn set_listen_pid(buf: &mut [u8; 22]) {
+    let mut pid = libc::getpid() as u32;
+    let mut digits = [0; 10];
+    let mut len = 0;
+    loop {
+        digits[len] = b'0' + (pid % 10) as u8;
+        pid /= 10;
+        len += 1;
+        if pid == 0 {
+            break;
+        }
+    }
+    let start = LISTEN_PID.len();
+    for i in 0..len {
+        buf[start + i] = digits[len - 1 - i];
+    }
+    buf[start + len] = 0;
+
+    let mut entry = environ as *mut *const libc::c_char;
+    while !(*entry).is_null() {
+        if CStr::from_ptr(*entry).to_bytes().starts_with(LISTEN_PID) {
+            *entry = buf.as_ptr().cast();
+            return;
+        }
+        entry = entry.add(1);
+    }
+}
+
+fn cvt(result: libc::c_int) -> io::Result<libc::c_int> {
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
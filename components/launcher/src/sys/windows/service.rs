@@ -1,6 +1,7 @@
 use crate::{core::{os::{process::{handle_from_pid,
                                   windows_child::{ExitStatus,
-                                                  Handle}},
+                                                  Handle},
+                                  Signal},
                         users::get_current_username},
                    util},
             error::ServiceRunError,
@@ -14,6 +15,7 @@ use std::{collections::HashMap,
           env,
           io,
           mem,
+          net::TcpListener,
           time::{Duration,
                  Instant}};
 use winapi::{shared::{minwindef::{DWORD,
@@ -76,6 +78,12 @@ impl Process {
         }
     }
 
+    /// Windows processes can't be sent Unix signals.
+    pub fn signal(&self, signal: Signal) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other,
+                           format!("Cannot send {} on Windows", signal)))
+    }
+
     pub fn wait(&mut self) -> io::Result<ExitStatus> {
         unsafe {
             let res = synchapi::WaitForSingleObject(self.handle.raw(), INFINITE);
@@ -116,7 +124,11 @@ impl Process {
     }
 }
 
-pub fn run(msg: protocol::Spawn) -> Result<Service, ServiceRunError> {
+// `ServiceTable::listen_sockets` never holds sockets on Windows, so
+// `_sockets` is always empty.
+pub fn run(msg: protocol::Spawn,
+           _sockets: Vec<(u16, TcpListener)>)
+           -> Result<Service, ServiceRunError> {
     debug!("launcher is spawning {}", msg.binary);
     let ps_cmd = format!("iex $(gc {} | out-string)", &msg.binary);
     let password = msg.svc_password.clone();
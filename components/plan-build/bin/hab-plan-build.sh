@@ -211,6 +211,65 @@
 #   [storage]="port host"
 # )
 #
+# ### pkg_svc_rlimits
+# An associative array of POSIX resource limits to apply to the service by default. Supported keys
+# are `nofile`, `nproc`, `core` and `memlock`. Each can be overridden by `hab svc load`.
+# ```
+# pkg_svc_rlimits=(
+#   [nofile]=65536
+# )
+# ```
+#
+# ### pkg_svc_nice
+# The default scheduling priority of the service, from -20 (highest) to 19 (lowest).
+# ```
+# pkg_svc_nice=-5
+# ```
+#
+# ### pkg_svc_ionice
+# The default best-effort IO scheduling priority of the service, from 0 (highest) to 7 (lowest).
+# ```
+# pkg_svc_ionice=2
+# ```
+#
+# ### pkg_svc_cpu_affinity
+# The CPUs the service may run on by default, as a CPU list.
+# ```
+# pkg_svc_cpu_affinity="0-3,8"
+# ```
+#
+# ### pkg_reload_signal
+# The signal that makes the service reload its configuration. When set, the Supervisor sends it
+# to the service when its rendered configuration changes, rather than restarting it or running
+# its `reconfigure` hook.
+# ```
+# pkg_reload_signal=HUP
+# ```
+#
+# ### pkg_hook_timeouts
+# An associative array of how long, in seconds, each of the service's hooks may run before the
+# Supervisor kills it. Hooks that aren't listed may run for as long as they like. Each can be
+# overridden by `hab svc load`.
+# ```
+# pkg_hook_timeouts=(
+#   [init]=300
+#   [reconfigure]=30
+# )
+# ```
+#
+# ### pkg_health_probes
+# An array of checks the Supervisor runs itself to find out whether the service is healthy, in
+# place of a `health-check` hook: an `http://` URL optionally followed by the status the response
+# must have, a `tcp://` address to connect to, or `exec:` followed by a command to run. Can be
+# replaced by `hab svc load`.
+# ```
+# pkg_health_probes=(
+#   "http://localhost:8080/healthz 200"
+#   "tcp://localhost:5432"
+#   "exec:pg_isready -q"
+# )
+# ```
+#
 # ### pkg_origin
 # A string to use for the origin. The origin is used to denote a particular upstream of a
 # package; when we resolve dependencies, we consider a version of a package to be equal
@@ -393,9 +452,12 @@ pkg_pconfig_dirs=()
 # The command to run the service - must not fork or return
 pkg_svc_run=''
 pkg_exposes=()
+pkg_health_probes=()
 declare -A pkg_exports
 declare -A pkg_binds
 declare -A pkg_binds_optional
+declare -A pkg_svc_rlimits
+declare -A pkg_hook_timeouts
 # The user to run the service as
 pkg_svc_user=hab
 # The group to run the service as
@@ -1987,6 +2049,13 @@ do_default_install() {
 # * `$pkg_prefix/LDFLAGS` - Any LDFLAGS for things that link against us
 # * `$pkg_prefix/LD_RUN_PATH` - The LD_RUN_PATH for things that link against us
 # * `$pkg_prefix/PATH` - Any PATH entries for things that link against us
+# * `$pkg_prefix/SVC_RLIMITS` - Default POSIX resource limits for the service
+# * `$pkg_prefix/SVC_NICE` - Default scheduling priority for the service
+# * `$pkg_prefix/SVC_IONICE` - Default IO scheduling priority for the service
+# * `$pkg_prefix/SVC_CPU_AFFINITY` - Default CPUs for the service to run on
+# * `$pkg_prefix/RELOAD_SIGNAL` - The signal that makes the service reload its configuration
+# * `$pkg_prefix/HOOK_TIMEOUTS` - How long each of the service's hooks may run
+# * `$pkg_prefix/HEALTH_PROBES` - The service's built-in health probes, one per line
 _build_metadata() {
   build_line "Building package metadata"
 
@@ -2023,6 +2092,13 @@ _build_metadata() {
   if [[ -f "$PLAN_CONTEXT/hooks/run" || -n "${pkg_svc_run:-}" ]]; then
     _render_metadata_SVC_USER
     _render_metadata_SVC_GROUP
+    _render_metadata_SVC_RLIMITS
+    _render_metadata_SVC_NICE
+    _render_metadata_SVC_IONICE
+    _render_metadata_SVC_CPU_AFFINITY
+    _render_metadata_RELOAD_SIGNAL
+    _render_metadata_HOOK_TIMEOUTS
+    _render_metadata_HEALTH_PROBES
   fi
 
   # We render out the PACKAGE_TYPE metadata file only for native packages.
@@ -238,6 +238,50 @@ _render_metadata_SVC_GROUP() {
   echo "$pkg_svc_group" > "$pkg_prefix"/SVC_GROUP
 }
 
+_render_metadata_SVC_CPU_AFFINITY() {
+  if [[ -n "${pkg_svc_cpu_affinity:-}" ]]; then
+    debug "Rendering SVC_CPU_AFFINITY metadata file"
+    echo "$pkg_svc_cpu_affinity" > "$pkg_prefix"/SVC_CPU_AFFINITY
+  fi
+}
+
+_render_metadata_SVC_IONICE() {
+  if [[ -n "${pkg_svc_ionice:-}" ]]; then
+    debug "Rendering SVC_IONICE metadata file"
+    echo "$pkg_svc_ionice" > "$pkg_prefix"/SVC_IONICE
+  fi
+}
+
+_render_metadata_SVC_NICE() {
+  if [[ -n "${pkg_svc_nice:-}" ]]; then
+    debug "Rendering SVC_NICE metadata file"
+    echo "$pkg_svc_nice" > "$pkg_prefix"/SVC_NICE
+  fi
+}
+
+_render_metadata_RELOAD_SIGNAL() {
+  if [[ -n "${pkg_reload_signal:-}" ]]; then
+    debug "Rendering RELOAD_SIGNAL metadata file"
+    echo "$pkg_reload_signal" > "$pkg_prefix"/RELOAD_SIGNAL
+  fi
+}
+
+_render_metadata_HOOK_TIMEOUTS() {
+  _render_associative_array_file "${pkg_prefix}" HOOK_TIMEOUTS pkg_hook_timeouts
+}
+
+_render_metadata_HEALTH_PROBES() {
+  # shellcheck disable=2154
+  if [[ ${#pkg_health_probes[@]} -gt 0 ]]; then
+    debug "Rendering HEALTH_PROBES metadata file"
+    printf '%s\n' "${pkg_health_probes[@]}" > "$pkg_prefix"/HEALTH_PROBES
+  fi
+}
+
+_render_metadata_SVC_RLIMITS() {
+  _render_associative_array_file "${pkg_prefix}" SVC_RLIMITS pkg_svc_rlimits
+}
+
 _render_metadata_SVC_USER() {
   debug "Rendering SVC_USER metadata file"
   # shellcheck disable=2154
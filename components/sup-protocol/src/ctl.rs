@@ -3,8 +3,12 @@
 //!
 //! Note: See `protocols/ctl.proto` for type level documentation for generated types.
 
-use crate::message;
-use std::fmt;
+use crate::{message,
+            net::{self,
+                  ErrCode,
+                  NetErr}};
+use std::{convert::TryFrom,
+          fmt};
 
 include!(concat!(env!("OUT_DIR"), "/sup.ctl.rs"));
 
@@ -20,6 +24,26 @@ impl message::MessageStatic for ServiceBindList {
     const MESSAGE_ID: &'static str = "ServiceBindList";
 }
 
+impl message::MessageStatic for PackageIdentList {
+    const MESSAGE_ID: &'static str = "PackageIdentList";
+}
+
+impl message::MessageStatic for MaintenanceWindowList {
+    const MESSAGE_ID: &'static str = "MaintenanceWindowList";
+}
+
+impl message::MessageStatic for ServiceEnv {
+    const MESSAGE_ID: &'static str = "ServiceEnv";
+}
+
+impl message::MessageStatic for HealthProbeList {
+    const MESSAGE_ID: &'static str = "HealthProbeList";
+}
+
+impl message::MessageStatic for PortList {
+    const MESSAGE_ID: &'static str = "PortList";
+}
+
 impl message::MessageStatic for SupDepart {
     const MESSAGE_ID: &'static str = "SupDepart";
 }
@@ -40,6 +64,18 @@ impl message::MessageStatic for SvcValidateCfg {
     const MESSAGE_ID: &'static str = "SvcValidateCfg";
 }
 
+impl message::MessageStatic for SvcConfigHistory {
+    const MESSAGE_ID: &'static str = "SvcConfigHistory";
+}
+
+impl message::MessageStatic for SvcConfigRollback {
+    const MESSAGE_ID: &'static str = "SvcConfigRollback";
+}
+
+impl message::MessageStatic for SvcDryRunCfg {
+    const MESSAGE_ID: &'static str = "SvcDryRunCfg";
+}
+
 impl message::MessageStatic for SvcSetCfg {
     const MESSAGE_ID: &'static str = "SvcSetCfg";
 }
@@ -64,6 +100,30 @@ impl message::MessageStatic for SvcStop {
     const MESSAGE_ID: &'static str = "SvcStop";
 }
 
+impl message::MessageStatic for SvcReset {
+    const MESSAGE_ID: &'static str = "SvcReset";
+}
+
+impl message::MessageStatic for SvcDrain {
+    const MESSAGE_ID: &'static str = "SvcDrain";
+}
+
+impl message::MessageStatic for SvcUndrain {
+    const MESSAGE_ID: &'static str = "SvcUndrain";
+}
+
+impl message::MessageStatic for SvcLogs {
+    const MESSAGE_ID: &'static str = "SvcLogs";
+}
+
+impl message::MessageStatic for SvcHooks {
+    const MESSAGE_ID: &'static str = "SvcHooks";
+}
+
+impl message::MessageStatic for SvcHealthHistory {
+    const MESSAGE_ID: &'static str = "SvcHealthHistory";
+}
+
 impl message::MessageStatic for SvcStatus {
     const MESSAGE_ID: &'static str = "SvcStatus";
 }
@@ -90,3 +150,66 @@ impl Into<Vec<habitat_core::service::ServiceBind>> for ServiceBindList {
         self.binds.into_iter().map(Into::into).collect()
     }
 }
+
+impl std::iter::FromIterator<habitat_core::package::PackageIdent> for PackageIdentList {
+    fn from_iter<T>(iter: T) -> Self
+        where T: IntoIterator<Item = habitat_core::package::PackageIdent>
+    {
+        PackageIdentList { idents: iter.into_iter().map(Into::into).collect(), }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<Vec<habitat_core::package::PackageIdent>> for PackageIdentList {
+    fn into(self) -> Vec<habitat_core::package::PackageIdent> {
+        self.idents.into_iter().map(Into::into).collect()
+    }
+}
+
+impl std::iter::FromIterator<habitat_core::service::MaintenanceWindow> for MaintenanceWindowList {
+    fn from_iter<T>(iter: T) -> Self
+        where T: IntoIterator<Item = habitat_core::service::MaintenanceWindow>
+    {
+        MaintenanceWindowList { windows: iter.into_iter().map(Into::into).collect(), }
+    }
+}
+
+impl TryFrom<MaintenanceWindowList> for Vec<habitat_core::service::MaintenanceWindow> {
+    type Error = NetErr;
+
+    fn try_from(list: MaintenanceWindowList) -> Result<Self, Self::Error> {
+        let invalid = |e: habitat_core::Error| net::err(ErrCode::InvalidPayload, e);
+        list.windows
+            .iter()
+            .map(|window| window.parse().map_err(invalid))
+            .collect()
+    }
+}
+
+impl std::iter::FromIterator<habitat_core::service::HealthProbe> for HealthProbeList {
+    fn from_iter<T>(iter: T) -> Self
+        where T: IntoIterator<Item = habitat_core::service::HealthProbe>
+    {
+        HealthProbeList { probes: iter.into_iter().map(Into::into).collect(), }
+    }
+}
+
+impl TryFrom<HealthProbeList> for Vec<habitat_core::service::HealthProbe> {
+    type Error = NetErr;
+
+    fn try_from(list: HealthProbeList) -> Result<Self, Self::Error> {
+        let invalid = |e: habitat_core::Error| net::err(ErrCode::InvalidPayload, e);
+        list.probes
+            .iter()
+            .map(|probe| probe.parse().map_err(invalid))
+            .collect()
+    }
+}
+
+impl std::iter::FromIterator<(String, String)> for ServiceEnv {
+    fn from_iter<T>(iter: T) -> Self
+        where T: IntoIterator<Item = (String, String)>
+    {
+        ServiceEnv { vars: iter.into_iter().collect(), }
+    }
+}
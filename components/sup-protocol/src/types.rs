@@ -12,7 +12,8 @@ use crate::{core::{self,
             net::{self,
                   ErrCode,
                   NetErr}};
-use std::{fmt::{self,
+use std::{convert::TryFrom,
+          fmt::{self,
                 Write},
           str::FromStr};
 
@@ -36,9 +37,48 @@ impl message::MessageStatic for ServiceGroup {
 impl message::MessageStatic for ServiceStatus {
     const MESSAGE_ID: &'static str = "ServiceStatus";
 }
+impl message::MessageStatic for JobStatus {
+    const MESSAGE_ID: &'static str = "JobStatus";
+}
+impl message::MessageStatic for JobRun {
+    const MESSAGE_ID: &'static str = "JobRun";
+}
+impl message::MessageStatic for HookRun {
+    const MESSAGE_ID: &'static str = "HookRun";
+}
+impl message::MessageStatic for HealthCheck {
+    const MESSAGE_ID: &'static str = "HealthCheck";
+}
+impl message::MessageStatic for HealthHistory {
+    const MESSAGE_ID: &'static str = "HealthHistory";
+}
+impl message::MessageStatic for ServiceConfigVersion {
+    const MESSAGE_ID: &'static str = "ServiceConfigVersion";
+}
+impl message::MessageStatic for ServiceConfigHistory {
+    const MESSAGE_ID: &'static str = "ServiceConfigHistory";
+}
+impl message::MessageStatic for CfgDryRun {
+    const MESSAGE_ID: &'static str = "CfgDryRun";
+}
 impl message::MessageStatic for HealthCheckInterval {
     const MESSAGE_ID: &'static str = "HealthCheckInterval";
 }
+impl message::MessageStatic for LivenessPolicy {
+    const MESSAGE_ID: &'static str = "LivenessPolicy";
+}
+impl message::MessageStatic for CanaryPolicy {
+    const MESSAGE_ID: &'static str = "CanaryPolicy";
+}
+impl message::MessageStatic for RolloutPolicy {
+    const MESSAGE_ID: &'static str = "RolloutPolicy";
+}
+impl message::MessageStatic for ResourceLimits {
+    const MESSAGE_ID: &'static str = "ResourceLimits";
+}
+impl message::MessageStatic for ProcessControls {
+    const MESSAGE_ID: &'static str = "ProcessControls";
+}
 
 impl ServiceGroup {
     pub fn validate(value: &str) -> core::Result<()> {
@@ -74,6 +114,7 @@ impl fmt::Display for ProcessState {
         let state = match *self {
             ProcessState::Down => "down",
             ProcessState::Up => "up",
+            ProcessState::Failed => "failed",
         };
         write!(f, "{}", state)
     }
@@ -114,10 +155,11 @@ impl FromStr for ProcessState {
         match value.to_lowercase().as_ref() {
             "0" => Ok(ProcessState::Down),
             "1" => Ok(ProcessState::Up),
+            "2" => Ok(ProcessState::Failed),
             _ => {
                 Err(net::err(ErrCode::InvalidPayload,
                              format!("Invalid process state \"{:?}\", must \
-                                      be `up` or `down`.",
+                                      be `up`, `down` or `failed`.",
                                      value)))
             }
         }
@@ -200,6 +242,104 @@ impl From<core::service::HealthCheckInterval> for HealthCheckInterval {
     fn from(h: core::service::HealthCheckInterval) -> Self { Self { seconds: h.into() } }
 }
 
+impl From<core::service::LivenessPolicy> for LivenessPolicy {
+    fn from(policy: core::service::LivenessPolicy) -> Self {
+        Self { failure_threshold: policy.failure_threshold,
+               grace_period:      policy.grace_period, }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<core::service::LivenessPolicy> for LivenessPolicy {
+    fn into(self) -> core::service::LivenessPolicy {
+        core::service::LivenessPolicy { failure_threshold: self.failure_threshold,
+                                        grace_period:      self.grace_period, }
+    }
+}
+
+impl From<core::service::CanaryPolicy> for CanaryPolicy {
+    fn from(policy: core::service::CanaryPolicy) -> Self {
+        Self { size:        policy.size.map(|size| size.to_string()),
+               bake_period: policy.bake_period, }
+    }
+}
+
+impl TryFrom<CanaryPolicy> for core::service::CanaryPolicy {
+    type Error = NetErr;
+
+    fn try_from(policy: CanaryPolicy) -> Result<Self, Self::Error> {
+        let size = match policy.size {
+            Some(size) => {
+                let size = size.parse::<core::service::CanarySize>()
+                               .map_err(|e| net::err(ErrCode::InvalidPayload, e))?;
+                Some(size)
+            }
+            None => None,
+        };
+        Ok(Self { size,
+                  bake_period: policy.bake_period })
+    }
+}
+
+impl From<core::service::RolloutPolicy> for RolloutPolicy {
+    fn from(policy: core::service::RolloutPolicy) -> Self {
+        Self { batch_size:     policy.batch_size,
+               health_timeout: policy.health_timeout, }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<core::service::RolloutPolicy> for RolloutPolicy {
+    fn into(self) -> core::service::RolloutPolicy {
+        core::service::RolloutPolicy { batch_size:     self.batch_size,
+                                       health_timeout: self.health_timeout, }
+    }
+}
+
+impl From<core::os::process::ResourceLimits> for ResourceLimits {
+    fn from(limits: core::os::process::ResourceLimits) -> Self {
+        Self { memory_max: limits.memory_max,
+               cpu_max:    limits.cpu_max,
+               pids_max:   limits.pids_max,
+               io_weight:  limits.io_weight, }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<core::os::process::ResourceLimits> for ResourceLimits {
+    fn into(self) -> core::os::process::ResourceLimits {
+        core::os::process::ResourceLimits { memory_max: self.memory_max,
+                                            cpu_max:    self.cpu_max,
+                                            pids_max:   self.pids_max,
+                                            io_weight:  self.io_weight, }
+    }
+}
+
+impl From<core::os::process::ProcessControls> for ProcessControls {
+    fn from(controls: core::os::process::ProcessControls) -> Self {
+        Self { nofile:       controls.nofile,
+               nproc:        controls.nproc,
+               core:         controls.core,
+               memlock:      controls.memlock,
+               nice:         controls.nice,
+               ionice:       controls.ionice,
+               cpu_affinity: controls.cpu_affinity.map(|c| c.mask()), }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<core::os::process::ProcessControls> for ProcessControls {
+    fn into(self) -> core::os::process::ProcessControls {
+        core::os::process::ProcessControls { nofile:       self.nofile,
+                                             nproc:        self.nproc,
+                                             core:         self.core,
+                                             memlock:      self.memlock,
+                                             nice:         self.nice,
+                                             ionice:       self.ionice,
+                                             cpu_affinity: self.cpu_affinity.map(Into::into), }
+    }
+}
+
 impl From<package::PackageIdent> for PackageIdent {
     fn from(ident: package::PackageIdent) -> Self {
         Self { origin:  ident.origin,
@@ -306,6 +446,7 @@ impl UpdateStrategy {
             UpdateStrategy::None => "none",
             UpdateStrategy::AtOnce => "at-once",
             UpdateStrategy::Rolling => "rolling",
+            UpdateStrategy::Canary => "canary",
         }
     }
 }
@@ -318,6 +459,7 @@ impl FromStr for UpdateStrategy {
             "none" => Ok(UpdateStrategy::None),
             "at-once" => Ok(UpdateStrategy::AtOnce),
             "rolling" => Ok(UpdateStrategy::Rolling),
+            "canary" => Ok(UpdateStrategy::Canary),
             _ => Err(net::err(ErrCode::InvalidPayload, "Invalid update strategy.")),
         }
     }
@@ -437,6 +579,22 @@ mod test {
         assert!(UpdateStrategy::from_str(strategy_str).is_err());
     }
 
+    #[test]
+    fn update_strategy_canary_round_trips() {
+        let strategy = UpdateStrategy::from_str("canary").unwrap();
+
+        assert_eq!(strategy, UpdateStrategy::Canary);
+        assert_eq!("canary", strategy.to_string());
+    }
+
+    #[test]
+    fn canary_policy_with_invalid_size_is_rejected() {
+        let policy = CanaryPolicy { size:        Some("0%".to_string()),
+                                    bake_period: None, };
+
+        assert!(core::service::CanaryPolicy::try_from(policy).is_err());
+    }
+
     #[test]
     fn update_strategy_to_string() {
         let strategy = UpdateStrategy::AtOnce;
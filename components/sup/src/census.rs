@@ -1,4 +1,6 @@
-use crate::error::Error;
+use crate::{error::Error,
+            manager::service::HealthCheckResult,
+            util::history::BoundedHistory};
 use habitat_butterfly::{member::{Health,
                                  Member,
                                  MemberList,
@@ -14,9 +16,11 @@ use habitat_butterfly::{member::{Health,
                                 RumorStore}};
 use habitat_common::outputln;
 use habitat_core::{self,
-                   crypto::keys::KeyCache,
+                   crypto::{keys::KeyCache,
+                            Blake2bHash},
                    package::PackageIdent,
-                   service::ServiceGroup};
+                   service::{CanarySize,
+                             ServiceGroup}};
 use log::warn;
 use serde::{ser::SerializeStruct,
             Serialize,
@@ -29,7 +33,8 @@ use std::{borrow::Cow,
           iter::IntoIterator,
           result,
           str::{self,
-                FromStr}};
+                FromStr},
+          time::SystemTime};
 
 static LOGKEY: &str = "CE";
 
@@ -308,6 +313,30 @@ pub struct ServiceConfig {
     pub value:       toml::value::Table,
 }
 
+/// A version of a service group's gossiped configuration that was
+/// applied, kept so that it can be rolled back to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceConfigVersion {
+    pub incarnation: u64,
+    /// When it was applied, in seconds since the epoch.
+    pub applied_at:  u64,
+    pub encrypted:   bool,
+    /// The configuration as it was gossiped, encrypted if `encrypted`
+    /// is set.
+    pub payload:     Vec<u8>,
+}
+
+impl ServiceConfigVersion {
+    /// The version as one for the control gateway.
+    pub fn to_service_config_version(&self) -> habitat_sup_protocol::types::ServiceConfigVersion {
+        let hash = Blake2bHash::from_bytes(&self.payload).to_string();
+        habitat_sup_protocol::types::ServiceConfigVersion { incarnation: self.incarnation,
+                                                            applied_at: self.applied_at as i64,
+                                                            hash,
+                                                            encrypted: self.encrypted }
+    }
+}
+
 #[derive(Debug)]
 pub struct CensusGroup {
     pub service_group:          ServiceGroup,
@@ -322,9 +351,15 @@ pub struct CensusGroup {
     update_leader_id:      Option<MemberId>,
     changed_service_files: HashSet<String>,
     service_files:         HashMap<String, ServiceFile>,
+    /// The applied versions of the group's configuration.
+    config_history:        BoundedHistory<ServiceConfigVersion, { Self::MAX_CONFIG_HISTORY }>,
 }
 
 impl CensusGroup {
+    /// How many applied versions of the group's configuration are
+    /// remembered.
+    const MAX_CONFIG_HISTORY: usize = 10;
+
     fn new(sg: ServiceGroup, local_member_id: &str) -> Self {
         CensusGroup { service_group:          sg,
                       election_status:        ElectionStatus::None,
@@ -336,7 +371,8 @@ impl CensusGroup {
                       update_leader_id:       None,
                       service_config:         None,
                       service_files:          HashMap::new(),
-                      changed_service_files:  HashSet::new(), }
+                      changed_service_files:  HashSet::new(),
+                      config_history:         BoundedHistory::default(), }
     }
 
     /// Returns the census member in the census ring for the running Supervisor.
@@ -369,6 +405,13 @@ impl CensusGroup {
             .filter(|cm| cm.alive() || cm.suspect())
     }
 
+    /// Same as `active_members`, but without members that have been
+    /// drained. These are the members that should be sent traffic,
+    /// e.g. by services binding to this group.
+    pub fn serving_members(&self) -> impl Iterator<Item = &CensusMember> {
+        self.active_members().filter(|cm| !cm.draining)
+    }
+
     /// Return references to all a `CensusGroup`'s `ServiceFiles`.
     pub fn service_files(&self) -> impl IntoIterator<Item = &ServiceFile> {
         self.service_files.values()
@@ -410,6 +453,72 @@ impl CensusGroup {
         None
     }
 
+    /// Return the alive members in the order a rolling update reaches them. Each member updates
+    /// after its previous peer, so this is the update leader followed by the alive peers to its
+    /// right in the ordered members list, wrapping around.
+    pub fn update_order(&self) -> Vec<&CensusMember> {
+        match self.update_leader() {
+            Some(leader) => Self::update_order_impl(self.population.values(), leader),
+            None => Vec::new(),
+        }
+    }
+
+    fn update_order_impl<'a>(members: impl Iterator<Item = &'a CensusMember>,
+                             leader: &CensusMember)
+                             -> Vec<&'a CensusMember> {
+        let mut alive_members = members.filter(|cm| cm.alive()).collect::<Vec<_>>();
+        match alive_members.iter()
+                           .position(|cm| cm.member_id == leader.member_id)
+        {
+            Some(start) => {
+                alive_members.rotate_left(start);
+                alive_members
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Return the alive members a canary update is rolled out to before the rest of the group.
+    pub fn update_canaries(&self, size: CanarySize) -> Vec<&CensusMember> {
+        Self::update_canaries_impl(self.update_order(), size)
+    }
+
+    fn update_canaries_impl(mut order: Vec<&CensusMember>, size: CanarySize) -> Vec<&CensusMember> {
+        order.truncate(size.of(order.len()));
+        order
+    }
+
+    /// Return the members that must finish updating before it is this member's turn in a
+    /// rolling update that updates `batch_size` followers at a time. The update leader updates
+    /// on its own, so the first batch of followers waits for the leader and each later batch
+    /// waits for the batch before it. Returns an empty list for the update leader itself, or if
+    /// the update order is not known.
+    pub fn previous_update_batch(&self, batch_size: usize) -> Vec<&CensusMember> {
+        match self.me() {
+            Some(me) => Self::previous_update_batch_impl(&self.update_order(), me, batch_size),
+            None => Vec::new(),
+        }
+    }
+
+    fn previous_update_batch_impl<'a>(order: &[&'a CensusMember],
+                                      me: &CensusMember,
+                                      batch_size: usize)
+                                      -> Vec<&'a CensusMember> {
+        let batch_size = batch_size.max(1);
+        match order.iter().position(|cm| cm.member_id == me.member_id) {
+            Some(0) | None => Vec::new(),
+            Some(position) => {
+                // Followers are numbered from zero, after the update leader
+                let batch_start = 1 + (position - 1) / batch_size * batch_size;
+                if batch_start == 1 {
+                    vec![order[0]]
+                } else {
+                    order[batch_start - batch_size..batch_start].to_vec()
+                }
+            }
+        }
+    }
+
     fn update_from_service_rumors(&mut self, rumors: &HashMap<String, ServiceRumor>) {
         for (member_id, service_rumor) in rumors.iter() {
             // The group pkg_incarnation holds the highest incarnation of all of its
@@ -484,6 +593,14 @@ impl CensusGroup {
                     self.service_config = Some(ServiceConfig { incarnation:
                                                                    service_config.incarnation,
                                                                value:       config, });
+                    let applied_at = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)
+                                                      .unwrap_or_default()
+                                                      .as_secs();
+                    let version = ServiceConfigVersion { incarnation: service_config.incarnation,
+                                                         applied_at,
+                                                         encrypted: service_config.encrypted,
+                                                         payload: service_config.config.clone() };
+                    self.config_history.push(version);
                 }
             }
             Err(err) => warn!("{}", err),
@@ -525,6 +642,12 @@ impl CensusGroup {
         self.population.get_mut(member_id)
     }
 
+    /// The applied versions of the group's configuration, most recent
+    /// first.
+    pub fn config_history(&self) -> impl Iterator<Item = &ServiceConfigVersion> {
+        self.config_history.iter()
+    }
+
     /// Determine what configuration keys the group as a whole
     /// exports. Returns a set of the top-level exported keys.
     ///
@@ -597,6 +720,15 @@ pub struct CensusMember {
     pub suspect: bool,
     pub confirmed: bool,
     pub departed: bool,
+    /// Whether the member's service reports that it is accepting
+    /// traffic. This is independent of the member's network health.
+    pub ready: bool,
+    /// Whether the member has been drained with `hab svc drain`. Its
+    /// service keeps running, but should no longer be sent traffic.
+    pub draining: bool,
+    /// The result of the member's latest health check, or `None` if
+    /// its Supervisor is too old to report it.
+    pub health: Option<HealthCheckResult>,
     pub cfg: toml::value::Table,
 }
 
@@ -614,6 +746,9 @@ impl CensusMember {
         };
         self.pkg_incarnation = rumor.pkg_incarnation;
         self.sys = rumor.sys.clone();
+        self.ready = rumor.ready;
+        self.draining = rumor.draining;
+        self.health = rumor.health.map(Into::into);
         self.cfg =
             toml::from_str(str::from_utf8(&rumor.cfg).unwrap_or_default()).unwrap_or_default();
     }
@@ -677,6 +812,9 @@ impl CensusMember {
     pub fn confirmed(&self) -> bool { self.confirmed }
 
     pub fn departed(&self) -> bool { self.departed }
+
+    /// Is this member alive and accepting traffic?
+    pub fn ready(&self) -> bool { self.alive && self.ready }
 }
 
 /// This data structure just wraps the CensusMember and allows us to tweak the serialization logic.
@@ -703,7 +841,7 @@ impl<'a> Serialize for CensusMemberProxy<'a> {
     fn serialize<S>(&self, serializer: S) -> result::Result<S::Ok, S::Error>
         where S: Serializer
     {
-        let mut strukt = serializer.serialize_struct("census_member", 24)?;
+        let mut strukt = serializer.serialize_struct("census_member", 27)?;
         strukt.serialize_field("member_id", &self.member_id)?;
         strukt.serialize_field("pkg", &self.pkg)?;
         strukt.serialize_field("pkg_incarnation", &self.pkg_incarnation)?;
@@ -731,6 +869,9 @@ impl<'a> Serialize for CensusMemberProxy<'a> {
         strukt.serialize_field("suspect", &self.suspect)?;
         strukt.serialize_field("confirmed", &self.confirmed)?;
         strukt.serialize_field("departed", &self.departed)?;
+        strukt.serialize_field("ready", &self.ready)?;
+        strukt.serialize_field("draining", &self.draining)?;
+        strukt.serialize_field("health", &self.health)?;
         strukt.serialize_field("cfg", &self.cfg)?;
         strukt.end()
     }
@@ -779,8 +920,37 @@ mod tests {
         assert_eq!(census_group_two.pkg_incarnation, 2);
 
         let mut members = census_group_two.members();
-        assert_eq!(members.next().unwrap().member_id, "member-a");
-        assert_eq!(members.next().unwrap().member_id, "member-b");
+        let member_a = members.next().unwrap();
+        assert_eq!(member_a.member_id, "member-a");
+        assert!(!member_a.ready());
+        let member_b = members.next().unwrap();
+        assert_eq!(member_b.member_id, "member-b");
+        assert!(member_b.ready());
+    }
+
+    #[test]
+    fn census_group_keeps_the_most_recent_applied_configs() {
+        let key_cache = KeyCache::new(&*CACHE_KEY_PATH);
+        let sg = ServiceGroup::new("shield", "one", None).unwrap();
+        let mut census_group = CensusGroup::new(sg.clone(), "member-a");
+        for incarnation in 1..=12 {
+            let mut rumor = ServiceConfigRumor::new("member-b",
+                                                    sg.clone(),
+                                                    format!("port = {}", incarnation).into_bytes());
+            rumor.incarnation = incarnation;
+            census_group.update_from_service_config_rumor(&key_cache, &rumor);
+        }
+        // An older version arriving late isn't applied
+        let mut rumor = ServiceConfigRumor::new("member-b", sg, b"port = 0".to_vec());
+        rumor.incarnation = 5;
+        census_group.update_from_service_config_rumor(&key_cache, &rumor);
+
+        let history = census_group.config_history().collect::<Vec<_>>();
+        assert_eq!(history.len(), CensusGroup::MAX_CONFIG_HISTORY);
+        assert_eq!(history[0].incarnation, 12);
+        assert_eq!(history[0].payload, b"port = 12".to_vec());
+        assert!(!history[0].encrypted);
+        assert_eq!(history[9].incarnation, 3);
     }
 
     #[test]
@@ -818,6 +988,7 @@ mod tests {
                                                 sys_info.clone(),
                                                 None);
         service_two.pkg_incarnation = 1;
+        service_two.ready = true;
         let mut service_three = ServiceRumor::new("member-a".to_string(),
                                                   &pg_id,
                                                   sg_two.clone(),
@@ -889,6 +1060,9 @@ mod tests {
                        suspect: health == Health::Suspect,
                        confirmed: health == Health::Confirmed,
                        departed: health == Health::Departed,
+                       ready: false,
+                       draining: false,
+                       health: None,
                        cfg: toml::value::Table::new() }
     }
 
@@ -915,6 +1089,32 @@ mod tests {
         assert!(active_members.next().is_none());
     }
 
+    #[test]
+    fn serving_members_leaves_out_draining_members() {
+        let mut draining = test_census_member("draining-one", Health::Alive);
+        draining.draining = true;
+        let population = vec![test_census_member("live-one", Health::Alive),
+                              draining,
+                              test_census_member("suspect-one", Health::Suspect),
+                              test_census_member("confirmed-one", Health::Confirmed),];
+
+        let sg: ServiceGroup =
+            "test-service.default".parse()
+                                  .expect("This should be a valid service group");
+
+        let mut census_group = CensusGroup::new(sg, "live-one");
+        for member in population {
+            census_group.population
+                        .insert(member.member_id.clone(), member);
+        }
+
+        assert_eq!(census_group.active_members().count(), 3);
+        let mut serving_members = census_group.serving_members();
+        assert_eq!(serving_members.next().unwrap().member_id, "live-one");
+        assert_eq!(serving_members.next().unwrap().member_id, "suspect-one");
+        assert!(serving_members.next().is_none());
+    }
+
     fn assert_eq_member_ids(cm: Option<&CensusMember>, id: Option<&str>) {
         assert_eq!(cm.map(|cm| cm.member_id.as_str()), id);
     }
@@ -990,4 +1190,68 @@ mod tests {
         assert_eq_member_ids(CensusGroup::previous_peer_impl(members.iter(), &me),
                              Some("left_of_me_with_wrapping"));
     }
+
+    fn member_ids<'a>(members: &[&'a CensusMember]) -> Vec<&'a str> {
+        members.iter().map(|cm| cm.member_id.as_str()).collect()
+    }
+
+    #[test]
+    fn update_order_starts_at_the_update_leader() {
+        let leader = test_census_member("b", Health::Alive);
+        let members = vec![test_census_member("a", Health::Alive),
+                           leader.clone(),
+                           test_census_member("c", Health::Confirmed),
+                           test_census_member("d", Health::Alive),];
+        let order = CensusGroup::update_order_impl(members.iter(), &leader);
+        assert_eq!(member_ids(&order), vec!["b", "d", "a"]);
+    }
+
+    #[test]
+    fn update_order_with_dead_update_leader() {
+        let leader = test_census_member("b", Health::Confirmed);
+        let members = vec![test_census_member("a", Health::Alive), leader.clone()];
+        assert!(CensusGroup::update_order_impl(members.iter(), &leader).is_empty());
+    }
+
+    #[test]
+    fn update_canaries_wraparound() {
+        let leader = test_census_member("c", Health::Alive);
+        let members = vec![test_census_member("a", Health::Alive),
+                           test_census_member("b", Health::Alive),
+                           leader.clone(),];
+        let order = CensusGroup::update_order_impl(members.iter(), &leader);
+        let canaries = CensusGroup::update_canaries_impl(order, CanarySize::Percent(50));
+        assert_eq!(member_ids(&canaries), vec!["c", "a"]);
+    }
+
+    #[test]
+    fn update_canaries_never_exceed_the_group() {
+        let leader = test_census_member("a", Health::Alive);
+        let members = vec![leader.clone(), test_census_member("b", Health::Alive)];
+        let order = CensusGroup::update_order_impl(members.iter(), &leader);
+        let canaries = CensusGroup::update_canaries_impl(order, CanarySize::Count(5));
+        assert_eq!(member_ids(&canaries), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn previous_update_batch() {
+        let members =
+            ["leader", "a", "b", "c", "d", "e"].iter()
+                                               .map(|id| test_census_member(id, Health::Alive))
+                                               .collect::<Vec<_>>();
+        let order = members.iter().collect::<Vec<_>>();
+        let previous_batch = |me: usize, batch_size| {
+            let batch = CensusGroup::previous_update_batch_impl(&order, &members[me], batch_size);
+            member_ids(&batch)
+        };
+
+        assert!(previous_batch(0, 2).is_empty());
+        assert_eq!(previous_batch(1, 2), vec!["leader"]);
+        assert_eq!(previous_batch(2, 2), vec!["leader"]);
+        assert_eq!(previous_batch(3, 2), vec!["a", "b"]);
+        assert_eq!(previous_batch(4, 2), vec!["a", "b"]);
+        assert_eq!(previous_batch(5, 2), vec!["c", "d"]);
+        // One at a time is the same as waiting for the previous peer
+        assert_eq!(previous_batch(3, 1), vec!["b"]);
+    }
 }
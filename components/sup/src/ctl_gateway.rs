@@ -96,6 +96,10 @@ impl CtlRequest {
         self.send_msg(msg, true);
     }
 
+    /// Returns true once the client has gone away, or the request
+    /// can't be replied to at all.
+    pub fn is_closed(&self) -> bool { self.tx.as_ref().map_or(true, |tx| tx.is_closed()) }
+
     /// Returns true if the request is transactional and false if not.
     pub fn transactional(&self) -> bool { self.transaction.is_some() && self.tx.is_some() }
 
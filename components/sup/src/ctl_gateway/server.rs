@@ -314,6 +314,11 @@ impl SrvHandler {
             "SvcFilePut" => util::to_command(msg, ctl_sender, commands::service_file_put),
             "SvcSetCfg" => util::to_command(msg, ctl_sender, commands::service_cfg_set),
             "SvcValidateCfg" => util::to_command(msg, ctl_sender, commands::service_cfg_validate),
+            "SvcDryRunCfg" => util::to_command(msg, ctl_sender, commands::service_cfg_dry_run_msr),
+            "SvcConfigHistory" => util::to_command(msg, ctl_sender, commands::service_cfg_history),
+            "SvcConfigRollback" => {
+                util::to_command(msg, ctl_sender, commands::service_cfg_rollback)
+            }
             "SvcLoad" => {
                 // This arm doesn't use a `util` module helper because
                 // it's currently the only thing that behaves like
@@ -339,6 +344,14 @@ impl SrvHandler {
             "SvcUnload" => util::to_supervisor_command(msg, ctl_sender, commands::service_unload),
             "SvcStart" => util::to_command(msg, ctl_sender, commands::service_start),
             "SvcStop" => util::to_supervisor_command(msg, ctl_sender, commands::service_stop),
+            "SvcReset" => util::to_supervisor_command(msg, ctl_sender, commands::service_reset),
+            "SvcDrain" => util::to_supervisor_command(msg, ctl_sender, commands::service_drain),
+            "SvcUndrain" => util::to_supervisor_command(msg, ctl_sender, commands::service_undrain),
+            "SvcLogs" => util::to_command(msg, ctl_sender, commands::service_logs),
+            "SvcHooks" => util::to_command(msg, ctl_sender, commands::service_hooks_gsr),
+            "SvcHealthHistory" => {
+                util::to_command(msg, ctl_sender, commands::service_health_history_gsr)
+            }
             "SvcStatus" => util::to_command(msg, ctl_sender, commands::service_status_gsr),
             "SupDepart" => util::to_command(msg, ctl_sender, commands::supervisor_depart),
             "SupRestart" => util::to_command(msg, ctl_sender, commands::supervisor_restart),
@@ -413,7 +426,29 @@ impl Future for SrvHandler {
                     }
                 }
                 SrvHandlerState::Sending => {
-                    match futures::ready!(self.ctl_receiver.poll_next_unpin(cx)) {
+                    let reply = match self.ctl_receiver.poll_next_unpin(cx) {
+                        Poll::Ready(reply) => reply,
+                        Poll::Pending => {
+                            // Replies can stream for as long as the
+                            // client listens, as when following a log,
+                            // so watch for it going away in between.
+                            match ready!(self.io.poll_next_unpin(cx)) {
+                                None => {
+                                    debug!("Client went away while awaiting replies");
+                                    break;
+                                }
+                                Some(Ok(msg)) => {
+                                    warn!("Ignoring {} message received while replying",
+                                          msg.message_id());
+                                    continue;
+                                }
+                                Some(Err(err)) => {
+                                    return Poll::Ready(Err(HandlerError::from(err)));
+                                }
+                            }
+                        }
+                    };
+                    match reply {
                         Some(msg) => {
                             trace!("MgrSender -> SrvHandler, {:?}", msg);
                             if msg.is_complete() {
@@ -581,3 +616,30 @@ impl CtlGatewayServer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpStream;
+
+    #[tokio::test]
+    async fn handler_ends_when_the_client_goes_away_while_awaiting_replies() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).await
+                                                                       .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        let (mgr_sender, _mgr_receiver) = mpsc::unbounded();
+        let mut handler = SrvHandler::new(SrvCodec::new().framed(TcpOrTlsStream::new(server)),
+                                          mgr_sender);
+        handler.state = SrvHandlerState::Sending;
+        let ctl_sender = handler.ctl_sender.clone();
+
+        drop(client);
+        time::timeout(Duration::from_secs(5), handler).await
+                                                      .expect("Handler still running")
+                                                      .unwrap();
+        // Anything still replying, such as a task following a log,
+        // now sees the request closed.
+        assert!(ctl_sender.is_closed());
+    }
+}
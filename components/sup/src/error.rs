@@ -51,12 +51,15 @@ pub enum Error {
     InvalidBinds(Vec<String>),
     InvalidCertFile(PathBuf),
     InvalidHealthCheckResult(i32),
+    InvalidHookTimeouts(Vec<String>),
     InvalidKeyFile(PathBuf),
     InvalidKeyParameter(String),
+    InvalidListenPorts(Vec<u16>),
     InvalidPidFile,
     InvalidTopology(String),
     InvalidUpdateStrategy(String),
     Io(io::Error),
+    JobLeaderOnlyWithoutLeader,
     TaskJoin(JoinError),
     LauncherIPCCommand(habitat_launcher_client::IPCCommandError),
     LauncherTryIPCCommand(habitat_launcher_client::TryIPCCommandError),
@@ -79,7 +82,7 @@ pub enum Error {
     RecvError(mpsc::RecvError),
     RecvTimeoutError(mpsc::RecvTimeoutError),
     ServiceDeserializationError(serde_json::Error),
-    ServiceNotLoaded(package::PackageIdent),
+    ServiceNotLoaded(String),
     ServiceSerializationError(serde_json::Error),
     ServiceSpecFileIO(PathBuf, io::Error),
     ServiceSpecParse(toml::de::Error),
@@ -182,10 +185,26 @@ impl fmt::Display for Error {
             Error::InvalidKeyParameter(ref e) => {
                 format!("Invalid parameter for key generation: {:?}", e)
             }
+            Error::InvalidHookTimeouts(ref timeouts) => {
+                format!("Invalid hook timeout(s) {}, only the {} hooks can be given a timeout of \
+                         at least a second",
+                        timeouts.join(", "),
+                        package::install::TIMED_HOOKS.join(", "))
+            }
+            Error::InvalidListenPorts(ref ports) => {
+                format!("Listen port(s) not exposed by the package, {}",
+                        ports.iter()
+                             .map(ToString::to_string)
+                             .collect::<Vec<_>>()
+                             .join(", "))
+            }
             Error::InvalidPidFile => "Invalid child process PID file".to_string(),
             Error::InvalidTopology(ref t) => format!("Invalid topology: {}", t),
             Error::InvalidUpdateStrategy(ref s) => format!("Invalid update strategy: {}", s),
             Error::Io(ref err) => err.to_string(),
+            Error::JobLeaderOnlyWithoutLeader => {
+                "A job can only run on the leader with the leader topology".to_string()
+            }
             Error::TaskJoin(ref err) => err.to_string(),
             Error::LauncherIPCCommand(err) => {
                 let mut chain: Vec<String> = vec![format!("{}", err)];
@@ -238,7 +257,7 @@ impl fmt::Display for Error {
             Error::ServiceDeserializationError(ref e) => {
                 format!("Can't deserialize service status: {}", e)
             }
-            Error::ServiceNotLoaded(ref ident) => format!("Service {} not loaded", ident),
+            Error::ServiceNotLoaded(ref service) => format!("Service {} not loaded", service),
             Error::ServiceSerializationError(ref e) => {
                 format!("Can't serialize service to file: {}", e)
             }
@@ -306,7 +325,11 @@ impl From<habitat_api_client::Error> for Error {
 impl From<Error> for habitat_sup_protocol::net::NetErr {
     fn from(err: Error) -> habitat_sup_protocol::net::NetErr {
         match err {
-            Error::MissingRequiredBind(_) | Error::InvalidBinds(_) => {
+            Error::MissingRequiredBind(_)
+            | Error::InvalidBinds(_)
+            | Error::InvalidHookTimeouts(_)
+            | Error::InvalidListenPorts(_)
+            | Error::JobLeaderOnlyWithoutLeader => {
                 habitat_sup_protocol::net::err(habitat_sup_protocol::net::ErrCode::InvalidPayload,
                                                err)
             }
@@ -19,6 +19,10 @@ pub(crate) use self::types::ServiceMetadata;
 use self::types::{EventMessage,
                   EventMetadata,
                   HealthCheckEvent,
+                  HealthFlappingEvent,
+                  ServiceFailedEvent,
+                  ServiceJobRunEvent,
+                  ServiceRollbackEvent,
                   ServiceStartedEvent,
                   ServiceStoppedEvent,
                   ServiceUpdateStartedEvent};
@@ -59,8 +63,16 @@ lazy_static! {
         "habitat.event.service_stopped".parse().expect("valid NATS subject");
     static ref SERVICE_UPDATE_STARTED_SUBJECT: Subject =
         "habitat.event.service_update_started".parse().expect("valid NATS subject");
+    static ref SERVICE_FAILED_SUBJECT: Subject =
+        "habitat.event.service_failed".parse().expect("valid NATS subject");
+    static ref SERVICE_ROLLBACK_SUBJECT: Subject =
+        "habitat.event.service_rollback".parse().expect("valid NATS subject");
+    static ref SERVICE_JOB_RUN_SUBJECT: Subject =
+        "habitat.event.service_job_run".parse().expect("valid NATS subject");
     static ref HEALTHCHECK_SUBJECT: Subject =
         "habitat.event.healthcheck".parse().expect("valid NATS subject");
+    static ref HEALTH_FLAPPING_SUBJECT: Subject =
+        "habitat.event.health_flapping".parse().expect("valid NATS subject");
 
     /// Reference to the event stream.
     static ref NATS_MESSAGE_STREAM: InitCell<NatsMessageStream> = InitCell::new();
@@ -130,6 +142,44 @@ pub fn service_update_started(service: &Service, update: &PackageIdent) {
     }
 }
 
+/// Send an event when a Service exhausts its restart budget and will
+/// no longer be restarted.
+pub fn service_failed(service: &Service, restart_count: usize, restart_window: Duration) {
+    if initialized() {
+        let restart_count = u32::try_from(restart_count).unwrap_or(u32::MAX);
+        let prost_window = ProstDuration::try_from(restart_window).unwrap_or_default();
+        publish(&SERVICE_FAILED_SUBJECT,
+                ServiceFailedEvent { event_metadata: None,
+                                     service_metadata: Some(service.to_service_metadata()),
+                                     restart_count,
+                                     restart_window: Some(prost_window) });
+    }
+}
+
+/// Send an event when a Service is rolled back from a release that
+/// failed after it was updated to it.
+pub fn service_rollback(service: &Service, failed: &PackageIdent, rollback_to: &PackageIdent) {
+    if initialized() {
+        publish(&SERVICE_ROLLBACK_SUBJECT,
+                ServiceRollbackEvent { event_metadata:         None,
+                                       service_metadata:       Some(service.to_service_metadata()),
+                                       failed_package_ident:   failed.to_string(),
+                                       rollback_package_ident: rollback_to.to_string(), });
+    }
+}
+
+/// Send an event when a run of a job Service finishes.
+pub fn service_job_run(service: &Service, duration: Duration, exit_status: Option<i32>) {
+    if initialized() {
+        let prost_duration = ProstDuration::try_from(duration).unwrap_or_default();
+        publish(&SERVICE_JOB_RUN_SUBJECT,
+                ServiceJobRunEvent { event_metadata: None,
+                                     service_metadata: Some(service.to_service_metadata()),
+                                     duration: Some(prost_duration),
+                                     exit_status });
+    }
+}
+
 // Takes metadata directly, rather than a `&Service` like other event
 // functions, because of how the asynchronous health checking
 // currently works. Revisit when async/await + Pin is all stabilized.
@@ -164,6 +214,24 @@ pub fn health_check(metadata: ServiceMetadata,
     }
 }
 
+/// Send an event when a Service's health starts or stops flapping.
+// Takes metadata directly for the same reason as `health_check`.
+pub fn health_flapping(metadata: ServiceMetadata,
+                       flapping: bool,
+                       transitions: usize,
+                       window: Duration) {
+    if initialized() {
+        let transitions = u32::try_from(transitions).unwrap_or(u32::MAX);
+        let prost_window = ProstDuration::try_from(window).unwrap_or_default();
+        publish(&HEALTH_FLAPPING_SUBJECT,
+                HealthFlappingEvent { event_metadata: None,
+                                      service_metadata: Some(metadata),
+                                      flapping,
+                                      transitions,
+                                      window: Some(prost_window) });
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////
 
 /// A collection of data that will be present in all events. Rather
@@ -51,6 +51,7 @@ impl Service {
             }
             DomainUpdateStrategy::AtOnce => UpdateStrategy::AtOnce,
             DomainUpdateStrategy::Rolling => UpdateStrategy::Rolling,
+            DomainUpdateStrategy::Canary => UpdateStrategy::Canary,
         };
 
         Some(UpdateConfig { strategy: strategy.into(),
@@ -104,4 +105,8 @@ macro_rules! event_msg_impl {
 event_msg_impl!(ServiceStartedEvent);
 event_msg_impl!(ServiceStoppedEvent);
 event_msg_impl!(ServiceUpdateStartedEvent);
+event_msg_impl!(ServiceFailedEvent);
+event_msg_impl!(ServiceRollbackEvent);
+event_msg_impl!(ServiceJobRunEvent);
 event_msg_impl!(HealthCheckEvent);
+event_msg_impl!(HealthFlappingEvent);
@@ -1,5 +1,6 @@
 use crate::manager::{self,
                      service::{HealthCheckHook,
+                               HealthCheckRecord,
                                HealthCheckResult}};
 use actix_rt::System;
 use actix_web::{body::BoxBody,
@@ -57,6 +58,10 @@ pub const HTTP_THREAD_COUNT: usize = 2;
 /// Default listening port for the HTTPGateway listener.
 pub const DEFAULT_PORT: u16 = 9631;
 
+/// The status the health endpoints report for a service whose health is
+/// flapping, in place of its latest health check result.
+const FLAPPING_STATUS: &str = "FLAPPING";
+
 lazy_static! {
     static ref HTTP_GATEWAY_REQUESTS: CounterVec =
         register_counter_vec!("hab_sup_http_gateway_requests_total",
@@ -89,6 +94,12 @@ struct HealthCheckBody {
     stderr: String,
 }
 
+#[derive(Serialize)]
+struct HealthHistoryBody {
+    flapping: bool,
+    checks:   Vec<HealthCheckRecord>,
+}
+
 #[allow(clippy::from_over_into)]
 impl Into<StatusCode> for HealthCheckResult {
     fn into(self) -> StatusCode {
@@ -233,12 +244,16 @@ impl Services {
                   web::get().to(config_without_org_gsr))
            .route("/services/{svc}/{group}/health",
                   web::get().to(health_without_org_gsr))
+           .route("/services/{svc}/{group}/health/history",
+                  web::get().to(health_history_without_org_gsr))
            .route("/services/{svc}/{group}/{org}",
                   web::get().to(service_with_org_gsr))
            .route("/services/{svc}/{group}/{org}/config",
                   web::get().to(config_with_org_gsr))
            .route("/services/{svc}/{group}/{org}/health",
-                  web::get().to(health_with_org_gsr));
+                  web::get().to(health_with_org_gsr))
+           .route("/services/{svc}/{group}/{org}/health/history",
+                  web::get().to(health_history_with_org_gsr));
     }
 }
 
@@ -427,20 +442,30 @@ fn health_gsr(svc: String, group: String, org: Option<&str>, state: &AppState) -
         Err(_) => return HttpResponse::BadRequest().finish(),
     };
 
-    let service_health_check = state.gateway_state
-                                    .lock_gsr()
-                                    .services_data()
-                                    .iter()
-                                    .find(|service| service.service_group == service_group)
-                                    .map(|service| service.health_check);
+    let service_health_check = {
+        let gateway_state = state.gateway_state.lock_gsr();
+        let flapping = gateway_state.service_histories()
+                                    .get(&service_group)
+                                    .map_or(false, |histories| histories.health.is_flapping());
+        gateway_state.services_data()
+                     .iter()
+                     .find(|service| service.service_group == service_group)
+                     .map(|service| (service.health_check, flapping))
+    };
 
-    if let Some(health_check) = service_health_check {
+    if let Some((health_check, flapping)) = service_health_check {
         let mut body = HealthCheckBody::default();
         let stdout_path = hooks::stdout_log_path::<HealthCheckHook>(service_group.service());
         let stderr_path = hooks::stderr_log_path::<HealthCheckHook>(service_group.service());
-        let http_status: StatusCode = health_check.into();
+        // A flapping health can't be trusted, whatever the latest result was
+        let http_status = if flapping {
+            body.status = FLAPPING_STATUS.to_string();
+            StatusCode::SERVICE_UNAVAILABLE
+        } else {
+            body.status = health_check.to_string();
+            health_check.into()
+        };
 
-        body.status = health_check.to_string();
         if let Ok(mut file) = File::open(stdout_path) {
             let _ = file.read_to_string(&mut body.stdout);
         }
@@ -456,6 +481,51 @@ fn health_gsr(svc: String, group: String, org: Option<&str>, state: &AppState) -
     }
 }
 
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn health_history_with_org_gsr(path: Path<(String, String, String)>,
+                                     state: Data<AppState>)
+                                     -> HttpResponse {
+    let (svc, group, org) = path.into_inner();
+    health_history_gsr(svc, group, Some(&org), &state)
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+#[allow(clippy::needless_pass_by_value)]
+async fn health_history_without_org_gsr(path: Path<(String, String)>,
+                                        state: Data<AppState>)
+                                        -> HttpResponse {
+    let (svc, group) = path.into_inner();
+    health_history_gsr(svc, group, None, &state)
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+fn health_history_gsr(svc: String,
+                      group: String,
+                      org: Option<&str>,
+                      state: &AppState)
+                      -> HttpResponse {
+    let service_group = match ServiceGroup::new(svc, group, org) {
+        Ok(sg) => sg,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
+
+    match state.gateway_state
+               .lock_gsr()
+               .service_histories()
+               .get(&service_group)
+    {
+        Some(histories) => {
+            HttpResponse::Ok().json(HealthHistoryBody { flapping: histories.health.is_flapping(),
+                                                        checks:   histories.health.checks(), })
+        }
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
 /// # Locking (see locking.md)
 /// * `GatewayState::inner` (read)
 #[allow(clippy::needless_pass_by_value)]
@@ -285,12 +285,15 @@ async fn split_apart_sup_run(sup_run: SupRun,
     let cfg =
         ManagerConfig { auto_update: sup_run.auto_update,
                         auto_update_period: sup_run.auto_update_period.into(),
+                        auto_update_windows: sup_run.auto_update_window,
                         service_update_period: sup_run.service_update_period.into(),
                         service_restart_config:
                             ServiceRestartConfig::new(sup_run.service_min_backoff_period.into(),
                                                       sup_run.service_max_backoff_period.into(),
                                                       sup_run.service_restart_cooldown_period
-                                                             .into()),
+                                                             .into(),
+                                                      sup_run.service_max_restarts,
+                                                      sup_run.service_restart_window.into()),
                         custom_state_path: None, // remove entirely?
                         key_cache,
                         update_url: bldr_url.clone(),
@@ -728,6 +731,7 @@ gpoVMSncu2jMIDZX63IkQII=
             let config = config_from_cmd_str("hab-sup run");
             assert_eq!(ManagerConfig { auto_update:                false,
                                        auto_update_period:         Duration::from_secs(60),
+                                       auto_update_windows:        Vec::new(),
                                        service_update_period:      Duration::from_secs(60),
                                        service_restart_config:     ServiceRestartConfig::default(),
                                        custom_state_path:          None,
@@ -802,6 +806,7 @@ gpoVMSncu2jMIDZX63IkQII=
             let config = config_from_cmd_str(&args);
             assert_eq!(ManagerConfig { auto_update: true,
                                        auto_update_period: Duration::from_secs(90),
+                                       auto_update_windows: Vec::new(),
                                        service_update_period: Duration::from_secs(30),
                                        service_restart_config: ServiceRestartConfig::default(),
                                        custom_state_path: None,
@@ -844,6 +849,7 @@ gpoVMSncu2jMIDZX63IkQII=
             let config = config_from_cmd_str(args);
             assert_eq!(ManagerConfig { auto_update:                false,
                                        auto_update_period:         Duration::from_secs(60),
+                                       auto_update_windows:        Vec::new(),
                                        service_update_period:      Duration::from_secs(60),
                                        service_restart_config:     ServiceRestartConfig::default(),
                                        custom_state_path:          None,
@@ -883,6 +889,7 @@ gpoVMSncu2jMIDZX63IkQII=
             let config = config_from_cmd_str(args);
             assert_eq!(ManagerConfig { auto_update:                false,
                                        auto_update_period:         Duration::from_secs(60),
+                                       auto_update_windows:        Vec::new(),
                                        service_update_period:      Duration::from_secs(60),
                                        service_restart_config:     ServiceRestartConfig::default(),
                                        custom_state_path:          None,
@@ -956,6 +963,7 @@ gpoVMSncu2jMIDZX63IkQII=
                 ManagerConfig {
                     auto_update: false,
                     auto_update_period: Duration::from_secs(60),
+                    auto_update_windows: Vec::new(),
                     service_update_period: Duration::from_secs(60),
                     service_restart_config: ServiceRestartConfig::default(),
                     custom_state_path: None,
@@ -1037,7 +1045,22 @@ gpoVMSncu2jMIDZX63IkQII=
                                                      Some(health_check_interval),
                                                  shutdown_timeout:       Some(12),
                                                  update_condition:
-                                                     Some(UpdateCondition::TrackChannel.into()), },
+                                                     Some(UpdateCondition::TrackChannel.into()),
+                                                 dependencies:           None,
+                                                 resource_limits:        None,
+                                                 process_controls:       None,
+                                                 liveness:               None,
+                                                 canary:                 None,
+                                                 rollout:                None,
+                                                 update_windows:         None,
+                                                 env:                    None,
+                                                 instance:               None,
+                                                 schedule:               None,
+                                                 job_leader_only:        Some(false),
+                                                 listen_ports:           None,
+                                                 reload_signal:          None,
+                                                 hook_timeouts:          None,
+                                                 health_probes:          None, },
                        service_load);
         }
 
@@ -1110,6 +1133,7 @@ ring = "tester"
 cache_key_path = "{}"
 auto_update = true
 auto_update_period = 3600
+auto_update_window = ["Sat,Sun 01:00-05:00 UTC"]
 service_update_period = 1_000
 key_file = "{}"
 cert_file = "{}"
@@ -1137,6 +1161,8 @@ sys_ip_address = "7.8.9.0"
             let config = config_from_cmd_str(&args);
             assert_eq!(ManagerConfig { auto_update: true,
                                        auto_update_period: Duration::from_secs(3600),
+                                       auto_update_windows:
+                                           vec!["Sat,Sun 01:00-05:00 UTC".parse().unwrap()],
                                        service_update_period: Duration::from_secs(1_000),
                                        service_restart_config: ServiceRestartConfig::default(),
                                        custom_state_path: None,
@@ -1188,6 +1214,7 @@ sys_ip_address = "7.8.9.0"
             let config = config_from_cmd_str(&args);
             assert_eq!(ManagerConfig { auto_update:                false,
                                        auto_update_period:         Duration::from_secs(60),
+                                       auto_update_windows:        Vec::new(),
                                        service_update_period:      Duration::from_secs(60),
                                        service_restart_config:     ServiceRestartConfig::default(),
                                        custom_state_path:          None,
@@ -1236,6 +1263,7 @@ sys_ip_address = "7.8.9.0"
             let config = config_from_cmd_str(&args);
             assert_eq!(ManagerConfig { auto_update:                false,
                                        auto_update_period:         Duration::from_secs(60),
+                                       auto_update_windows:        Vec::new(),
                                        service_update_period:      Duration::from_secs(60),
                                        service_restart_config:     ServiceRestartConfig::default(),
                                        custom_state_path:          None,
@@ -1289,6 +1317,7 @@ sys_ip_address = "7.8.9.0"
             let config = config_from_cmd_str(&args);
             assert_eq!(ManagerConfig { auto_update: false,
                                        auto_update_period: Duration::from_secs(60),
+                                       auto_update_windows: Vec::new(),
                                        service_update_period: Duration::from_secs(60),
                                        service_restart_config: ServiceRestartConfig::default(),
                                        custom_state_path: None,
@@ -1398,6 +1427,7 @@ event_stream_server_certificate = "{}"
                 ManagerConfig {
                     auto_update: false,
                     auto_update_period: Duration::from_secs(60),
+                    auto_update_windows: Vec::new(),
                     service_update_period: Duration::from_secs(60),
                     service_restart_config: ServiceRestartConfig::default(),
                     custom_state_path: None,
@@ -1497,7 +1527,22 @@ pkg_ident_or_artifact = "core/redis"
                                                      Some(health_check_interval),
                                                  shutdown_timeout:       Some(12),
                                                  update_condition:
-                                                     Some(UpdateCondition::TrackChannel.into()), },
+                                                     Some(UpdateCondition::TrackChannel.into()),
+                                                 dependencies:           None,
+                                                 resource_limits:        None,
+                                                 process_controls:       None,
+                                                 liveness:               None,
+                                                 canary:                 None,
+                                                 rollout:                None,
+                                                 update_windows:         None,
+                                                 env:                    None,
+                                                 instance:               None,
+                                                 schedule:               None,
+                                                 job_leader_only:        Some(false),
+                                                 listen_ports:           None,
+                                                 reload_signal:          None,
+                                                 hook_timeouts:          None,
+                                                 health_probes:          None, },
                        service_load);
         }
 
@@ -1587,6 +1632,7 @@ organization = "MY_ORG_FROM_SECOND_CONFG"
             let config = config_from_cmd_str(&args);
             assert_eq!(ManagerConfig { auto_update:                false,
                                        auto_update_period:         Duration::from_secs(60),
+                                       auto_update_windows:        Vec::new(),
                                        service_update_period:      Duration::from_secs(60),
                                        service_restart_config:     ServiceRestartConfig::default(),
                                        custom_state_path:          None,
@@ -1647,6 +1693,22 @@ organization = "MY_ORG_FROM_SECOND_CONFG"
             let svc_load = service_load_from_cmd_str(args);
             assert_eq!(i32::from(UpdateStrategy::Rolling),
                        svc_load.update_strategy.unwrap());
+
+            let args = "hab-sup run --strategy canary --canary-size 10% --canary-bake-period 600 \
+                        core/redis";
+            let svc_load = service_load_from_cmd_str(args);
+            assert_eq!(i32::from(UpdateStrategy::Canary),
+                       svc_load.update_strategy.unwrap());
+            let canary = svc_load.canary.unwrap();
+            assert_eq!(Some("10%".to_string()), canary.size);
+            assert_eq!(Some(600), canary.bake_period);
+
+            let args = "hab-sup run --strategy rolling --update-batch-size 5 \
+                        --update-health-timeout 900 core/redis";
+            let svc_load = service_load_from_cmd_str(args);
+            let rollout = svc_load.rollout.unwrap();
+            assert_eq!(Some(5), rollout.batch_size);
+            assert_eq!(Some(900), rollout.health_timeout);
         }
     }
 }
@@ -20,8 +20,10 @@ use self::{action::{ShutdownInput,
                             ServiceOperation},
                      ConfigRendering,
                      DesiredState,
+                     HealthCheckResult,
                      PersistentServiceWrapper,
                      Service,
+                     ServiceHistories,
                      ServiceQueryModel,
                      ServiceRunState,
                      ServiceSpec,
@@ -50,8 +52,7 @@ use derivative::Derivative;
 use futures::{channel::{mpsc as fut_mpsc,
                         oneshot},
               future,
-              prelude::*,
-              stream::FuturesUnordered};
+              prelude::*};
 use habitat_butterfly::{member::Member,
                         server::{timing::Timing,
                                  ServerProxy,
@@ -76,7 +77,9 @@ use habitat_core::{crypto::keys::{KeyCache,
                    package::{Identifiable,
                              PackageIdent,
                              PackageInstall},
-                   service::ServiceGroup,
+                   service::{self,
+                             MaintenanceWindow,
+                             ServiceGroup},
                    util::ToI64,
                    ChannelIdent};
 use habitat_launcher_client::{LauncherCli,
@@ -110,8 +113,7 @@ use std::{collections::{HashMap,
           io::{BufReader,
                Read,
                Write},
-          iter::{FromIterator,
-                 IntoIterator},
+          iter::IntoIterator,
           net::{IpAddr,
                 SocketAddr},
           path::{Path,
@@ -271,16 +273,25 @@ pub struct ServiceRestartConfig {
     /// know if a service started successfully other than waiting for some time and checking
     /// that it does not go down.
     pub cooldown_period:    Duration,
+    /// The number of restarts allowed within `restart_window` before the service is considered
+    /// to be crash looping and is moved to the failed state. Services are restarted forever if
+    /// this is not set.
+    pub max_restarts:       Option<u32>,
+    pub restart_window:     Duration,
 }
 
 impl ServiceRestartConfig {
     pub fn new(min_backoff_period: Duration,
                max_backoff_period: Duration,
-               restart_cooldown_period: Duration)
+               restart_cooldown_period: Duration,
+               max_restarts: Option<u32>,
+               restart_window: Duration)
                -> ServiceRestartConfig {
         ServiceRestartConfig { min_backoff_period,
                                max_backoff_period,
-                               cooldown_period: restart_cooldown_period }
+                               cooldown_period: restart_cooldown_period,
+                               max_restarts,
+                               restart_window }
     }
 }
 
@@ -288,7 +299,9 @@ impl Default for ServiceRestartConfig {
     fn default() -> Self {
         Self { min_backoff_period: Default::default(),
                max_backoff_period: Default::default(),
-               cooldown_period:    Duration::from_secs(300), }
+               cooldown_period:    Duration::from_secs(300),
+               max_restarts:       None,
+               restart_window:     Duration::from_secs(600), }
     }
 }
 
@@ -297,6 +310,7 @@ impl Default for ServiceRestartConfig {
 pub struct ManagerConfig {
     pub auto_update:                bool,
     pub auto_update_period:         Duration,
+    pub auto_update_windows:        Vec<MaintenanceWindow>,
     pub service_update_period:      Duration,
     pub service_restart_config:     ServiceRestartConfig,
     pub custom_state_path:          Option<PathBuf>,
@@ -338,19 +352,23 @@ impl ManagerConfig {
         habitat_sup_protocol::sup_root(self.custom_state_path.as_ref())
     }
 
-    fn spec_path_for(&self, ident: &PackageIdent) -> PathBuf {
+    fn spec_path_for(&self, service_name: &str) -> PathBuf {
         self.sup_root()
             .join("specs")
-            .join(ServiceSpec::ident_file(ident))
+            .join(ServiceSpec::service_file(service_name))
     }
 
     pub fn save_spec_for(&self, spec: &ServiceSpec) -> Result<()> {
-        spec.to_file(self.spec_path_for(&spec.ident))
+        spec.to_file(self.spec_path_for(&spec.service_name()))
     }
 
-    /// Given a `PackageIdent`, return current spec if it exists.
-    pub fn spec_for_ident(&self, ident: &PackageIdent) -> Option<ServiceSpec> {
-        let spec_file = self.spec_path_for(ident);
+    /// Given a `PackageIdent` and an instance name, return current spec if it exists. The
+    /// default instance is used if `instance` is `None`.
+    pub fn spec_for_ident(&self,
+                          ident: &PackageIdent,
+                          instance: Option<&str>)
+                          -> Option<ServiceSpec> {
+        let spec_file = self.spec_path_for(&service::service_name(&ident.name, instance));
 
         // JC: This mimics the logic from when we had composites.  But
         // should we check for Err ?
@@ -431,6 +449,7 @@ pub struct ManagerState {
     cfg:            ManagerConfig,
     services:       Arc<sync::ManagerServices>,
     gateway_state:  Arc<sync::GatewayState>,
+    census_ring:    Arc<RwLock<CensusRing>>,
     should_restart: AtomicBool,
 }
 
@@ -450,6 +469,10 @@ pub(crate) mod sync {
         pub fn census_data(&self) -> &str { &self.0.census_data }
 
         pub fn services_data(&self) -> &[ServiceQueryModel] { self.0.services_data.as_slice() }
+
+        pub fn service_histories(&self) -> &HashMap<ServiceGroup, ServiceHistories> {
+            &self.0.service_histories
+        }
     }
 
     pub struct GatewayStateWriteGuard<'a>(WriteGuard<'a, GatewayStateInner>);
@@ -468,6 +491,10 @@ pub(crate) mod sync {
         pub fn get_services_data_mut(&mut self) -> &mut Vec<ServiceQueryModel> {
             self.0.services_data.as_mut()
         }
+
+        pub fn set_service_histories(&mut self, new_data: HashMap<ServiceGroup, ServiceHistories>) {
+            self.0.service_histories = new_data
+        }
     }
 
     /// All the data that is ultimately served from the Supervisor's HTTP
@@ -490,27 +517,29 @@ pub(crate) mod sync {
     #[derive(Debug, Default)]
     struct GatewayStateInner {
         /// JSON returned by the /census endpoint
-        census_data:    String,
+        census_data:       String,
         /// JSON returned by the /butterfly endpoint
-        butterfly_data: String,
+        butterfly_data:    String,
         /// JSON returned by the /services endpoint
-        services_data:  Vec<ServiceQueryModel>,
+        services_data:     Vec<ServiceQueryModel>,
+        /// The health check and hook run histories of each running
+        /// service, which have endpoints of their own
+        service_histories: HashMap<ServiceGroup, ServiceHistories>,
     }
 
-    type ManagerServicesInner = HashMap<PackageIdent, PersistentServiceWrapper>;
+    /// Services keyed by their service name (see `ServiceSpec::service_name`)
+    type ManagerServicesInner = HashMap<String, PersistentServiceWrapper>;
 
     pub struct ManagerServicesReadGuard<'a>(ReadGuard<'a, ManagerServicesInner>);
 
     impl<'a> ManagerServicesReadGuard<'a> {
         fn new(lock: &'a Lock<ManagerServicesInner>) -> Self { Self(lock.read()) }
 
-        pub fn iter(&self) -> impl Iterator<Item = (&PackageIdent, &PersistentServiceWrapper)> {
+        pub fn iter(&self) -> impl Iterator<Item = (&String, &PersistentServiceWrapper)> {
             self.0.iter()
         }
 
-        pub fn get(&self, key: &PackageIdent) -> Option<&PersistentServiceWrapper> {
-            self.0.get(key)
-        }
+        pub fn get(&self, key: &str) -> Option<&PersistentServiceWrapper> { self.0.get(key) }
 
         pub fn running_services(&self) -> impl Iterator<Item = &Service> {
             self.0
@@ -525,12 +554,11 @@ pub(crate) mod sync {
         fn new(lock: &'a Lock<ManagerServicesInner>) -> Self { Self(lock.write()) }
 
         pub fn iter_mut(&mut self)
-                        -> impl Iterator<Item = (&PackageIdent, &mut PersistentServiceWrapper)>
-        {
+                        -> impl Iterator<Item = (&String, &mut PersistentServiceWrapper)> {
             self.0.iter_mut()
         }
 
-        pub fn insert(&mut self, key: PackageIdent, value: PersistentServiceWrapper) {
+        pub fn insert(&mut self, key: String, value: PersistentServiceWrapper) {
             if let Some(state) = self.0.get_mut(&key) {
                 state.take_service(value);
                 state.start();
@@ -539,11 +567,11 @@ pub(crate) mod sync {
             }
         }
 
-        pub fn remove(&mut self, key: &PackageIdent) -> Option<PersistentServiceWrapper> {
+        pub fn remove(&mut self, key: &str) -> Option<PersistentServiceWrapper> {
             self.0.remove(key)
         }
 
-        pub fn get_mut(&mut self, key: &PackageIdent) -> Option<&mut PersistentServiceWrapper> {
+        pub fn get_mut(&mut self, key: &str) -> Option<&mut PersistentServiceWrapper> {
             self.0.get_mut(key)
         }
 
@@ -629,9 +657,9 @@ pub struct Manager {
     /// up, then down, then up; etc).
     ///
     /// Feel free to refactor to something different!
-    service_states:      HashMap<PackageIdent, SystemTime>,
+    service_states:      HashMap<String, SystemTime>,
 
-    /// Collects the identifiers of all services that are currently
+    /// Collects the names of all services that are currently
     /// doing something asynchronously (like shutting down, or running
     /// a lifecycle hook). We want to know which to ignore if changes
     /// in their spec files are detected while they're asynchronously
@@ -644,7 +672,15 @@ pub struct Manager {
     // that point, we might need / want to change from a HashSet to
     // something else (maybe a HashMap?) in order to cleanly manage
     // the different operations.
-    busy_services: Arc<Mutex<HashSet<PackageIdent>>>,
+    busy_services: Arc<Mutex<HashSet<String>>>,
+    /// Services whose start has been put off until the services they
+    /// depend on are up. Used to avoid repeating the same message on
+    /// every check, and to know whether a check is needed at all.
+    services_awaiting_dependencies:   HashSet<String>,
+    /// The services that were up when dependencies were last checked.
+    /// Services awaiting dependencies are only checked again once this
+    /// changes.
+    services_up_when_checked:         HashSet<PackageIdent>,
     updated_service_pkg_incarnations: Arc<Mutex<HashMap<ServiceGroup, u64>>>,
     services_need_reconciliation:     ReconciliationFlag,
 
@@ -706,7 +742,8 @@ impl Manager {
                 Some(SelfUpdater::new(&THIS_SUPERVISOR_IDENT,
                                       cfg.update_url,
                                       cfg.update_channel,
-                                      cfg.auto_update_period))
+                                      cfg.auto_update_period,
+                                      cfg.auto_update_windows))
             } else {
                 warn!("Supervisor version not fully qualified, unable to start self-updater");
                 None
@@ -765,6 +802,7 @@ impl Manager {
         Ok(Manager { state: Arc::new(ManagerState { cfg: cfg_static,
                                                     services,
                                                     gateway_state: Arc::default(),
+                                                    census_ring: Arc::clone(&census_ring),
                                                     should_restart: AtomicBool::default() }),
                      self_updater,
                      service_updater:
@@ -784,6 +822,8 @@ impl Manager {
                      sys: Arc::new(sys),
                      http_disable: cfg.http_disable,
                      busy_services: Arc::default(),
+                     services_awaiting_dependencies: HashSet::new(),
+                     services_up_when_checked: HashSet::new(),
                      updated_service_pkg_incarnations: Arc::default(),
                      services_need_reconciliation: ReconciliationFlag::new(false),
                      feature_flags: cfg.feature_flags,
@@ -890,6 +930,7 @@ impl Manager {
     /// * `ManagerServices::inner` (read)
     async fn add_service_rsw_mlw_rhw_msr(&mut self, spec: ServiceSpec) {
         let ident = spec.ident.clone();
+        let service_name = spec.service_name();
         let mut service = match Service::new(self.sys.clone(),
                                              spec,
                                              self.fs_cfg.clone(),
@@ -906,7 +947,7 @@ impl Manager {
             Err(err) => {
                 outputln!("Unable to start {}, {}", ident, err);
                 // Remove the spec file so it does not look like this service is loaded.
-                self.remove_spec_file(&ident).ok();
+                self.remove_spec_file(&service_name).ok();
                 return;
             }
         };
@@ -967,7 +1008,7 @@ impl Manager {
         self.state
             .services
             .lock_msw()
-            .insert(service.spec_ident(),
+            .insert(service_name,
                     PersistentServiceWrapper::new(service, &self.state.cfg.service_restart_config))
     }
 
@@ -1227,12 +1268,16 @@ impl Manager {
                             warn!("Tried to stop '{}', but couldn't update the spec: {:?}",
                                   service_spec.ident, err);
                         }
-                        self.stop_service_gsw_msw(&service_spec.ident, &shutdown_input);
+                        let service_name = service_spec.service_name();
+                        self.services_awaiting_dependencies.remove(&service_name);
+                        self.stop_service_gsw_msw(&service_name, &shutdown_input);
                     }
                     UnloadService { service_spec,
                                     shutdown_input, } => {
-                        self.remove_spec_file(&service_spec.ident).ok();
-                        self.stop_service_gsw_msw(&service_spec.ident, &shutdown_input);
+                        let service_name = service_spec.service_name();
+                        self.remove_spec_file(&service_name).ok();
+                        self.services_awaiting_dependencies.remove(&service_name);
+                        self.stop_service_gsw_msw(&service_name, &shutdown_input);
                     }
                     UpdateService { service_spec } => {
                         trace!("Received UpdateService action for {}", service_spec.ident);
@@ -1241,6 +1286,40 @@ impl Manager {
                                   service_spec.ident, err);
                         }
                     }
+                    ResetService { service_spec } => {
+                        let reset = self.state
+                                        .services
+                                        .lock_msw()
+                                        .get_mut(&service_spec.service_name())
+                                        .map_or(false, PersistentServiceWrapper::reset);
+                        if reset {
+                            outputln!("Resetting failed service {}", service_spec.ident);
+                            self.services_need_reconciliation.set();
+                        } else {
+                            outputln!("Not resetting {}, it has not failed", service_spec.ident);
+                        }
+                    }
+                    DrainService { service_spec,
+                                   draining, } => {
+                        let mut services = self.state.services.lock_msw();
+                        match services.get_mut(&service_spec.service_name())
+                                      .and_then(PersistentServiceWrapper::service_mut)
+                        {
+                            Some(service) if service.set_draining(draining) => {
+                                if draining {
+                                    outputln!(preamble service.service_group, "Draining");
+                                } else {
+                                    outputln!(preamble service.service_group, "Undraining");
+                                }
+                                self.gossip_latest_service_rumor_rsw_mlw_rhw(service, None);
+                            }
+                            Some(_) => {}
+                            None => {
+                                outputln!("Not changing the drain state of {}, it is not running",
+                                          service_spec.ident);
+                            }
+                        }
+                    }
                 }
             }
 
@@ -1318,6 +1397,16 @@ impl Manager {
                 }
             }
 
+            // Health checks run asynchronously, so services waiting on
+            // their dependencies need another look whenever a service
+            // comes up or goes down.
+            if !self.services_awaiting_dependencies.is_empty()
+               && self.up_services_msr() != self.services_up_when_checked
+            {
+                debug!("Services are up or down, setting reconciliation flag");
+                self.services_need_reconciliation.set()
+            }
+
             // This is really only needed until everything is running
             // in futures.
             let now = Instant::now();
@@ -1362,18 +1451,14 @@ impl Manager {
                 outputln!("Gracefully departing from butterfly network.");
                 self.butterfly.set_departed_mlw_smw_rhw();
 
-                #[allow(clippy::from_iter_instead_of_collect)]
-                let service_stop_futures =
-                    FuturesUnordered::from_iter(self.state
-                                                    .services
-                                                    .lock_msw()
-                                                    .drain_services()
-                                                    .map(|service| {
-                                                        self.stop_service_future_gsw(service, None,
-                                                                                     None)
-                                                    }));
+                let services = self.state
+                                   .services
+                                   .lock_msw()
+                                   .drain_services()
+                                   .map(|service| (service, None))
+                                   .collect();
                 // Wait while all services are stopped
-                service_stop_futures.collect::<Vec<_>>().await;
+                self.stop_services_future_gsw(services).await;
             }
         }
 
@@ -1399,16 +1484,31 @@ impl Manager {
     /// * `MemberList::entries` (read)
     /// * `RumorHeat::inner` (write)
     /// * `ManagerServices::inner` (write)
-    fn restart_services_rsw_mlr_rhw_msw(&mut self, updaters_to_register: &mut Vec<PackageIdent>) {
+    fn restart_services_rsw_mlr_rhw_msw(&mut self, updaters_to_register: &mut Vec<String>) {
         let mut service_updater = self.service_updater.lock();
 
         let mut state_services = self.state.services.lock_msw();
         let mut idents_to_restart_and_latest_desired_on_restart = Vec::new();
         for (ident, service_state) in state_services.iter_mut() {
-            // We need to use this has_update flag due to the borrow checker rules
-            let mut has_update = false;
+            // We need to use these flags due to the borrow checker rules
+            let mut has_update = None;
+            let mut rolled_back = false;
+            let failed_release = service_state.failed_release();
             if let Some(service) = service_state.service() {
-                if let Some(new_ident) = service_updater.has_update(&service.service_group) {
+                if let Some((release, previous)) = failed_release {
+                    outputln!("Rolling back {} from {} to {}, the release failed after updating",
+                              ident,
+                              release,
+                              previous);
+                    rolled_back = true;
+                    event::service_rollback(service, &release, &previous);
+                    // Keep the updater from updating to the failed release again
+                    service_updater.record_failed_release(service, &release);
+                    // Uninstalling the failed release ensures the service restarts with the
+                    // package it ran before.
+                    idents_to_restart_and_latest_desired_on_restart.push((ident.clone(),
+                                                                          Some(previous)));
+                } else if let Some(new_ident) = service_updater.has_update(&service.service_group) {
                     if service.pkg.ident.as_ref() == &new_ident.ident {
                         // Here a rolling follower got asked to update to the same version it
                         // already had This is because the leader had a
@@ -1421,11 +1521,10 @@ impl Manager {
                         self.gossip_latest_service_rumor_rsw_mlw_rhw(service,
                                                                      new_ident.incarnation);
                         service_updater.remove(&service.service_group);
-                        // ident here should just be the spec ident origin/pkg
                         updaters_to_register.push(ident.clone());
                     } else {
                         outputln!("Restarting {} with package {}", ident, new_ident.ident);
-                        has_update = true;
+                        has_update = Some(new_ident.ident.clone());
                         // stash this updated service's incarnation for later gossiping
                         if let Some(incarnation) = new_ident.incarnation {
                             self.updated_service_pkg_incarnations
@@ -1447,27 +1546,33 @@ impl Manager {
             } else {
                 trace!("Restart in progress for {}", ident);
             }
-            if has_update {
-                service_state.mark_for_restart_due_to_update(SystemTime::now());
+            if let Some(release) = has_update {
+                service_state.mark_for_restart_due_to_update(release, SystemTime::now());
+            } else if rolled_back {
+                service_state.mark_for_restart_due_to_rollback(SystemTime::now());
             }
         }
 
+        let mut services_to_stop = Vec::new();
         for (ident, latest_desired_on_restart) in idents_to_restart_and_latest_desired_on_restart {
             // unwrap is safe because we've to the write lock, and we
             // know there's a value present at this key.
             let service = state_services.get_mut(&ident)
                                         .and_then(|service_state| service_state.shutdown(true))
                                         .unwrap();
-            // TODO (CM): In the future, when service start up is
-            // future-based, we'll want to have an actual "restart"
-            // future, that queues up the start future after the stop
-            // future.
-            //
-            // Until then, we will just stop the services, and rely on the
-            // our specfile reconciliation logic to catch the fact that
-            // the service needs to be restarted. At that point, this function
-            // can be renamed; right now, it says exactly what it's doing.
-            tokio::spawn(self.stop_service_future_gsw(service, latest_desired_on_restart, None));
+            services_to_stop.push((service, latest_desired_on_restart));
+        }
+        // TODO (CM): In the future, when service start up is
+        // future-based, we'll want to have an actual "restart"
+        // future, that queues up the start future after the stop
+        // future.
+        //
+        // Until then, we will just stop the services, and rely on the
+        // our specfile reconciliation logic to catch the fact that
+        // the service needs to be restarted. At that point, this function
+        // can be renamed; right now, it says exactly what it's doing.
+        if !services_to_stop.is_empty() {
+            tokio::spawn(self.stop_services_future_gsw(services_to_stop));
         }
     }
 
@@ -1524,7 +1629,7 @@ impl Manager {
         for loaded in self.spec_dir
                           .specs()
                           .iter()
-                          .filter(|s| !active_services.contains(&s.ident))
+                          .filter(|s| !active_services.contains(&s.service_name()))
         {
             // These are loaded but not-running services. As such,
             // we'll use the Epoch as a "default" time marker that
@@ -1532,7 +1637,7 @@ impl Manager {
             //
             // TODO (CM): why do we bother tracking loaded but not
             // running services at all?
-            service_states.insert(loaded.ident.clone(), SystemTime::UNIX_EPOCH);
+            service_states.insert(loaded.service_name(), SystemTime::UNIX_EPOCH);
         }
 
         if service_states != self.service_states {
@@ -1595,9 +1700,7 @@ impl Manager {
         let mut watched_services = Vec::new();
         for spec in self.spec_dir.specs() {
             let ident = spec.ident.clone();
-            if let Some((_, svc_state)) =
-                service_map.iter().find(|(ident, _)| **ident == spec.ident)
-            {
+            if let Some(svc_state) = service_map.get(&spec.service_name()) {
                 // If the service wrapper does not contain a service we create one
                 if svc_state.service().is_none() {
                     match Service::new(self.sys.clone(),
@@ -1657,11 +1760,15 @@ impl Manager {
                        .collect();
 
         services_data.extend(watched_service_proxies);
+        let service_histories =
+            service_map.iter()
+                       .filter_map(|(_, svc_state)| svc_state.service())
+                       .map(|service| (service.service_group.clone(), service.histories()))
+                       .collect();
 
-        self.state
-            .gateway_state
-            .lock_gsw()
-            .set_services_data(services_data);
+        let mut gateway_state = self.state.gateway_state.lock_gsw();
+        gateway_state.set_services_data(services_data);
+        gateway_state.set_service_histories(service_histories);
     }
 
     /// Check if any elections need restarting.
@@ -1679,15 +1786,31 @@ impl Manager {
     /// # Locking (see locking.md)
     /// * `GatewayState::inner` (write)
     /// * `ManagerServices::inner` (write)
-    fn stop_service_gsw_msw(&mut self, ident: &PackageIdent, shutdown_input: &ShutdownInput) {
-        if let Some(mut service_state) = self.remove_service_from_state_msw(ident) {
+    fn stop_service_gsw_msw(&mut self, service_name: &str, shutdown_input: &ShutdownInput) {
+        if let Some(mut service_state) = self.remove_service_from_state_msw(service_name) {
             if let Some(service) = service_state.shutdown(false) {
+                self.release_listen_sockets(&service);
                 let future = self.stop_service_future_gsw(service, None, Some(shutdown_input));
                 tokio::spawn(future);
             }
         } else {
             warn!("Tried to stop '{}', but couldn't find it in our list of running services!",
-                  ident);
+                  service_name);
+        }
+    }
+
+    /// Have the Launcher close the listening sockets it holds for a
+    /// Service that is being stopped for good. Its process keeps its
+    /// own copies until it exits.
+    fn release_listen_sockets(&self, service: &Service) {
+        if service.spec_ref().listen_ports.is_empty() {
+            return;
+        }
+        if let Err(err) = self.launcher
+                              .release_sockets(&service.service_group.to_string())
+        {
+            warn!("Unable to release the listening sockets of {}: {}",
+                  service.service_group, err);
         }
     }
 
@@ -1710,7 +1833,7 @@ impl Manager {
         // JW TODO: Update service rumor to remove service from
         // cluster
         // TODO (CM): But only if we're not going down for a restart.
-        let ident = service.spec_ident();
+        let service_name = service.spec_ref().service_name();
         let stop_it = async move {
             service.stop_gsw(shutdown_config).await;
             event::service_stopped(&service);
@@ -1722,12 +1845,56 @@ impl Manager {
                 Self::uninstall_newer_packages(&service.spec_ident(), &latest_desired_ident).await;
             }
         };
-        Self::wrap_async_service_operation(ident,
+        Self::wrap_async_service_operation(service_name,
                                            busy_services,
                                            services_need_reconciliation,
                                            stop_it)
     }
 
+    /// Create a future for stopping several Services, honoring the
+    /// dependencies declared between them: a Service is only stopped
+    /// once every Service in `services` that depends on it has
+    /// stopped. Services that do not depend on one another are
+    /// stopped concurrently.
+    ///
+    /// As with `stop_service_future_gsw`, the Services are assumed to
+    /// have been removed from the internal list of active services
+    /// already. All of them are flagged as busy right away, so that
+    /// nothing tries to start them again while they wait their turn
+    /// to stop.
+    ///
+    /// # Locking for the returned Future (see locking.md)
+    /// * `GatewayState::inner` (write)
+    fn stop_services_future_gsw(&self,
+                                services: Vec<(Service, Option<PackageIdent>)>)
+                                -> impl Future<Output = ()> {
+        {
+            let mut busy_services = self.busy_services.lock();
+            for (service, _) in &services {
+                busy_services.insert(service.spec_ref().service_name());
+            }
+        }
+        let tiers = Self::dependency_tiers(services, |(service, _)| service.spec_ref());
+        // Dependents must stop before their dependencies, so we walk
+        // the start order backwards.
+        let tiers =
+            tiers.into_iter()
+                 .rev()
+                 .map(|tier| {
+                     tier.into_iter()
+                         .map(|(service, latest_desired_on_restart)| {
+                             self.stop_service_future_gsw(service, latest_desired_on_restart, None)
+                         })
+                         .collect::<Vec<_>>()
+                 })
+                 .collect::<Vec<_>>();
+        async move {
+            for tier in tiers {
+                future::join_all(tier).await;
+            }
+        }
+    }
+
     /// Uninstall packages that are newer than the specified ident.
     ///
     /// This can be used to guarantee that when a service restarts it starts with the desired
@@ -1751,13 +1918,13 @@ impl Manager {
         }
     }
 
-    fn remove_spec_file(&self, ident: &PackageIdent) -> std::io::Result<()> {
-        let file = self.state.cfg.spec_path_for(ident);
+    fn remove_spec_file(&self, service_name: &str) -> std::io::Result<()> {
+        let file = self.state.cfg.spec_path_for(service_name);
         let result = fs::remove_file(&file);
         if let Err(ref err) = result {
             warn!("Tried to remove spec file '{}' for '{}': {:?}",
                   file.display(),
-                  ident,
+                  service_name,
                   err);
         };
         result
@@ -1777,19 +1944,19 @@ impl Manager {
     /// As more service operations (e.g., hooks) become asynchronous,
     /// we'll need to wrap those operations in this logic to ensure
     /// consistent operation.
-    async fn wrap_async_service_operation<F>(ident: PackageIdent,
-                                             busy_services: Arc<Mutex<HashSet<PackageIdent>>>,
+    async fn wrap_async_service_operation<F>(service_name: String,
+                                             busy_services: Arc<Mutex<HashSet<String>>>,
                                              services_need_reconciliation: ReconciliationFlag,
                                              fut: F)
         where F: Future<Output = ()>
     {
         trace!("Flagging '{:?}' as busy, pending an asynchronous operation",
-               ident);
-        busy_services.lock().insert(ident.clone());
+               service_name);
+        busy_services.lock().insert(service_name.clone());
         fut.await;
         trace!("Removing 'busy' flag for '{:?}'; asynchronous operation over",
-               ident);
-        busy_services.lock().remove(&ident);
+               service_name);
+        busy_services.lock().remove(&service_name);
         services_need_reconciliation.set();
     }
 
@@ -1808,7 +1975,7 @@ impl Manager {
     /// * `GatewayState::inner` (write)
     /// * `RumorHeat::inner` (write)
     /// * `ManagerServices::inner` (write)
-    async fn maybe_spawn_service_futures_rsw_mlw_gsw_rhw_msw(&mut self) -> Vec<PackageIdent> {
+    async fn maybe_spawn_service_futures_rsw_mlw_gsw_rhw_msw(&mut self) -> Vec<String> {
         let ops = self.compute_service_operations_msr();
         self.spawn_futures_from_operations_rsw_mlw_gsw_rhw_msw(ops)
             .await
@@ -1817,9 +1984,9 @@ impl Manager {
     /// # Locking (see locking.md)
     /// * `ManagerServices::inner` (write)
     fn remove_service_from_state_msw(&mut self,
-                                     ident: &PackageIdent)
+                                     service_name: &str)
                                      -> Option<PersistentServiceWrapper> {
-        self.state.services.lock_msw().remove(ident)
+        self.state.services.lock_msw().remove(service_name)
     }
 
     /// Start, stop, or restart services to bring what's running in
@@ -1837,13 +2004,13 @@ impl Manager {
     /// * `GatewayState::inner` (write)
     /// * `RumorHeat::inner` (write)
     /// * `ManagerServices::inner` (write)
-    async fn spawn_futures_from_operations_rsw_mlw_gsw_rhw_msw<O>(&mut self,
-                                                                  ops: O)
-                                                                  -> Vec<PackageIdent>
+    async fn spawn_futures_from_operations_rsw_mlw_gsw_rhw_msw<O>(&mut self, ops: O) -> Vec<String>
         where O: IntoIterator<Item = ServiceOperation>
     {
         let mut services_started = Vec::new();
+        let mut services_to_stop = Vec::new();
         for op in ops.into_iter() {
+            let is_stop = matches!(op, ServiceOperation::Stop(_));
             match op {
                 ServiceOperation::Restart { to_stop: spec, .. } | ServiceOperation::Stop(spec) => {
                     // Yes, Stop and Restart both turn into
@@ -1857,10 +2024,14 @@ impl Manager {
                     // onto the end of the stop one for a *real*
                     // restart future.
                     if let Some(service) =
-                        self.remove_service_from_state_msw(&spec.ident)
+                        self.remove_service_from_state_msw(&spec.service_name())
                             .and_then(|mut service_state| service_state.shutdown(false))
                     {
-                        tokio::spawn(self.stop_service_future_gsw(service, None, None));
+                        // A restarting service keeps its sockets
+                        if is_stop {
+                            self.release_listen_sockets(&service);
+                        }
+                        services_to_stop.push((service, None));
                     } else {
                         // We really don't expect this to happen....
                         outputln!("Tried to remove service for {} but could not find it running, \
@@ -1869,16 +2040,32 @@ impl Manager {
                     }
                 }
                 ServiceOperation::Start(spec) => {
+                    let service_name = spec.service_name();
+                    let waiting_on = self.unready_dependencies_msr(&spec);
+                    if !waiting_on.is_empty() {
+                        if self.services_awaiting_dependencies
+                               .insert(service_name.clone())
+                        {
+                            outputln!("Waiting for {} to be up before starting {}",
+                                      waiting_on.iter()
+                                                .map(ToString::to_string)
+                                                .collect::<Vec<_>>()
+                                                .join(", "),
+                                      spec.ident);
+                        }
+                        continue;
+                    }
+                    self.services_awaiting_dependencies.remove(&service_name);
                     // We need to check if the service is already known, if yes, then is it ready to
                     // be restarted yet
                     if self.state
                            .services
                            .lock_msr()
-                           .get(&spec.ident)
+                           .get(&service_name)
                            .map_or(true, PersistentServiceWrapper::is_ready_for_restart)
                     {
-                        self.add_service_rsw_mlw_rhw_msr(spec.clone()).await;
-                        services_started.push(spec.ident.clone());
+                        self.add_service_rsw_mlw_rhw_msr(spec).await;
+                        services_started.push(service_name);
                     }
                 }
                 ServiceOperation::Update(spec, ops) => {
@@ -1886,7 +2073,7 @@ impl Manager {
                     let mut services = self.state.services.lock_msw();
                     // Relies on spec.ident not having changed, which
                     // ServiceSpec#reconcile must guarantee.
-                    if let Some(service) = services.get_mut(&spec.ident)
+                    if let Some(service) = services.get_mut(&spec.service_name())
                                                    .and_then(PersistentServiceWrapper::service_mut)
                     {
                         service.set_spec(spec);
@@ -1896,6 +2083,9 @@ impl Manager {
                                 RefreshOperation::RestartUpdater => {
                                     self.service_updater.lock().register(service);
                                 }
+                                // The new dependencies are part of the spec
+                                // we just set; nothing else to do.
+                                RefreshOperation::UpdateDependencies => {}
                             }
                         }
                     } else {
@@ -1910,9 +2100,97 @@ impl Manager {
                 }
             }
         }
+        if !services_to_stop.is_empty() {
+            tokio::spawn(self.stop_services_future_gsw(services_to_stop));
+        }
         services_started
     }
 
+    /// Returns the dependencies of `spec` which are not yet up on this
+    /// Supervisor, warning about any that are not loaded at all.
+    ///
+    /// # Locking (see locking.md)
+    /// * `ManagerServices::inner` (read)
+    fn unready_dependencies_msr(&mut self, spec: &ServiceSpec) -> Vec<PackageIdent> {
+        self.services_up_when_checked = self.up_services_msr();
+        let services = self.state.services.lock_msr();
+        let mut unready = Vec::new();
+        for dependency in &spec.dependencies {
+            if !services.running_services()
+                        .any(|service| service.pkg.ident.as_ref().satisfies(dependency))
+            {
+                warn!("{} depends on {}, which is not loaded on this Supervisor",
+                      spec.ident, dependency);
+            }
+            if !self.services_up_when_checked
+                    .iter()
+                    .any(|ident| ident.satisfies(dependency))
+            {
+                unready.push(dependency.clone());
+            }
+        }
+        unready
+    }
+
+    /// Returns the idents of the services running on this Supervisor
+    /// that are up, with an `Ok` or `Warning` health check result.
+    /// Services that depend on them may start.
+    ///
+    /// # Locking (see locking.md)
+    /// * `ManagerServices::inner` (read)
+    fn up_services_msr(&self) -> HashSet<PackageIdent> {
+        self.state
+            .services
+            .lock_msr()
+            .running_services()
+            .filter(|service| {
+                matches!(service.health_check_result(),
+                         HealthCheckResult::Ok | HealthCheckResult::Warning)
+            })
+            .map(|service| service.pkg.ident.as_ref().clone())
+            .collect()
+    }
+
+    /// Pure utility function to group `items` into tiers, such that
+    /// every item comes in a later tier than all of the items it
+    /// depends on (as declared in the `ServiceSpec` returned by
+    /// `spec_of`). Starting the tiers in order, or stopping them in
+    /// reverse order, honors the dependencies between services.
+    ///
+    /// Dependencies on services that are not among `items` are
+    /// ignored. Items caught in a dependency cycle are all placed in
+    /// the final tier.
+    fn dependency_tiers<T, F>(items: Vec<T>, spec_of: F) -> Vec<Vec<T>>
+        where F: Fn(&T) -> &ServiceSpec
+    {
+        let mut tiers = Vec::new();
+        let mut remaining = items;
+        while !remaining.is_empty() {
+            let is_blocked = |i: usize| {
+                let spec = spec_of(&remaining[i]);
+                remaining.iter()
+                         .enumerate()
+                         .any(|(j, other)| i != j && spec.depends_on(&spec_of(other).ident))
+            };
+            let blocked = (0..remaining.len()).map(is_blocked).collect::<Vec<_>>();
+            if blocked.iter().all(|b| *b) {
+                warn!("Dependency cycle detected between {}; ignoring their dependencies",
+                      remaining.iter()
+                               .map(|item| spec_of(item).ident.to_string())
+                               .collect::<Vec<_>>()
+                               .join(", "));
+                tiers.push(remaining);
+                break;
+            }
+            let (tier, rest): (Vec<_>, Vec<_>) = remaining.into_iter()
+                                                          .zip(blocked)
+                                                          .partition(|(_, blocked)| !blocked);
+            tiers.push(tier.into_iter().map(|(item, _)| item).collect());
+            remaining = rest.into_iter().map(|(item, _)| item).collect();
+        }
+        tiers
+    }
+
     /// Determine what services we need to start, stop, or restart in
     /// order to be running what our on-disk spec files tell us we
     /// should be running.
@@ -1932,7 +2210,7 @@ impl Manager {
         let on_disk_specs = self.spec_dir
                                 .specs()
                                 .into_iter()
-                                .filter(|s| !busy_services.contains(&s.ident));
+                                .filter(|s| !busy_services.contains(&s.service_name()));
 
         Self::specs_to_operations(currently_running_specs, on_disk_specs)
     }
@@ -1955,7 +2233,7 @@ impl Manager {
         }
 
         for rs in currently_running_specs {
-            svc_states.insert(rs.ident.clone(),
+            svc_states.insert(rs.service_name(),
                               ServiceState { running: Some(rs),
                                              disk:    None, });
         }
@@ -1964,14 +2242,13 @@ impl Manager {
         // entries for services that are currently running, yet have
         // on-disk spec changes that must be reconciled.
         for ds in on_disk_specs {
-            let ident = ds.ident.clone();
-            svc_states.entry(ident)
+            svc_states.entry(ds.service_name())
                       .or_insert_with(ServiceState::default)
                       .disk = Some(ds);
         }
 
         svc_states.into_iter()
-                  .filter_map(|(_ident, ss)| ServiceSpec::reconcile(ss.running, ss.disk))
+                  .filter_map(|(_service_name, ss)| ServiceSpec::reconcile(ss.running, ss.disk))
                   .collect()
     }
 
@@ -2115,6 +2392,73 @@ mod test {
         }
     }
 
+    mod dependency_tiers {
+        use super::*;
+
+        fn spec(ident: &str, dependencies: &[&str]) -> ServiceSpec {
+            let mut spec = ServiceSpec::new(ident.parse().unwrap());
+            spec.dependencies = dependencies.iter().map(|d| d.parse().unwrap()).collect();
+            spec
+        }
+
+        fn tier_idents(tiers: Vec<Vec<ServiceSpec>>) -> Vec<Vec<String>> {
+            tiers.into_iter()
+                 .map(|tier| tier.into_iter().map(|s| s.ident.to_string()).collect())
+                 .collect()
+        }
+
+        #[test]
+        fn dependencies_come_before_their_dependents() {
+            let specs = vec![spec("core/app", &["core/api"]),
+                             spec("core/api", &["core/db", "core/cache"]),
+                             spec("core/db", &[]),
+                             spec("core/cache", &[]),];
+
+            assert_eq!(tier_idents(Manager::dependency_tiers(specs, |s| s)),
+                       vec![vec!["core/db", "core/cache"],
+                            vec!["core/api"],
+                            vec!["core/app"]]);
+        }
+
+        #[test]
+        fn dependencies_outside_the_set_are_ignored() {
+            let specs = vec![spec("core/api", &["core/db"]), spec("core/web", &[])];
+
+            assert_eq!(tier_idents(Manager::dependency_tiers(specs, |s| s)),
+                       vec![vec!["core/api", "core/web"]]);
+        }
+
+        #[test]
+        fn cycles_end_up_in_the_final_tier() {
+            let specs = vec![spec("core/a", &["core/b"]),
+                             spec("core/b", &["core/a"]),
+                             spec("core/c", &[]),];
+
+            assert_eq!(tier_idents(Manager::dependency_tiers(specs, |s| s)),
+                       vec![vec!["core/c"], vec!["core/a", "core/b"]]);
+        }
+    }
+
+    mod specs_to_operations {
+        use super::*;
+
+        fn spec(ident: &str, instance: Option<&str>) -> ServiceSpec {
+            let mut spec = ServiceSpec::new(ident.parse().unwrap());
+            spec.instance = instance.map(str::to_string);
+            spec
+        }
+
+        #[test]
+        fn instances_of_a_package_are_reconciled_separately() {
+            let running = vec![spec("core/redis", Some("cache"))];
+            let on_disk = vec![spec("core/redis", Some("cache")),
+                               spec("core/redis", Some("sessions")),];
+
+            assert_eq!(Manager::specs_to_operations(running, on_disk),
+                       vec![ServiceOperation::Start(spec("core/redis", Some("sessions")))]);
+        }
+    }
+
     // Implementing Default in production code encourages passing the entirety of this struct
     // around when it would be better to be more targeted. However, it is very handy for test
     // code, so only implement it under test configuration.
@@ -2122,6 +2466,7 @@ mod test {
         fn default() -> Self {
             ManagerConfig { auto_update:                false,
                             auto_update_period:         Duration::from_secs(60),
+                            auto_update_windows:        Vec::new(),
                             service_update_period:      Duration::from_secs(60),
                             service_restart_config:     ServiceRestartConfig::default(),
                             custom_state_path:          None,
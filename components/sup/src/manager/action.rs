@@ -32,6 +32,13 @@ pub enum SupervisorAction {
     UpdateService {
         service_spec: ServiceSpec,
     },
+    ResetService {
+        service_spec: ServiceSpec,
+    },
+    DrainService {
+        service_spec: ServiceSpec,
+        draining:     bool,
+    },
 }
 
 pub type ActionSender = mpsc::Sender<SupervisorAction>;
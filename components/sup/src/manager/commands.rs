@@ -1,6 +1,7 @@
 //! All the code for responding to Supervisor commands
 
-use crate::{ctl_gateway::CtlRequest,
+use crate::{census::ServiceConfigVersion,
+            ctl_gateway::CtlRequest,
             error::Error,
             manager::{action::{ActionSender,
                                SupervisorAction},
@@ -11,8 +12,11 @@ use crate::{ctl_gateway::CtlRequest,
 use habitat_butterfly as butterfly;
 use habitat_common::{command::package::install::InstallSource,
                      outputln,
+                     service_log::{self,
+                                   LogFollower},
                      ui::UIWriter};
-use habitat_core::{package::{Identifiable,
+use habitat_core::{fs,
+                   package::{Identifiable,
                              PackageIdent,
                              PackageTarget},
                    service::ServiceGroup};
@@ -22,10 +26,16 @@ use habitat_sup_protocol::{self as protocol,
                                  NetResult}};
 use std::{convert::TryFrom,
           str,
-          sync::atomic::Ordering};
+          sync::atomic::Ordering,
+          time::{Duration,
+                 SystemTime}};
+use tokio::time;
 
 static LOGKEY: &str = "CMD";
 
+/// How often a followed service log is checked for new lines.
+const SVC_LOG_FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
 /// # Locking (see locking.md)
 /// * `ManagerServices::inner` (read)
 pub fn service_cfg_msr(mgr: &ManagerState,
@@ -55,27 +65,7 @@ pub fn service_cfg_validate(_mgr: &ManagerState,
                             opts: protocol::ctl::SvcValidateCfg)
                             -> NetResult<()> {
     let cfg = opts.cfg.ok_or_else(err_update_client)?;
-    let format = opts.format
-                     .and_then(protocol::types::service_cfg::Format::from_i32)
-                     .unwrap_or_default();
-    if cfg.len() > protocol::butterfly::MAX_SVC_CFG_SIZE {
-        return Err(net::err(ErrCode::EntityTooLarge, "Configuration too large."));
-    }
-    if format != protocol::types::service_cfg::Format::Toml {
-        return Err(net::err(ErrCode::NotSupported,
-                            format!("Configuration format {} not available.",
-                                    format)));
-    }
-    let cfg_str =
-        str::from_utf8(&cfg).map_err(|e| {
-                                net::err(ErrCode::BadPayload,
-                                         format!("Unable to decode configuration to string, {}", e))
-                            })?;
-    let _new_cfg: toml::value::Table = toml::from_str(cfg_str).map_err(|e| {
-                                                                  net::err(ErrCode::BadPayload,
-                                            format!("Unable to decode configuration as {}, {}",
-                                                    format, e))
-                                                              })?;
+    let _new_cfg = decode_cfg(&cfg, opts.format)?;
     req.reply_complete(net::ok());
     Ok(())
     // JW TODO: Hold off on validation until we can validate services which aren't currently
@@ -110,6 +100,50 @@ pub fn service_cfg_validate(_mgr: &ManagerState,
     // ))
 }
 
+/// # Locking (see locking.md)
+/// * `ManagerServices::inner` (read)
+pub fn service_cfg_dry_run_msr(mgr: &ManagerState,
+                               req: &mut CtlRequest,
+                               opts: protocol::ctl::SvcDryRunCfg)
+                               -> NetResult<()> {
+    let cfg = opts.cfg.ok_or_else(err_update_client)?;
+    let service_group: ServiceGroup = opts.service_group.ok_or_else(err_update_client)?.into();
+    let new_cfg = decode_cfg(&cfg, opts.format)?;
+    let services = mgr.services.lock_msr();
+    let service = services.running_services()
+                          .find(|service| service.service_group == service_group)
+                          .ok_or_else(|| {
+                              net::err(ErrCode::NotFound,
+                                       format!("Service not loaded, {}", service_group))
+                          })?;
+    req.reply_complete(service.dry_run_cfg(&mgr.census_ring.read(), new_cfg));
+    Ok(())
+}
+
+/// Decode a configuration sent by a client, in the given format.
+fn decode_cfg(cfg: &[u8], format: Option<i32>) -> NetResult<toml::value::Table> {
+    let format = format.and_then(protocol::types::service_cfg::Format::from_i32)
+                       .unwrap_or_default();
+    if cfg.len() > protocol::butterfly::MAX_SVC_CFG_SIZE {
+        return Err(net::err(ErrCode::EntityTooLarge, "Configuration too large."));
+    }
+    if format != protocol::types::service_cfg::Format::Toml {
+        return Err(net::err(ErrCode::NotSupported,
+                            format!("Configuration format {} not available.",
+                                    format)));
+    }
+    let cfg_str =
+        str::from_utf8(cfg).map_err(|e| {
+                               net::err(ErrCode::BadPayload,
+                                        format!("Unable to decode configuration to string, {}", e))
+                           })?;
+    toml::from_str(cfg_str).map_err(|e| {
+                               net::err(ErrCode::BadPayload,
+                                        format!("Unable to decode configuration as {}, {}",
+                                                format, e))
+                           })
+}
+
 pub fn service_cfg_set(mgr: &ManagerState,
                        req: &mut CtlRequest,
                        opts: protocol::ctl::SvcSetCfg)
@@ -141,6 +175,81 @@ pub fn service_cfg_set(mgr: &ManagerState,
           })
 }
 
+/// # Locking (see locking.md)
+/// * `ManagerState::census_ring` (read)
+pub fn service_cfg_history(mgr: &ManagerState,
+                           req: &mut CtlRequest,
+                           opts: protocol::ctl::SvcConfigHistory)
+                           -> NetResult<()> {
+    let service_group: ServiceGroup = opts.service_group.ok_or_else(err_update_client)?.into();
+    let versions = mgr.census_ring
+                      .read()
+                      .census_group_for(&service_group)
+                      .map(|census_group| {
+                          census_group.config_history()
+                                      .map(ServiceConfigVersion::to_service_config_version)
+                                      .collect()
+                      })
+                      .unwrap_or_default();
+    req.reply_complete(protocol::types::ServiceConfigHistory { versions });
+    Ok(())
+}
+
+/// # Locking (see locking.md)
+/// * `ManagerState::census_ring` (read)
+///
+/// The lock is released before the rolled back configuration is gossiped.
+pub fn service_cfg_rollback(mgr: &ManagerState,
+                            req: &mut CtlRequest,
+                            opts: protocol::ctl::SvcConfigRollback)
+                            -> NetResult<()> {
+    let service_group: ServiceGroup = opts.service_group.ok_or_else(err_update_client)?.into();
+    let version = opts.version.ok_or_else(err_update_client)?;
+    let (rollback, incarnation) = {
+        let census_ring = mgr.census_ring.read();
+        let not_found = || {
+            net::err(ErrCode::NotFound,
+                     format!("Configuration version {} of {} is not in the history",
+                             version, service_group))
+        };
+        let census_group = census_ring.census_group_for(&service_group)
+                                      .ok_or_else(not_found)?;
+        let rollback = census_group.config_history()
+                                   .find(|applied| applied.incarnation == version)
+                                   .cloned()
+                                   .ok_or_else(not_found)?;
+        // Gossip it under a new incarnation, so that it supersedes the
+        // current configuration
+        let current = census_group.service_config
+                                  .as_ref()
+                                  .map_or(version, |config| config.incarnation);
+        (rollback, current + 1)
+    };
+    outputln!("Rolling back configuration of {} to version {} as version {}",
+              service_group,
+              version,
+              incarnation);
+    let mut client =
+        match butterfly::client::Client::new(&mgr.cfg.gossip_listen.local_addr().to_string(),
+                                             mgr.cfg.ring_key.clone())
+        {
+            Ok(client) => client,
+            Err(err) => {
+                outputln!("Failed to connect to own gossip server, {}", err);
+                return Err(net::err(ErrCode::Internal, err.to_string()));
+            }
+        };
+    client.send_service_config(service_group.clone(),
+                               incarnation,
+                               &rollback.payload,
+                               rollback.encrypted)
+          .map_err(|e| net::err(ErrCode::Internal, e.to_string()))?;
+    req.info(format!("Rolled back configuration of {} to version {} as version {}",
+                     service_group, version, incarnation))?;
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
 pub fn service_file_put(mgr: &ManagerState,
                         req: &mut CtlRequest,
                         opts: protocol::ctl::SvcFilePut)
@@ -180,7 +289,9 @@ pub async fn service_load(mgr: &ManagerState,
                           -> NetResult<()> {
     let ident: PackageIdent = opts.ident.clone().ok_or_else(err_update_client)?.into();
     let source = InstallSource::Ident(ident.clone(), PackageTarget::active_target());
-    let spec = if let Some(spec) = mgr.cfg.spec_for_ident(source.as_ref()) {
+    let spec = if let Some(spec) = mgr.cfg
+                                      .spec_for_ident(source.as_ref(), opts.instance.as_deref())
+    {
         // We've seen this service before. Thus `load` acts as a way to edit spec files from the
         // command line. As a result, we check that you *really* meant to change an existing spec.
         if !opts.force.unwrap_or(false) {
@@ -189,7 +300,8 @@ pub async fn service_load(mgr: &ManagerState,
                                          and try again, or load with the \
                                          --force flag to reload and restart the \
                                          service.",
-                                        ident)));
+                                        service_label(&ident,
+                                                      opts.instance.as_deref()))));
         }
         spec.merge_svc_load(opts)?
     } else {
@@ -200,7 +312,8 @@ pub async fn service_load(mgr: &ManagerState,
     spec.validate(&package)?;
     mgr.cfg.save_spec_for(&spec)?;
 
-    req.info(format!("The {} service was successfully loaded", spec.ident))?;
+    req.info(format!("The {} service was successfully loaded",
+                     service_label(&spec.ident, spec.instance.as_deref())))?;
     req.reply_complete(net::ok());
     Ok(())
 }
@@ -211,16 +324,17 @@ pub fn service_update(mgr: &ManagerState,
                       action_sender: &ActionSender)
                       -> NetResult<()> {
     let ident: PackageIdent = opts.ident.clone().ok_or_else(err_update_client)?.into();
-    if let Some(mut service_spec) = mgr.cfg.spec_for_ident(&ident) {
-        service_spec.merge_svc_update(opts);
+    let label = service_label(&ident, opts.instance.as_deref());
+    if let Some(mut service_spec) = mgr.cfg.spec_for_ident(&ident, opts.instance.as_deref()) {
+        service_spec.merge_svc_update(opts)?;
         let action = SupervisorAction::UpdateService { service_spec };
         send_action(action, action_sender)?;
 
-        req.info(format!("Updating {}", ident))?;
+        req.info(format!("Updating {}", label))?;
         req.reply_complete(net::ok());
         Ok(())
     } else {
-        Err(net::err(ErrCode::Internal, Error::ServiceNotLoaded(ident)))
+        Err(net::err(ErrCode::Internal, Error::ServiceNotLoaded(label)))
     }
 }
 
@@ -230,7 +344,8 @@ pub fn service_unload(mgr: &ManagerState,
                       action_sender: &ActionSender)
                       -> NetResult<()> {
     let ident: PackageIdent = opts.ident.clone().ok_or_else(err_update_client)?.into();
-    if let Some(service_spec) = mgr.cfg.spec_for_ident(&ident) {
+    let label = service_label(&ident, opts.instance.as_deref());
+    if let Some(service_spec) = mgr.cfg.spec_for_ident(&ident, opts.instance.as_deref()) {
         let shutdown_input = opts.into();
         let action = SupervisorAction::UnloadService { service_spec,
                                                        shutdown_input };
@@ -238,11 +353,11 @@ pub fn service_unload(mgr: &ManagerState,
 
         // JW TODO: Change this to unloaded from unloading when the Supervisor waits for
         // the work to complete.
-        req.info(format!("Unloading {}", ident))?;
+        req.info(format!("Unloading {}", label))?;
         req.reply_complete(net::ok());
         Ok(())
     } else {
-        Err(net::err(ErrCode::Internal, Error::ServiceNotLoaded(ident)))
+        Err(net::err(ErrCode::Internal, Error::ServiceNotLoaded(label)))
     }
 }
 
@@ -251,7 +366,8 @@ pub fn service_start(mgr: &ManagerState,
                      opts: protocol::ctl::SvcStart)
                      -> NetResult<()> {
     let ident = opts.ident.ok_or_else(err_update_client)?.into();
-    match mgr.cfg.spec_for_ident(&ident) {
+    let label = service_label(&ident, opts.instance.as_deref());
+    match mgr.cfg.spec_for_ident(&ident, opts.instance.as_deref()) {
         Some(mut spec) => {
             if spec.desired_state == DesiredState::Down {
                 spec.desired_state = DesiredState::Up;
@@ -261,11 +377,11 @@ pub fn service_start(mgr: &ManagerState,
                 // synchronously control services from the ctl gateway.
                 req.info(format!("Supervisor starting {}. See the Supervisor output for more \
                                   details.",
-                                 &ident))?;
+                                 &label))?;
             }
         }
         None => {
-            return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", &ident)));
+            return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", &label)));
         }
     };
     req.reply_complete(net::ok());
@@ -278,7 +394,8 @@ pub fn service_stop(mgr: &ManagerState,
                     action_sender: &ActionSender)
                     -> NetResult<()> {
     let ident: PackageIdent = opts.ident.clone().ok_or_else(err_update_client)?.into();
-    match mgr.cfg.spec_for_ident(&ident) {
+    let label = service_label(&ident, opts.instance.as_deref());
+    match mgr.cfg.spec_for_ident(&ident, opts.instance.as_deref()) {
         Some(service_spec) => {
             if service_spec.desired_state == DesiredState::Up {
                 let shutdown_input = opts.into();
@@ -290,11 +407,81 @@ pub fn service_stop(mgr: &ManagerState,
                 // synchronously control services from the ctl gateway.
                 req.info(format!("Supervisor stopping {}. See the Supervisor output for more \
                                   details.",
-                                 &ident))?;
+                                 &label))?;
             }
         }
         None => {
-            return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", &ident)));
+            return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", &label)));
+        }
+    };
+
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
+pub fn service_reset(mgr: &ManagerState,
+                     req: &mut CtlRequest,
+                     opts: protocol::ctl::SvcReset,
+                     action_sender: &ActionSender)
+                     -> NetResult<()> {
+    let ident: PackageIdent = opts.ident.ok_or_else(err_update_client)?.into();
+    let label = service_label(&ident, opts.instance.as_deref());
+    match mgr.cfg.spec_for_ident(&ident, opts.instance.as_deref()) {
+        Some(service_spec) => {
+            let action = SupervisorAction::ResetService { service_spec };
+            send_action(action, action_sender)?;
+
+            req.info(format!("Supervisor resetting {}. See the Supervisor output for more \
+                              details.",
+                             &label))?;
+        }
+        None => {
+            return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", &label)));
+        }
+    };
+
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
+pub fn service_drain(mgr: &ManagerState,
+                     req: &mut CtlRequest,
+                     opts: protocol::ctl::SvcDrain,
+                     action_sender: &ActionSender)
+                     -> NetResult<()> {
+    let ident: PackageIdent = opts.ident.ok_or_else(err_update_client)?.into();
+    set_draining(mgr, req, ident, opts.instance, true, action_sender)
+}
+
+pub fn service_undrain(mgr: &ManagerState,
+                       req: &mut CtlRequest,
+                       opts: protocol::ctl::SvcUndrain,
+                       action_sender: &ActionSender)
+                       -> NetResult<()> {
+    let ident: PackageIdent = opts.ident.ok_or_else(err_update_client)?.into();
+    set_draining(mgr, req, ident, opts.instance, false, action_sender)
+}
+
+fn set_draining(mgr: &ManagerState,
+                req: &mut CtlRequest,
+                ident: PackageIdent,
+                instance: Option<String>,
+                draining: bool,
+                action_sender: &ActionSender)
+                -> NetResult<()> {
+    let label = service_label(&ident, instance.as_deref());
+    match mgr.cfg.spec_for_ident(&ident, instance.as_deref()) {
+        Some(service_spec) => {
+            let action = SupervisorAction::DrainService { service_spec,
+                                                          draining };
+            send_action(action, action_sender)?;
+
+            req.info(format!("Supervisor {} {}. See the Supervisor output for more details.",
+                             if draining { "draining" } else { "undraining" },
+                             &label))?;
+        }
+        None => {
+            return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", &label)));
         }
     };
 
@@ -336,61 +523,185 @@ pub fn supervisor_restart(mgr: &ManagerState,
     Ok(())
 }
 
+pub fn service_logs(mgr: &ManagerState,
+                    req: &mut CtlRequest,
+                    opts: protocol::ctl::SvcLogs)
+                    -> NetResult<()> {
+    let ident: PackageIdent = opts.ident.ok_or_else(err_update_client)?.into();
+    let label = service_label(&ident, opts.instance.as_deref());
+    let service_spec =
+        mgr.cfg
+           .spec_for_ident(&ident, opts.instance.as_deref())
+           .ok_or_else(|| net::err(ErrCode::NotFound, format!("Service not loaded, {}", &label)))?;
+    let path = service_log::path(&fs::svc_var_path(&service_spec.service_name()));
+    let since = opts.since.map(|secs| {
+                              SystemTime::now().checked_sub(Duration::from_secs(secs))
+                                               .unwrap_or(SystemTime::UNIX_EPOCH)
+                          });
+
+    // Start following before reading so that nothing written in
+    // between is missed.
+    let follower = if opts.follow.unwrap_or(false) {
+        Some(LogFollower::new(&path).map_err(err_service_log)?)
+    } else {
+        None
+    };
+    for line in service_log::read(&path, since).map_err(err_service_log)? {
+        send_log_line(req, line);
+    }
+
+    match follower {
+        Some(follower) => {
+            // Lines keep being streamed from a task of their own
+            // until the client goes away.
+            tokio::spawn(follow_service_log(req.clone(), follower));
+        }
+        None => req.reply_complete(net::ok()),
+    }
+    Ok(())
+}
+
+async fn follow_service_log(mut req: CtlRequest, mut follower: LogFollower) {
+    let mut interval = time::interval(SVC_LOG_FOLLOW_INTERVAL);
+    while !req.is_closed() {
+        interval.tick().await;
+        match follower.poll() {
+            Ok(lines) => {
+                for line in lines {
+                    send_log_line(&mut req, line);
+                }
+            }
+            Err(e) => {
+                req.reply_complete(err_service_log(e));
+                return;
+            }
+        }
+    }
+}
+
+fn send_log_line(req: &mut CtlRequest, mut line: String) {
+    line.push('\n');
+    req.reply_partial(protocol::ctl::ConsoleLine { line,
+                                                   color: None,
+                                                   bold: false });
+}
+
+fn err_service_log(err: std::io::Error) -> net::NetErr {
+    net::err(ErrCode::Internal,
+             format!("Unable to read service log: {}", err))
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+pub fn service_hooks_gsr(mgr: &ManagerState,
+                         req: &mut CtlRequest,
+                         opts: protocol::ctl::SvcHooks)
+                         -> NetResult<()> {
+    let ident: PackageIdent = opts.ident.ok_or_else(err_update_client)?.into();
+    let label = service_label(&ident, opts.instance.as_deref());
+    let service_name =
+        mgr.cfg
+           .spec_for_ident(&ident, opts.instance.as_deref())
+           .ok_or_else(|| net::err(ErrCode::NotFound, format!("Service not loaded, {}", &label)))?
+           .service_name();
+    // As with the status, the runs are converted into messages before any are sent to keep the
+    // gateway state locked only briefly.
+    let hook_runs: Vec<_> =
+        mgr.gateway_state
+           .lock_gsr()
+           .service_histories()
+           .iter()
+           .filter(|(service_group, _)| service_group.service() == service_name)
+           .flat_map(|(_, histories)| histories.hooks.runs())
+           .flat_map(|(hook, runs)| runs.into_iter().map(move |run| run.to_hook_run(&hook)))
+           .collect();
+    for hook_run in hook_runs {
+        req.reply_partial(hook_run);
+    }
+    req.reply_complete(net::ok());
+    Ok(())
+}
+
+/// # Locking (see locking.md)
+/// * `GatewayState::inner` (read)
+pub fn service_health_history_gsr(mgr: &ManagerState,
+                                  req: &mut CtlRequest,
+                                  opts: protocol::ctl::SvcHealthHistory)
+                                  -> NetResult<()> {
+    let ident: PackageIdent = opts.ident.ok_or_else(err_update_client)?.into();
+    let label = service_label(&ident, opts.instance.as_deref());
+    let service_name =
+        mgr.cfg
+           .spec_for_ident(&ident, opts.instance.as_deref())
+           .ok_or_else(|| net::err(ErrCode::NotFound, format!("Service not loaded, {}", &label)))?
+           .service_name();
+    let history = mgr.gateway_state
+                     .lock_gsr()
+                     .service_histories()
+                     .iter()
+                     .find(|(service_group, _)| service_group.service() == service_name)
+                     .map(|(_, histories)| {
+                         let health = &histories.health;
+                         let checks = health.checks()
+                                            .iter()
+                                            .map(|check| check.to_health_check())
+                                            .collect();
+                         protocol::types::HealthHistory { flapping: health.is_flapping(),
+                                                          checks }
+                     })
+                     .unwrap_or_default();
+    req.reply_complete(history);
+    Ok(())
+}
+
 /// # Locking (see locking.md)
 /// * `GatewayState::inner` (read)
 pub fn service_status_gsr(mgr: &ManagerState,
                           req: &mut CtlRequest,
                           opts: protocol::ctl::SvcStatus)
                           -> NetResult<()> {
-    if let Some(ident) = opts.ident {
-        let service_status = mgr.gateway_state
-                                .lock_gsr()
-                                .services_data()
-                                .iter()
-                                .find_map(|service| {
-                                    if service.pkg.ident.satisfies(&ident) {
-                                        Some(protocol::types::ServiceStatus::from(service))
-                                    } else {
-                                        None
-                                    }
-                                });
-        match service_status {
-            Some(service_status) => {
-                req.reply_complete(service_status);
-                Ok(())
-            }
-            None => Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", ident))),
+    // Several named instances of a package may match a single ident, so we may be dealing with
+    // several services even if an ident was given. We serialize service data into messages
+    // completely before sending them over the network to minimize locking of the gateway state.
+    let ident = opts.ident.as_ref();
+    let service_statuses: Vec<_> =
+        mgr.gateway_state
+           .lock_gsr()
+           .services_data()
+           .iter()
+           .filter(|service| ident.map_or(true, |i| service.pkg.ident.satisfies(i)))
+           .map(protocol::types::ServiceStatus::from)
+           .collect();
+    if service_statuses.is_empty() {
+        if let Some(ident) = opts.ident {
+            return Err(net::err(ErrCode::NotFound, format!("Service not loaded, {}", ident)));
         }
+        req.reply_complete(net::ok());
     } else {
-        // We're not dealing with a single service, but with all of them.
-        // We serialize service data into messages completely before sending them over the network
-        // to minimize locking of the gateway state.
-        let service_statuses: Vec<_> = mgr.gateway_state
-                                          .lock_gsr()
-                                          .services_data()
-                                          .iter()
-                                          .map(protocol::types::ServiceStatus::from)
-                                          .collect();
-        if service_statuses.is_empty() {
-            req.reply_complete(net::ok());
-        } else {
-            let mut list = service_statuses.into_iter().peekable();
-            while let Some(service_status) = list.next() {
-                if list.peek().is_some() {
-                    req.reply_partial(service_status);
-                } else {
-                    req.reply_complete(service_status);
-                }
+        let mut list = service_statuses.into_iter().peekable();
+        while let Some(service_status) = list.next() {
+            if list.peek().is_some() {
+                req.reply_partial(service_status);
+            } else {
+                req.reply_complete(service_status);
             }
         }
-        Ok(())
     }
+    Ok(())
 }
 
 ////////////////////////////////////////////////////////////////////////
 // Private helper functions
 fn err_update_client() -> net::NetErr { net::err(ErrCode::UpdateClient, "client out of date") }
 
+/// Names a service in replies, adding the instance name for named instances.
+fn service_label(ident: &PackageIdent, instance: Option<&str>) -> String {
+    match instance {
+        Some(instance) => format!("{} (instance {})", ident, instance),
+        None => ident.to_string(),
+    }
+}
+
 /// Helper function to ensure that all errors in sending are handled identically.
 fn send_action(action: SupervisorAction, sender: &ActionSender) -> NetResult<()> {
     if sender.send(action).is_err() {
@@ -401,3 +712,23 @@ fn send_action(action: SupervisorAction, sender: &ActionSender) -> NetResult<()>
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn following_a_service_log_ends_when_the_client_goes_away() {
+        let tmpdir = TempDir::new().unwrap();
+        let follower = LogFollower::new(tmpdir.path().join("service.log")).unwrap();
+        let (tx, rx) = mpsc::unbounded();
+        let follow = tokio::spawn(follow_service_log(CtlRequest::new(tx, None), follower));
+
+        drop(rx);
+        time::timeout(SVC_LOG_FOLLOW_INTERVAL * 3, follow).await
+                                                          .expect("Still following the log")
+                                                          .unwrap();
+    }
+}
@@ -1,10 +1,12 @@
 //! Encapsulates logic required for updating the Habitat Supervisor
 //! itself.
 
-use crate::util;
+use crate::util::{self,
+                  maintenance};
 use habitat_common::command::package::install::InstallSource;
 use habitat_core::{package::{PackageIdent,
                              PackageInstall},
+                   service::MaintenanceWindow,
                    ChannelIdent};
 use log::{debug,
           trace,
@@ -47,6 +49,7 @@ pub struct SelfUpdater {
     update_url:     String,
     update_channel: ChannelIdent,
     period:         Duration,
+    windows:        Vec<MaintenanceWindow>,
 }
 
 /// The subset of data from `SelfUpdater` needed to spawn the updater task.
@@ -55,6 +58,7 @@ struct Runner {
     update_url:     String,
     update_channel: ChannelIdent,
     period:         Duration,
+    windows:        Vec<MaintenanceWindow>,
 }
 
 impl<T: Borrow<SelfUpdater>> From<T> for Runner {
@@ -63,7 +67,8 @@ impl<T: Borrow<SelfUpdater>> From<T> for Runner {
         Self { current:        other.current.clone(),
                update_url:     other.update_url.clone(),
                update_channel: other.update_channel.clone(),
-               period:         other.period, }
+               period:         other.period,
+               windows:        other.windows.clone(), }
     }
 }
 
@@ -71,18 +76,21 @@ impl SelfUpdater {
     pub fn new(current: &PackageIdent,
                update_url: String,
                update_channel: ChannelIdent,
-               period: Duration)
+               period: Duration,
+               windows: Vec<MaintenanceWindow>)
                -> Self {
         let runner = Runner { current: current.clone(),
                               update_url: update_url.clone(),
                               update_channel: update_channel.clone(),
-                              period };
+                              period,
+                              windows: windows.clone() };
         let rx = Self::init(runner);
         SelfUpdater { rx,
                       current: current.clone(),
                       update_url,
                       update_channel,
-                      period }
+                      period,
+                      windows }
     }
 
     /// Spawn a new Supervisor updater task.
@@ -99,7 +107,8 @@ impl SelfUpdater {
         let Runner { current,
                      update_url,
                      update_channel,
-                     period, } = runner;
+                     period,
+                     windows, } = runner;
         let period = SelfUpdatePeriod::get().unwrap_or(period);
         let splay = Duration::from_secs(rand::thread_rng().gen_range(0..period.as_secs()));
         debug!("Starting self updater with current package {} in {}s",
@@ -110,6 +119,7 @@ impl SelfUpdater {
             match util::pkg::install_no_ui(&update_url, &install_source, &update_channel).await {
                 Ok(package) => {
                     if &current < package.ident() {
+                        maintenance::wait_for_window(&windows).await;
                         debug!("Self updater installing newer Supervisor, {}",
                                package.ident());
                         tx.send(package).expect("Main thread has gone away!");
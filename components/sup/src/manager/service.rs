@@ -10,25 +10,36 @@
 
 mod context;
 mod health;
+mod health_history;
+mod hook_history;
 mod hook_runner;
 mod hooks;
+mod job;
 #[cfg(windows)]
 mod pipe_hook_client;
+mod probe_runner;
 pub mod spec;
 mod supervisor;
 mod terminator;
 
 use self::{context::RenderContext,
+           health::LivenessTracker,
+           health_history::HealthHistory,
+           hook_history::HookHistory,
            hook_runner::HookRunner,
            hooks::{HookCompileTable,
                    HookTable,
                    HookTableQueryModel},
+           job::{JobQueryModel,
+                 JobTracker},
+           probe_runner::ProbeRunner,
            supervisor::{PidUpdate,
                         SupervisedProcessQueryModel,
                         Supervisor}};
 pub use self::{health::{HealthCheckBundle,
                         HealthCheckHookStatus,
                         HealthCheckResult},
+               health_history::HealthCheckRecord,
                hooks::{HealthCheckHook,
                        ProcessOutput,
                        StandardStreams},
@@ -45,7 +56,8 @@ use crate::{census::{CensusGroup,
                       FsCfg,
                       ServicePidSource,
                       ShutdownConfig,
-                      Sys}};
+                      Sys},
+            util};
 use futures::future::{self,
                       AbortHandle};
 use habitat_butterfly::rumor::service::Service as ServiceRumor;
@@ -69,11 +81,15 @@ use habitat_core::{crypto::Blake2bHash,
                         SvcDir,
                         FS_ROOT_PATH},
                    os::process::{Pid,
-                                 ShutdownTimeout},
+                                 ShutdownTimeout,
+                                 Signal},
                    package::{metadata::Bind,
                              PackageIdent,
-                             PackageInstall},
-                   service::{HealthCheckInterval,
+                             PackageInstall,
+                             VersionConstraint},
+                   service::{CanaryPolicy,
+                             HealthCheckInterval,
+                             RolloutPolicy,
                              ServiceBind,
                              ServiceGroup},
                    ChannelIdent};
@@ -95,7 +111,8 @@ use serde::{ser::{Error as _,
             Serialize,
             Serializer};
 use std::{self,
-          collections::HashSet,
+          collections::{HashSet,
+                        VecDeque},
           convert::TryFrom,
           fmt,
           fs,
@@ -106,6 +123,7 @@ use std::{self,
           sync::{Arc,
                  Mutex},
           time::{Duration,
+                 Instant,
                  SystemTime}};
 
 use super::ServiceRestartConfig;
@@ -116,6 +134,10 @@ static LOGKEY: &str = "SR";
 #[cfg(not(windows))]
 pub const GOSSIP_FILE_PERMISSIONS: u32 = 0o640;
 
+/// The number of failures after which a release on probation is
+/// rolled back.
+const ROLLBACK_FAILURE_THRESHOLD: u32 = 3;
+
 lazy_static! {
     static ref HOOK_DURATION: HistogramVec =
         register_histogram_vec!("hab_sup_hook_duration_seconds",
@@ -132,7 +154,7 @@ enum BindStatus<'a> {
     /// The bound group is not present in the census
     NotPresent,
     /// The bound group is present in the census, but has no active
-    /// members.
+    /// members that haven't been drained.
     Empty,
     /// The bound group is present in the census, has active members,
     /// but does not satisfy the contract of the bind; the set of
@@ -151,13 +173,19 @@ struct TemplateUpdate {
     hooks:                 HookCompileTable,
     config_changed:        bool,
     have_reconfigure_hook: bool,
+    have_reload_signal:    bool,
 }
 
 impl TemplateUpdate {
-    fn new(hooks: HookCompileTable, config_changed: bool, have_reconfigure_hook: bool) -> Self {
+    fn new(hooks: HookCompileTable,
+           config_changed: bool,
+           have_reconfigure_hook: bool,
+           have_reload_signal: bool)
+           -> Self {
         Self { hooks,
                config_changed,
-               have_reconfigure_hook }
+               have_reconfigure_hook,
+               have_reload_signal }
     }
 
     /// Returns `true` if the service needs to be restarted.
@@ -165,7 +193,7 @@ impl TemplateUpdate {
     /// A restart is needed under the following conditions:
     /// 1. the `init`, `run` or `post-run` hooks have changed. A restart is limited to these hooks
     /// because they are the only hooks that can impact the execution of the service.
-    /// 2. `/config` changed and there is no `reconfigure` hook
+    /// 2. `/config` changed and there is no `reconfigure` hook or reload signal
     fn needs_restart(&self) -> Option<ProcessTerminationReason> {
         if self.hooks.init_changed() {
             Some(ProcessTerminationReason::InitHookUpdated)
@@ -173,13 +201,18 @@ impl TemplateUpdate {
             Some(ProcessTerminationReason::RunHookUpdated)
         } else if self.hooks.post_run_changed() {
             Some(ProcessTerminationReason::PostRunHookUpdated)
-        } else if !self.have_reconfigure_hook && self.config_changed {
+        } else if !self.have_reconfigure_hook && !self.have_reload_signal && self.config_changed {
             Some(ProcessTerminationReason::AppConfigUpdated)
         } else {
             None
         }
     }
 
+    /// Returns `true` if the service needs to be sent its reload signal.
+    ///
+    /// The signal is sent when `/config` changed, in place of running the `reconfigure` hook.
+    fn needs_reload_signal(&self) -> bool { self.have_reload_signal && self.config_changed }
+
     /// Returns `true` if the service needs to be reconfigured.
     ///
     /// A reconfigure is needed if `/config` or the `reconfigure` hook changed.
@@ -206,6 +239,9 @@ pub enum RestartState {
     Restarting,
     RestartingImmediately,
     Restarted,
+    /// The service exhausted its restart budget and will not be
+    /// restarted until it is reset.
+    Failed,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -224,6 +260,10 @@ pub enum ProcessTerminationReason {
     RunHookUpdated,
     #[serde(rename = "post_run_hook_updated")]
     PostRunHookUpdated,
+    #[serde(rename = "health_check_failed")]
+    HealthCheckFailed,
+    #[serde(rename = "package_rolled_back")]
+    PackageRolledBack,
 }
 
 #[derive(Debug, Clone)]
@@ -263,6 +303,8 @@ pub struct ServiceRunState {
     current_pid:            Option<Pid>,
     restart_state:          RestartState,
     restart_backoff:        Backoff,
+    /// When each restart within the current restart window happened
+    recent_restarts:        VecDeque<SystemTime>,
     last_updated_at:        SystemTime,
 }
 
@@ -276,6 +318,7 @@ impl ServiceRunState {
                           restart_backoff:    Backoff::new(restart_config.min_backoff_period,
                                                            restart_config.max_backoff_period,
                                                            3f64),
+                          recent_restarts:    VecDeque::new(),
                           last_updated_at:    SystemTime::now(), }
     }
 
@@ -301,6 +344,7 @@ impl ServiceRunState {
         // Immediate restarts wipe out the restart out
         self.restart_count = 0;
         self.restart_backoff.reset();
+        self.recent_restarts.clear();
         self.last_updated_at = timestamp;
     }
 
@@ -308,12 +352,56 @@ impl ServiceRunState {
         self.restart_backoff.reset();
         self.last_updated_at = SystemTime::now();
     }
+
+    /// Returns `true` if the service exhausted its restart budget and
+    /// will not be restarted again until it is reset.
+    pub fn is_failed(&self) -> bool { self.restart_state == RestartState::Failed }
+
+    /// Clear the failed state of a service so that it is started
+    /// again. Returns `false` if the service had not failed.
+    pub fn reset(&mut self) -> bool {
+        if !self.is_failed() {
+            return false;
+        }
+        self.restart_state = RestartState::RestartingImmediately;
+        self.restart_count = 0;
+        self.restart_backoff.reset();
+        self.recent_restarts.clear();
+        self.last_updated_at = SystemTime::now();
+        true
+    }
+
+    /// Record a restart at `timestamp` against the restart budget.
+    /// Returns `true` if the budget is exhausted, i.e. there have been
+    /// more than `max_restarts` restarts within the restart window.
+    fn record_restart(&mut self, timestamp: SystemTime) -> bool {
+        let window = self.restart_config.restart_window;
+        self.recent_restarts.retain(|restarted_at| {
+                                timestamp.duration_since(*restarted_at)
+                                         .map_or(true, |elapsed| elapsed < window)
+                            });
+        self.recent_restarts.push_back(timestamp);
+        matches!(self.restart_config.max_restarts,
+                 Some(max_restarts) if self.recent_restarts.len() > max_restarts as usize)
+    }
+}
+
+/// A release the service was just updated to. It is rolled back to
+/// the package the service ran before if the service crash-loops or
+/// keeps failing its health checks while on probation.
+#[derive(Debug, Clone)]
+struct Probation {
+    release:  PackageIdent,
+    previous: PackageIdent,
+    until:    SystemTime,
+    failures: u32,
 }
 
 #[derive(Debug)]
 pub struct PersistentServiceWrapper {
     run_state: ServiceRunState,
     inner:     Option<Service>,
+    probation: Option<Probation>,
 }
 
 impl PersistentServiceWrapper {
@@ -321,7 +409,8 @@ impl PersistentServiceWrapper {
                restart_config: &ServiceRestartConfig)
                -> PersistentServiceWrapper {
         PersistentServiceWrapper { run_state: ServiceRunState::new(restart_config),
-                                   inner:     Some(service), }
+                                   inner:     Some(service),
+                                   probation: None, }
     }
 
     /// Takes ownership of a service from another wrapper
@@ -334,14 +423,73 @@ impl PersistentServiceWrapper {
 
     pub fn service_run_state_mut(&mut self) -> &ServiceRunState { &mut self.run_state }
 
-    /// Mark this service for an immediate restart
-    pub fn mark_for_restart_due_to_update(&mut self, timestamp: SystemTime) {
+    /// Mark this service for an immediate restart to run `release`,
+    /// which is put on probation for the restart window.
+    pub fn mark_for_restart_due_to_update(&mut self, release: PackageIdent, timestamp: SystemTime) {
+        let until = timestamp + self.run_state.restart_config.restart_window;
+        self.probation =
+            self.inner.as_ref().map(|service| {
+                                   Probation { release,
+                                               previous: service.pkg.ident.as_ref().clone(),
+                                               until,
+                                               failures: 0 }
+                               });
         self.run_state
             .mark_for_immediate_restart(self.run_state.current_pid,
                                         ProcessTerminationReason::PackageUpdated,
                                         timestamp);
     }
 
+    /// Mark this service for an immediate restart to roll back a
+    /// release that failed while on probation.
+    pub fn mark_for_restart_due_to_rollback(&mut self, timestamp: SystemTime) {
+        self.probation = None;
+        self.run_state
+            .mark_for_immediate_restart(self.run_state.current_pid,
+                                        ProcessTerminationReason::PackageRolledBack,
+                                        timestamp);
+    }
+
+    /// Check on the release this service was updated to while it is on
+    /// probation. Returns the release and the package to roll back to
+    /// once the service has been restarted after failing, or has
+    /// failed its health checks, `ROLLBACK_FAILURE_THRESHOLD` times in
+    /// a row. The threshold is lowered when the restart budget would
+    /// run out first.
+    ///
+    /// This must be called once for each pending restart, before the
+    /// service is shut down for it.
+    pub fn failed_release(&mut self) -> Option<(PackageIdent, PackageIdent)> {
+        let service = self.inner.as_ref()?;
+        let probation = self.probation.as_mut()?;
+        if SystemTime::now() >= probation.until {
+            debug!("{} is no longer on probation after updating to {}",
+                   service.service_group, probation.release);
+            self.probation = None;
+            return None;
+        }
+        if self.run_state.restart_state == RestartState::NeedsRestart {
+            probation.failures += 1;
+        }
+        let threshold = self.run_state
+                            .restart_config
+                            .max_restarts
+                            .map_or(ROLLBACK_FAILURE_THRESHOLD, |max_restarts| {
+                                ROLLBACK_FAILURE_THRESHOLD.min(max_restarts.saturating_add(1))
+                            });
+        if probation.failures >= threshold || service.critical_streak() >= threshold {
+            self.probation
+                .take()
+                .map(|probation| (probation.release, probation.previous))
+        } else {
+            None
+        }
+    }
+
+    /// Clear the failed state of this service so that it is started
+    /// again. Returns `false` if the service had not failed.
+    pub fn reset(&mut self) -> bool { self.run_state.reset() }
+
     pub fn service(&self) -> Option<&Service> { self.inner.as_ref() }
 
     pub fn service_mut(&mut self) -> Option<&mut Service> { self.inner.as_mut() }
@@ -377,6 +525,7 @@ impl PersistentServiceWrapper {
                 RestartState::Restarted => {
                     panic!("Start called on service which was already restarted")
                 }
+                RestartState::Failed => panic!("Start called on service which has failed"),
             };
         }
     }
@@ -384,10 +533,26 @@ impl PersistentServiceWrapper {
     pub fn shutdown(&mut self, is_restart: bool) -> Option<Service> {
         if let Some(service) = &self.inner {
             if is_restart {
+                let restart_budget_exhausted = self.run_state.restart_state
+                                               == RestartState::NeedsRestart
+                                               && self.run_state.record_restart(SystemTime::now());
                 self.run_state.restart_state = match self.run_state.restart_state {
                     RestartState::None => {
                         panic!("Shutdown called on service which did not need restarting")
                     }
+                    RestartState::NeedsRestart if restart_budget_exhausted => {
+                        // The restart that exhausted the budget is not performed
+                        let restarts = self.run_state.recent_restarts.len() - 1;
+                        let window = self.run_state.restart_config.restart_window;
+                        outputln!(preamble service.service_group,
+                                  "Stopping service, restarted {} times within {} secs, will \
+                                   not restart until reset",
+                                  restarts,
+                                  window.as_secs());
+                        event::service_failed(service, restarts, window);
+                        self.run_state.last_updated_at = SystemTime::now();
+                        RestartState::Failed
+                    }
                     RestartState::NeedsRestart => {
                         let restart_duration = self.run_state
                                                    .restart_backoff
@@ -411,6 +576,9 @@ impl PersistentServiceWrapper {
                     RestartState::Restarted => {
                         panic!("Shutdown called on service not requiring restart")
                     }
+                    RestartState::Failed => {
+                        panic!("Shutdown called on service which has already failed")
+                    }
                 };
             }
             self.inner.take()
@@ -425,7 +593,8 @@ impl PersistentServiceWrapper {
             RestartState::None
             | RestartState::Restarting
             | RestartState::RestartingImmediately
-            | RestartState::Restarted => false,
+            | RestartState::Restarted
+            | RestartState::Failed => false,
         }
     }
 
@@ -479,6 +648,32 @@ pub struct Service {
     // hook, we need to wrap some Arc<Mutex<_>> protection around it
     // :(
     health_check_result:  Arc<Mutex<HealthCheckResult>>,
+    /// Set by the health checks when the service's liveness policy
+    /// calls for a restart, to the time the policy was violated.
+    liveness_failed_at:   Arc<Mutex<Option<SystemTime>>>,
+    /// The number of critical health check results in a row since the
+    /// service's process was started.
+    critical_streak:      Arc<Mutex<u32>>,
+    /// The most recent health check results, and whether they are
+    /// flapping.
+    health_history:       HealthHistory,
+    /// When the service's process was last started. Liveness grace
+    /// periods are measured from this point.
+    started_at:           Instant,
+    /// Set by the health checks to whether the service is accepting
+    /// traffic, as reported by its `ready` hook.
+    ready:                Arc<Mutex<bool>>,
+    /// The readiness included in the last service rumor we gossiped.
+    gossiped_ready:       bool,
+    /// The health included in the last service rumor we gossiped.
+    gossiped_health:      HealthCheckResult,
+    /// Set by `hab svc drain` to take the service out of rotation
+    /// while it keeps running. This is not persisted, so reloading
+    /// the service or restarting the Supervisor clears it.
+    draining:             bool,
+    /// Set if the service runs as a job on a schedule rather than
+    /// being kept running.
+    job:                  Option<JobTracker>,
     last_election_status: ElectionStatus,
     /// The binds that the current service package declares, both
     /// required and optional. We don't differentiate because this is
@@ -499,6 +694,8 @@ pub struct Service {
     /// census.
     unsatisfied_binds:    HashSet<ServiceBind>,
     hooks:                HookTable,
+    /// The most recent runs of each of the service's hooks.
+    hook_history:         HookHistory,
     manager_fs_cfg:       Arc<FsCfg>,
     supervisor:           Arc<Mutex<Supervisor>>,
 
@@ -525,10 +722,53 @@ impl Service {
 
     pub(crate) fn update_condition(&self) -> UpdateCondition { self.spec.update_condition }
 
+    pub(crate) fn canary(&self) -> CanaryPolicy { self.spec.canary }
+
+    pub(crate) fn rollout(&self) -> RolloutPolicy { self.spec.rollout }
+
     pub(crate) fn shutdown_timeout(&self) -> Option<ShutdownTimeout> { self.spec.shutdown_timeout }
 
     pub(crate) fn spec(&self) -> ServiceSpec { self.spec.clone() }
 
+    pub(crate) fn spec_ref(&self) -> &ServiceSpec { &self.spec }
+
+    /// The most recent health check result for this service.
+    pub(crate) fn health_check_result(&self) -> HealthCheckResult {
+        *self.health_check_result
+             .lock()
+             .expect("Could not unlock health_check_result")
+    }
+
+    /// The number of critical health check results in a row since the
+    /// service's process was started.
+    fn critical_streak(&self) -> u32 {
+        *self.critical_streak
+             .lock()
+             .expect("Could not unlock critical_streak")
+    }
+
+    /// Whether the service is currently accepting traffic.
+    pub(crate) fn ready(&self) -> bool { *self.ready.lock().expect("Could not unlock ready") }
+
+    /// Drain or undrain the service. Returns `false` if it already
+    /// was in the requested state.
+    pub(crate) fn set_draining(&mut self, draining: bool) -> bool {
+        if self.draining == draining {
+            return false;
+        }
+        self.draining = draining;
+        true
+    }
+
+    /// Returns when the service's liveness policy was violated, if it
+    /// was since the last call.
+    fn take_liveness_failure(&self) -> Option<SystemTime> {
+        self.liveness_failed_at
+            .lock()
+            .expect("Could not unlock liveness_failed_at")
+            .take()
+    }
+
     pub(crate) fn set_spec(&mut self, spec: ServiceSpec) {
         trace!("Setting spec for {}: {:?}", self.spec.ident, spec);
         self.spec = spec
@@ -551,21 +791,36 @@ impl Service {
         if let Some(timeout) = spec.shutdown_timeout {
             pkg.shutdown_timeout = timeout;
         }
+        pkg.env.extend(spec.env.clone());
+        pkg.hook_timeouts.extend(spec.hook_timeouts.clone());
         let spec_file = manager_fs_cfg.specs_path.join(spec.file());
-        let service_group = ServiceGroup::new(&pkg.name, &spec.group, organization)?;
+        let service_group = ServiceGroup::new(spec.service_name(), &spec.group, organization)?;
         let config_root = Self::config_root(&pkg, spec.config_from.as_ref());
         let hooks_root = Self::hooks_root(&pkg, spec.config_from.as_ref());
         let cfg = Cfg::new(&pkg, spec.config_from.as_ref())?;
+        let job = spec.schedule
+                      .clone()
+                      .map(|schedule| JobTracker::new(schedule, SystemTime::now()));
         let mut service =
             Service { spec,
                       sys,
                       cfg,
                       config_renderer: CfgRenderer::new(config_root)?,
                       health_check_result: Arc::new(Mutex::new(HealthCheckResult::Unknown)),
-                      hooks: HookTable::load(&pkg.name,
+                      liveness_failed_at: Arc::new(Mutex::new(None)),
+                      critical_streak: Arc::new(Mutex::new(0)),
+                      health_history: HealthHistory::default(),
+                      started_at: Instant::now(),
+                      ready: Arc::new(Mutex::new(false)),
+                      gossiped_ready: false,
+                      gossiped_health: HealthCheckResult::Unknown,
+                      draining: false,
+                      job,
+                      hooks: HookTable::load(service_group.service(),
                                              hooks_root,
                                              svc_hooks_path(service_group.service()),
                                              feature_flags),
+                      hook_history: HookHistory::default(),
                       last_election_status: ElectionStatus::None,
                       user_config_updated: false,
                       initialization_state:
@@ -607,7 +862,7 @@ impl Service {
     // the current user.
     #[cfg(windows)]
     async fn resolve_pkg(package: &PackageInstall, spec: &ServiceSpec) -> Result<Pkg> {
-        let mut pkg = Pkg::from_install(package).await?;
+        let mut pkg = Pkg::from_install_for_service(package, &spec.service_name()).await?;
         if spec.svc_encrypted_password.is_none() && pkg.svc_user == DEFAULT_USER {
             if let Some(user) = users::get_current_username()? {
                 pkg.svc_user = user;
@@ -617,8 +872,8 @@ impl Service {
     }
 
     #[cfg(unix)]
-    async fn resolve_pkg(package: &PackageInstall, _spec: &ServiceSpec) -> Result<Pkg> {
-        Ok(Pkg::from_install(package).await?)
+    async fn resolve_pkg(package: &PackageInstall, spec: &ServiceSpec) -> Result<Pkg> {
+        Ok(Pkg::from_install_for_service(package, &spec.service_name()).await?)
     }
 
     /// Returns the config root given the package and optional config-from path.
@@ -662,11 +917,14 @@ impl Service {
     /// Create the service path for this package.
     pub fn create_svc_path(&self) -> Result<()> {
         debug!("{}, Creating svc paths", self.service_group);
-        SvcDir::new(&self.pkg.name, &self.pkg.svc_user, &self.pkg.svc_group).create()?;
+        SvcDir::new(self.service_group.service(),
+                    &self.pkg.svc_user,
+                    &self.pkg.svc_group).create()?;
         Ok(())
     }
 
-    fn start(&mut self, launcher: &LauncherCli) {
+    /// Returns `true` if the service's process was started.
+    fn start(&mut self, launcher: &LauncherCli) -> bool {
         debug!("Starting service {}", self.pkg.ident);
         let result = self.supervisor
                          .lock()
@@ -674,13 +932,24 @@ impl Service {
                          .start(&self.pkg,
                                 &self.service_group,
                                 launcher,
-                                self.spec.svc_encrypted_password.as_deref());
+                                self.spec.svc_encrypted_password.as_deref(),
+                                self.spec.resource_limits,
+                                self.spec.process_controls.or(self.pkg.process_controls),
+                                &self.spec.listen_ports);
         match result {
             Ok(_) => {
-                self.start_health_checks();
+                self.started_at = Instant::now();
+                *self.ready.lock().expect("Could not unlock ready") = false;
+                // A job's run hook runs to completion, so there is
+                // nothing to keep checking the health of
+                if self.job.is_none() {
+                    self.start_health_checks();
+                }
+                true
             }
             Err(e) => {
                 outputln!(preamble self.service_group, "Service start failed: {}", e);
+                false
             }
         }
     }
@@ -693,31 +962,64 @@ impl Service {
     /// appropriate actions upon receiving the results of a health check. The actions taken are:
     ///
     /// * Cache the health check result for this service
+    /// * Record the result in the service's health history, and send a `HealthFlappingEvent` when
+    ///   its health starts or stops flapping
     /// * Set the health check result for this service in the gateway state
     /// * Send a `HealthCheckEvent` over the event stream
+    /// * Request a restart if the service's liveness policy is violated
+    /// * Record whether the service is ready to accept traffic
     fn start_health_checks(&mut self) {
         debug!("Starting health checks for {}", self.pkg.ident);
+        let health_check_hook = self.hooks
+                                    .health_check
+                                    .as_ref()
+                                    .map(|hook| self.hook_runner(hook));
+        let ready_hook = self.hooks.ready.as_ref().map(|hook| self.hook_runner(hook));
+        let probes = self.probe_runner();
         let mut rx = health::check_repeatedly(Arc::clone(&self.supervisor),
-                                              self.hooks.health_check.clone(),
+                                              health_check_hook,
+                                              ready_hook,
+                                              probes,
                                               self.spec.health_check_interval,
                                               self.service_group.clone(),
-                                              self.pkg.clone(),
-                                              self.spec.svc_encrypted_password.clone());
+                                              self.pkg.clone());
 
         let service_group = self.service_group.clone();
         let service_event_metadata = self.to_service_metadata();
         let service_health_result = Arc::clone(&self.health_check_result);
+        let liveness_failed_at = Arc::clone(&self.liveness_failed_at);
+        let critical_streak = Arc::clone(&self.critical_streak);
+        let health_history = self.health_history.clone();
+        let mut liveness = LivenessTracker::new(self.spec.liveness, self.started_at);
+        let service_ready = Arc::clone(&self.ready);
         let gateway_state = Arc::clone(&self.gateway_state);
         let f = async move {
             while let Some(HealthCheckBundle { status,
                                                result,
-                                               interval, }) = rx.recv().await
+                                               interval,
+                                               ready, }) = rx.recv().await
             {
                 debug!("Caching HealthCheckResult = '{}' for '{}'",
                        result, service_group);
                 *service_health_result.lock()
                                       .expect("Could not unlock service_health_result") = result;
 
+                let flapping_change = health_history.record(SystemTime::now(), result, &status);
+                if let Some(change) = flapping_change {
+                    if change.flapping {
+                        outputln!(preamble service_group,
+                                  "Health is flapping, it changed {} times in the last {}s",
+                                  change.transitions,
+                                  HealthHistory::FLAP_WINDOW.as_secs());
+                    } else {
+                        outputln!(preamble service_group, "Health is no longer flapping");
+                    }
+                    event::health_flapping(service_event_metadata.clone(),
+                                           change.flapping,
+                                           change.transitions,
+                                           HealthHistory::FLAP_WINDOW);
+                }
+
                 gateway_state.lock_gsw()
                              .get_services_data_mut()
                              .iter_mut()
@@ -728,6 +1030,32 @@ impl Service {
                              });
 
                 event::health_check(service_event_metadata.clone(), result, status, interval);
+
+                let mut streak = critical_streak.lock()
+                                                .expect("Could not unlock critical_streak");
+                if result == HealthCheckResult::Critical {
+                    *streak += 1;
+                } else {
+                    *streak = 0;
+                }
+
+                if liveness.record(result, Instant::now()) {
+                    outputln!(preamble service_group,
+                              "Health check failed too many times in a row, restarting");
+                    *liveness_failed_at.lock()
+                                       .expect("Could not unlock liveness_failed_at") =
+                        Some(SystemTime::now());
+                }
+
+                let mut was_ready = service_ready.lock().expect("Could not unlock ready");
+                if *was_ready != ready {
+                    if ready {
+                        outputln!(preamble service_group, "Ready to accept traffic");
+                    } else {
+                        outputln!(preamble service_group, "Not ready to accept traffic");
+                    }
+                    *was_ready = ready;
+                }
             }
         };
         let (f, handle) = future::abortable(f);
@@ -763,7 +1091,9 @@ impl Service {
     fn reattach(&mut self) {
         outputln!("Reattaching to {}", self.service_group);
         *self.initialization_state.write() = InitializationState::Initialized;
-        self.restart_health_checks();
+        if self.job.is_none() {
+            self.restart_health_checks();
+        }
         // We intentionally do not restart the `post_run` retry future. Currently, there is not
         // a way to track if `post_run` ran successfully following a Supervisor restart.
         // See https://github.com/habitat-sh/habitat/issues/6739
@@ -788,15 +1118,40 @@ impl Service {
     }
 
     /// Return a future that will shut down a service, performing any
-    /// necessary cleanup, and run its post-stop hook, if any.
+    /// necessary cleanup, and run its pre-stop and post-stop hooks, if
+    /// any.
     /// # Locking for the returned Future (see locking.md)
     /// * `GatewayState::inner` (write)
-    pub async fn stop_gsw(&mut self, shutdown_config: ShutdownConfig) {
+    pub async fn stop_gsw(&mut self, mut shutdown_config: ShutdownConfig) {
         debug!("Stopping service {}", self.pkg.ident);
         self.detach();
 
         let service_group = self.service_group.clone();
 
+        // The pre-stop hook only makes sense while there's a process
+        // for it to prepare for shutdown
+        let running = self.supervisor
+                          .lock()
+                          .expect("Couldn't lock supervisor")
+                          .status()
+                      == ProcessState::Up;
+        if let Some(hook) = self.pre_stop().filter(|_| running) {
+            // The hook and the shutdown signal share the shutdown timeout
+            let timeout = Duration::from(shutdown_config.timeout);
+            let started = Instant::now();
+            if let Err(e) = hook.with_time_limit(timeout).into_future().await {
+                outputln!(preamble service_group, "Pre-stop failed: {}", e);
+            }
+            let elapsed = started.elapsed();
+            if elapsed >= timeout {
+                outputln!(preamble service_group,
+                          "Pre-stop hook killed after {} seconds; stopping the service anyway",
+                          timeout.as_secs());
+            }
+            let remaining = timeout.saturating_sub(elapsed).as_secs();
+            shutdown_config.timeout = u32::try_from(remaining).unwrap_or(u32::MAX).into();
+        }
+
         self.supervisor
             .lock()
             .expect("Couldn't lock supervisor")
@@ -818,9 +1173,15 @@ impl Service {
             .state_entered()
     }
 
+    pub fn histories(&self) -> ServiceHistories {
+        ServiceHistories { health: self.health_history.clone(),
+                           hooks:  self.hook_history.clone(), }
+    }
+
     /// Performs updates and executes hooks.
     ///
-    /// Returns `true` if the service was marked to be restarted or reconfigured.
+    /// Returns `true` if the service was marked to be restarted or reconfigured, or if its
+    /// readiness or health changed, meaning a new service rumor should be gossiped.
     fn tick(&mut self,
             run_state: &mut ServiceRunState,
             census_ring: &CensusRing,
@@ -857,7 +1218,7 @@ impl Service {
         }
 
         match self.spec.topology {
-            Topology::Standalone => self.execute_hooks(run_state, launcher, &template_update),
+            Topology::Standalone => self.execute_hooks(run_state, launcher, &template_update, true),
             Topology::Leader => {
                 let census_group =
                     census_ring.census_group_for(&self.service_group)
@@ -896,12 +1257,21 @@ impl Service {
                                       leader_id);
                             self.last_election_status = census_group.election_status;
                         }
-                        self.execute_hooks(run_state, launcher, &template_update)
+                        let is_leader = census_group.me()
+                                                    .map_or(false, |me| &me.member_id == leader_id);
+                        self.execute_hooks(run_state, launcher, &template_update, is_leader)
                     }
                 }
             }
         };
-        template_data_changed
+
+        let ready = self.ready();
+        let ready_changed = ready != self.gossiped_ready;
+        self.gossiped_ready = ready;
+        let health = self.health_check_result();
+        let health_changed = health != self.gossiped_health;
+        self.gossiped_health = health;
+        template_data_changed || ready_changed || health_changed
     }
 
     /// Iterate through all the service binds, marking any that are
@@ -928,7 +1298,8 @@ impl Service {
                 BindStatus::Empty => {
                     outputln!(preamble self.service_group,
                                   "The specified service group '{}' for binding '{}' is present in the \
-                                   census, but currently has no active members.",
+                                   census, but currently has no active members that haven't been \
+                                   drained.",
                                   bind.service_group(),
                                   bind.name());
                 }
@@ -984,7 +1355,7 @@ impl Service {
         match census_ring.census_group_for(service_bind.service_group()) {
             None => BindStatus::NotPresent,
             Some(group) => {
-                if group.active_members().count() == 0 {
+                if group.serving_members().count() == 0 {
                     BindStatus::Empty
                 } else {
                     match self.unsatisfied_bind_exports(group, service_bind.name()) {
@@ -1088,7 +1459,8 @@ impl Service {
             let ctx = self.render_context(census_ring);
             TemplateUpdate::new(self.compile_hooks(&ctx),
                                 self.compile_configuration(&ctx),
-                                self.hooks.reconfigure.is_some() || self.hooks.reload.is_some())
+                                self.hooks.reconfigure.is_some() || self.hooks.reload.is_some(),
+                                self.reload_signal().is_some())
         } else {
             TemplateUpdate::default()
         };
@@ -1112,6 +1484,9 @@ impl Service {
                                           exported);
         rumor.incarnation = incarnation;
         rumor.pkg_incarnation = pkg_incarnation;
+        rumor.ready = self.ready();
+        rumor.draining = self.draining;
+        rumor.health = Some(self.health_check_result().into());
         rumor
     }
 
@@ -1120,10 +1495,7 @@ impl Service {
         outputln!(preamble self.service_group, "Initializing");
         *self.initialization_state.write() = InitializationState::Initializing;
         if let Some(ref hook) = self.hooks.init {
-            let hook_runner = HookRunner::new(Arc::clone(hook),
-                                              self.service_group.clone(),
-                                              self.pkg.clone(),
-                                              self.spec.svc_encrypted_password.clone());
+            let hook_runner = self.hook_runner(hook);
             // These clones are unfortunate. async/await will make this much better.
             let service_group = self.service_group.clone();
             let initialization_state = Arc::clone(&self.initialization_state);
@@ -1160,20 +1532,12 @@ impl Service {
 
     /// Run reconfigure hook if present.
     fn reconfigure(&mut self) {
-        let _timer = hook_timer("reconfigure");
-
         if let Some(ref hook) = self.hooks.reload {
-            hook.run(&self.service_group,
-                     &self.pkg,
-                     self.spec.svc_encrypted_password.as_ref())
-                .ok();
+            self.hook_runner(hook).run().ok();
         }
 
         if let Some(ref hook) = self.hooks.reconfigure {
-            hook.run(&self.service_group,
-                     &self.pkg,
-                     self.spec.svc_encrypted_password.as_ref())
-                .ok();
+            self.hook_runner(hook).run().ok();
             // The intention here is to do a health check soon after a service's configuration
             // changes, as a way to (among other things) detect potential impacts when bound
             // services change exported configuration.
@@ -1181,12 +1545,35 @@ impl Service {
         }
     }
 
+    /// The signal that makes the service reload its configuration. The one given in the spec
+    /// takes precedence over the package's.
+    fn reload_signal(&self) -> Option<Signal> { self.spec.reload_signal.or(self.pkg.reload_signal) }
+
+    /// Have the Launcher send the service's reload signal to its process. Returns `false` if the
+    /// signal could not be delivered.
+    fn send_reload_signal(&mut self, launcher: &LauncherCli, pid: Pid) -> bool {
+        let signal = match self.reload_signal() {
+            Some(signal) => signal,
+            None => return false,
+        };
+        outputln!(preamble self.service_group, "Sending {} to reload configuration", signal);
+        match launcher.signal(pid, signal) {
+            Ok(()) => {
+                // As with the `reconfigure` hook, check the service's health soon after its
+                // configuration changes
+                self.restart_health_checks();
+                true
+            }
+            Err(err) => {
+                outputln!(preamble self.service_group, "Failed to send {}: {}", signal, err);
+                false
+            }
+        }
+    }
+
     fn post_run(&mut self) {
         if let Some(ref hook) = self.hooks.post_run {
-            let hook_runner = HookRunner::new(Arc::clone(hook),
-                                              self.service_group.clone(),
-                                              self.pkg.clone(),
-                                              self.spec.svc_encrypted_password.clone());
+            let hook_runner = self.hook_runner(hook);
             let f = HookRunner::retryable_future(hook_runner);
             let (f, handle) = future::abortable(f);
             self.post_run_handle = Some(handle);
@@ -1202,32 +1589,61 @@ impl Service {
         }
     }
 
+    fn pre_stop(&self) -> Option<HookRunner<hooks::PreStopHook>> {
+        self.hooks
+            .pre_stop
+            .as_ref()
+            .map(|hook| self.hook_runner(hook))
+    }
+
     fn post_stop(&self) -> Option<HookRunner<hooks::PostStopHook>> {
-        self.hooks.post_stop.as_ref().map(|hook| {
-                                         HookRunner::new(Arc::clone(hook),
-                                                         self.service_group.clone(),
-                                                         self.pkg.clone(),
-                                                         self.spec.svc_encrypted_password.clone())
-                                     })
+        self.hooks
+            .post_stop
+            .as_ref()
+            .map(|hook| self.hook_runner(hook))
     }
 
-    pub fn suitability(&self) -> Option<u64> {
-        let _timer = hook_timer("suitability");
+    /// Prepare to run one of the service's hooks. Runs are recorded in
+    /// the service's hook history.
+    fn hook_runner<H>(&self, hook: &Arc<H>) -> HookRunner<H>
+        where H: Hook + Sync + 'static
+    {
+        HookRunner::new(Arc::clone(hook),
+                        self.service_group.clone(),
+                        self.pkg.clone(),
+                        self.spec.svc_encrypted_password.clone(),
+                        self.hook_history.clone())
+    }
+
+    /// Prepare to run the service's health probes, if it has any. Those
+    /// given in its spec replace those its package declares.
+    fn probe_runner(&self) -> Option<ProbeRunner> {
+        let probes = if self.spec.health_probes.is_empty() {
+            self.pkg.health_probes.clone()
+        } else {
+            self.spec.health_probes.clone()
+        };
+        if probes.is_empty() {
+            None
+        } else {
+            Some(ProbeRunner::new(probes,
+                                  self.service_group.clone(),
+                                  self.pkg.clone(),
+                                  self.spec.svc_encrypted_password.clone()))
+        }
+    }
 
+    pub fn suitability(&self) -> Option<u64> {
         if !self.initialized() {
             return None;
         }
 
-        self.hooks
-            .suitability
-            .as_ref()
-            .and_then(|hook| {
-                hook.run(&self.service_group,
-                         &self.pkg,
-                         self.spec.svc_encrypted_password.as_ref())
-                    .ok()
-            })
-            .unwrap_or(None)
+        self.hooks.suitability.as_ref().and_then(|hook| {
+                                           self.hook_runner(hook)
+                                               .run()
+                                               .ok()
+                                               .and_then(|(suitability, _)| suitability)
+                                       })
     }
 
     /// Helper for compiling configuration templates into configuration files.
@@ -1264,6 +1680,41 @@ impl Service {
         hook_update_table
     }
 
+    /// Renders the service's configuration files and hooks as they would
+    /// be with `gossip` as its gossiped configuration, without writing
+    /// anything. Returns a unified diff of them against the files
+    /// currently rendered, along with any errors rendering them.
+    pub(crate) fn dry_run_cfg(&self,
+                              census_ring: &CensusRing,
+                              gossip: toml::value::Table)
+                              -> habitat_sup_protocol::types::CfgDryRun {
+        let mut cfg = self.cfg.clone();
+        cfg.set_gossip(cfg.gossip_incarnation + 1, gossip);
+        let ctx = self.render_context_with(census_ring, &cfg);
+
+        let rendered = self.config_renderer
+                           .render(&ctx)
+                           .into_iter()
+                           .map(|(file, content)| (self.pkg.svc_config_path.join(file), content))
+                           .chain(self.hooks.render(&ctx));
+        let mut dry_run = habitat_sup_protocol::types::CfgDryRun::default();
+        let mut diff = String::new();
+        for (path, content) in rendered {
+            match content {
+                Ok(content) => {
+                    // A file that hasn't been rendered yet diffs as empty
+                    let current = std::fs::read_to_string(&path).unwrap_or_default();
+                    diff.push_str(&util::diff::unified(&path.display().to_string(),
+                                                       &current,
+                                                       &content));
+                }
+                Err(err) => dry_run.errors.push(format!("{}: {}", path.display(), err)),
+            }
+        }
+        dry_run.diff = Some(diff);
+        dry_run
+    }
+
     // Copy the "run" file to the svc path.
     fn copy_run(&self) -> Result<()> {
         let svc_run = self.pkg.svc_path.join(hooks::RunHook::FILE_NAME);
@@ -1307,7 +1758,8 @@ impl Service {
     fn execute_hooks(&mut self,
                      run_state: &mut ServiceRunState,
                      launcher: &LauncherCli,
-                     template_update: &TemplateUpdate) {
+                     template_update: &TemplateUpdate,
+                     is_leader: bool) {
         let pid_update = self.update_process_state(launcher);
         // We copy the current process id to the run state to avoid
         // having to lock the supervisor for this information.
@@ -1336,10 +1788,19 @@ impl Service {
                 // Wait until the initializer finishes running
             }
             InitializationState::InitializerFinished => {
-                self.start(launcher);
-                self.post_run();
+                // Jobs are started when they come due instead
+                if self.job.is_none() {
+                    self.start(launcher);
+                    self.post_run();
+                }
                 *self.initialization_state.write() = InitializationState::Initialized;
             }
+            InitializationState::Initialized if self.job.is_some() => {
+                // A job's process exiting is expected, and changes
+                // take effect on its next run, so it is never
+                // restarted or reconfigured
+                self.tick_job(launcher, &pid_update, is_leader);
+            }
             InitializationState::Initialized => {
                 let restart_cooldown_period_expired =
                     run_state.restart_backoff
@@ -1355,10 +1816,26 @@ impl Service {
                                                ProcessTerminationReason::RunHookFailed,
                                                pid_update.timestamp.expect("Process update time \
                                                                             should be present"));
+                } else if let Some(failed_at) = self.take_liveness_failure() {
+                    run_state.mark_for_restart(pid_update.new_pid,
+                                               ProcessTerminationReason::HealthCheckFailed,
+                                               failed_at);
                 } else if let Some(termination_reason) = template_update.needs_restart() {
                     run_state.mark_for_immediate_restart(pid_update.new_pid,
                                                          termination_reason,
                                                          SystemTime::now());
+                } else if template_update.needs_reload_signal() {
+                    // Fall back to a restart if the signal couldn't be delivered, such as with
+                    // an older Launcher
+                    let delivered =
+                        pid_update.new_pid
+                                  .map_or(false, |pid| self.send_reload_signal(launcher, pid));
+                    if !delivered {
+                        let reason = ProcessTerminationReason::AppConfigUpdated;
+                        run_state.mark_for_immediate_restart(pid_update.new_pid,
+                                                             reason,
+                                                             SystemTime::now());
+                    }
                 } else if template_update.needs_reconfigure() {
                     // Only reconfigure if we did NOT restart the service
                     self.reconfigure();
@@ -1374,16 +1851,80 @@ impl Service {
         };
     }
 
+    /// Records the end of a job's run and starts the next run when it
+    /// comes due. Runs that come due while the previous run is still
+    /// going are skipped.
+    fn tick_job(&mut self, launcher: &LauncherCli, pid_update: &PidUpdate, is_leader: bool) {
+        let now = SystemTime::now();
+        let (finished, due) = {
+            let job = self.job.as_mut().expect("Job state should be present");
+            let mut finished = None;
+            if pid_update.is_running() {
+                // The run was already going when the Supervisor
+                // restarted, so it is timed from now
+                if !job.is_running() {
+                    job.run_started(now);
+                }
+            } else if job.is_running() {
+                // Older Launchers don't know exit codes
+                let exit_code = match pid_update.old_pid.map(|pid| launcher.exit_code_of(pid)) {
+                    Some(Ok(exit_code)) => exit_code,
+                    Some(Err(err)) => {
+                        debug!("Error getting exit code from launcher: {:#}",
+                               anyhow::anyhow!(err));
+                        None
+                    }
+                    None => None,
+                };
+                finished = job.run_finished(pid_update.timestamp.unwrap_or(now), exit_code)
+                              .cloned();
+            }
+            (finished, job.take_due(now))
+        };
+
+        if let Some(run) = finished {
+            match run.exit_code {
+                Some(code) => {
+                    outputln!(preamble self.service_group,
+                              "Scheduled run finished with exit code {} after {}s",
+                              code,
+                              run.duration)
+                }
+                None => {
+                    outputln!(preamble self.service_group,
+                              "Scheduled run finished after {}s",
+                              run.duration)
+                }
+            }
+            event::service_job_run(self, Duration::from_secs(run.duration), run.exit_code);
+        }
+
+        if !due {
+            return;
+        }
+        if pid_update.is_running() {
+            outputln!(preamble self.service_group,
+                      "Skipping scheduled run; the previous run is still going");
+        } else if self.spec.job_leader_only && !is_leader {
+            debug!("Skipping scheduled run of {}; not the leader",
+                   self.service_group);
+        } else {
+            outputln!(preamble self.service_group, "Starting scheduled run");
+            if self.start(launcher) {
+                if let Some(job) = self.job.as_mut() {
+                    job.run_started(now);
+                }
+            }
+        }
+    }
+
     /// Run file-updated hook if present.
     fn file_updated(&self) -> bool {
-        let _timer = hook_timer("file-updated");
-
         if self.initialized() {
             if let Some(ref hook) = self.hooks.file_updated {
-                return hook.run(&self.service_group,
-                                &self.pkg,
-                                self.spec.svc_encrypted_password.as_ref())
-                           .unwrap_or(false);
+                return self.hook_runner(hook)
+                           .run()
+                           .map_or(false, |(updated, _)| updated);
             }
         }
 
@@ -1447,13 +1988,22 @@ impl Service {
 
     /// Helper for constructing a new render context for the service.
     fn render_context<'a>(&'a self, census: &'a CensusRing) -> RenderContext<'a> {
+        self.render_context_with(census, &self.cfg)
+    }
+
+    /// Like `render_context`, but rendering `cfg` in place of the
+    /// service's configuration.
+    fn render_context_with<'a>(&'a self,
+                               census: &'a CensusRing,
+                               cfg: &'a Cfg)
+                               -> RenderContext<'a> {
         // Unsatisfied binds are filtered out; you only get bind
         // information in the render context if they actually satisfy
         // the contract!
         RenderContext::new(&self.service_group,
                            &self.sys,
                            &self.pkg,
-                           &self.cfg,
+                           cfg,
                            census,
                            self.spec
                                .binds
@@ -1590,6 +2140,7 @@ pub struct ServiceQueryModel {
     pub channel:                ChannelIdent,
     pub config_from:            Option<PathBuf>,
     pub desired_state:          DesiredState,
+    pub draining:               bool,
     pub health_check:           HealthCheckResult,
     pub hooks:                  HookTableQueryModel,
     pub initialized:            bool,
@@ -1611,6 +2162,9 @@ pub struct ServiceQueryModel {
     pub topology:               Topology,
     pub update_strategy:        UpdateStrategy,
     pub update_condition:       UpdateCondition,
+    pub update_hold:            bool,
+    pub update_pin:             Option<VersionConstraint>,
+    pub job:                    Option<JobQueryModel>,
     pub user_config_updated:    bool,
 }
 
@@ -1630,6 +2184,7 @@ impl ServiceQueryModel {
                             channel:                service.spec.channel.clone(),
                             config_from:            service.spec.config_from.clone(),
                             desired_state:          service.spec.desired_state,
+                            draining:               service.draining,
                             health_check:
                                 (*service.health_check_result
                                          .lock()
@@ -1640,13 +2195,7 @@ impl ServiceQueryModel {
                             last_election_status:   service.last_election_status,
                             manager_fs_cfg:         service.manager_fs_cfg.clone(),
                             pkg:                    PkgQueryModel::new(&service.pkg),
-                            process:
-                                SupervisedProcessQueryModel::new(service.supervisor
-                                                                        .lock()
-                                                                        .expect("Couldn't lock \
-                                                                                 supervisor for \
-                                                                                 serialization")
-                                                                        .deref()),
+                            process:                supervised_process(service, service_run_state),
                             last_process_state:     service_run_state.last_process_state.clone(),
                             next_restart_at:
                                 service_run_state.restart_backoff
@@ -1669,16 +2218,51 @@ impl ServiceQueryModel {
                             topology:               service.spec.topology,
                             update_strategy:        service.spec.update_strategy,
                             update_condition:       service.spec.update_condition,
+                            update_hold:            service.spec.update_hold,
+                            update_pin:             service.spec.update_pin.clone(),
+                            job:                    service.job.as_ref().map(JobQueryModel::new),
                             user_config_updated:    service.user_config_updated, }
     }
 }
 
+/// The recent health checks and hook runs of a service. They are served
+/// by endpoints of their own rather than with the rest of its query
+/// model. Clones share the service's histories, so they stay current.
+#[derive(Debug, Clone)]
+pub struct ServiceHistories {
+    pub health: HealthHistory,
+    pub hooks:  HookHistory,
+}
+
+/// The service's process as shown by the query model. A failed service
+/// has no process of its own, so its state comes from the run state
+/// instead.
+fn supervised_process(service: &Service,
+                      service_run_state: &ServiceRunState)
+                      -> SupervisedProcessQueryModel {
+    let mut process = SupervisedProcessQueryModel::new(service.supervisor
+                                                              .lock()
+                                                              .expect("Couldn't lock supervisor \
+                                                                       for serialization")
+                                                              .deref());
+    if service_run_state.is_failed() {
+        process.state = ProcessState::Failed;
+        process.state_entered =
+            UnixTimestamp::try_from(service_run_state.last_updated_at).map(|timestamp| timestamp.0)
+                                                                      .unwrap_or_default();
+    }
+    process
+}
+
 impl From<&ServiceQueryModel> for habitat_sup_protocol::types::ServiceStatus {
     fn from(service: &ServiceQueryModel) -> Self {
         Self { ident:         (*service.pkg.ident.as_ref()).clone().into(),
                process:       Some((&service.process).into()),
                service_group: service.service_group.clone().into(),
-               desired_state: Some(service.desired_state.into()), }
+               desired_state: Some(service.desired_state.into()),
+               update_hold:   Some(service.update_hold),
+               update_pin:    service.update_pin.as_ref().map(ToString::to_string),
+               job:           service.job.as_ref().map(Into::into), }
     }
 }
 
@@ -1768,4 +2352,93 @@ mod tests {
                                                                    JSON but failed");
         assert_valid(&json_without_config, "http_gateway_services_schema.json");
     }
+
+    #[tokio::test]
+    async fn failed_release_is_rolled_back_after_repeated_failures() {
+        let mut service_wrapper = initialize_test_service().await;
+        let previous = service_wrapper.service()
+                                      .unwrap()
+                                      .pkg
+                                      .ident
+                                      .as_ref()
+                                      .clone();
+        let release = PackageIdent::new(previous.origin.as_str(),
+                                        previous.name.as_str(),
+                                        Some("99.0.0"),
+                                        Some("20990101000000"));
+
+        service_wrapper.mark_for_restart_due_to_update(release.clone(), SystemTime::now());
+        // The restart for the update itself does not count as a failure
+        assert!(service_wrapper.failed_release().is_none());
+        for _ in 0..2 {
+            service_wrapper.run_state.mark_for_restart(None,
+                                                       ProcessTerminationReason::RunHookFailed,
+                                                       SystemTime::now());
+            assert!(service_wrapper.failed_release().is_none());
+        }
+        service_wrapper.run_state.mark_for_restart(None,
+                                                   ProcessTerminationReason::RunHookFailed,
+                                                   SystemTime::now());
+        assert_eq!(service_wrapper.failed_release(), Some((release, previous)));
+        // The release is no longer on probation once it is rolled back
+        assert!(service_wrapper.failed_release().is_none());
+    }
+
+    #[tokio::test]
+    async fn release_is_not_rolled_back_after_probation() {
+        let mut service_wrapper = initialize_test_service().await;
+        let release = service_wrapper.service()
+                                     .unwrap()
+                                     .pkg
+                                     .ident
+                                     .as_ref()
+                                     .clone();
+        // A single failure is enough to roll back while on probation
+        service_wrapper.run_state.restart_config.max_restarts = Some(0);
+
+        let updated_at = SystemTime::now() - Duration::from_secs(3600);
+        service_wrapper.mark_for_restart_due_to_update(release, updated_at);
+        service_wrapper.run_state.mark_for_restart(None,
+                                                   ProcessTerminationReason::RunHookFailed,
+                                                   SystemTime::now());
+        assert!(service_wrapper.failed_release().is_none());
+    }
+
+    #[test]
+    fn restart_budget_is_exhausted_after_max_restarts_within_window() {
+        let restart_config = ServiceRestartConfig { max_restarts: Some(2),
+                                                    restart_window: Duration::from_secs(60),
+                                                    ..Default::default() };
+        let mut run_state = ServiceRunState::new(&restart_config);
+        let start = SystemTime::now();
+
+        assert!(!run_state.record_restart(start));
+        assert!(!run_state.record_restart(start + Duration::from_secs(10)));
+        // The first restart has dropped out of the window
+        assert!(!run_state.record_restart(start + Duration::from_secs(65)));
+        assert!(run_state.record_restart(start + Duration::from_secs(70)));
+    }
+
+    #[test]
+    fn restart_budget_is_unlimited_by_default() {
+        let mut run_state = ServiceRunState::new(&ServiceRestartConfig::default());
+        let start = SystemTime::now();
+
+        for secs in 0..100 {
+            assert!(!run_state.record_restart(start + Duration::from_secs(secs)));
+        }
+    }
+
+    #[test]
+    fn only_failed_services_can_be_reset() {
+        let mut run_state = ServiceRunState::new(&ServiceRestartConfig::default());
+        assert!(!run_state.reset());
+
+        run_state.restart_state = RestartState::Failed;
+        run_state.restart_count = 3;
+        assert!(run_state.reset());
+        assert!(!run_state.is_failed());
+        assert_eq!(run_state.restart_state, RestartState::RestartingImmediately);
+        assert_eq!(run_state.restart_count, 0);
+    }
 }
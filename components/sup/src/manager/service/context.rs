@@ -351,9 +351,9 @@ struct BindGroup<'a> {
 
 impl<'a> BindGroup<'a> {
     fn new(group: &'a CensusGroup) -> Self {
-        BindGroup { first:   select_first(group),
+        BindGroup { first:   select_first_serving(group),
                     leader:  group.leader().map(SvcMember::new),
-                    members: group.active_members().map(SvcMember::new).collect(), }
+                    members: group.serving_members().map(SvcMember::new).collect(), }
     }
 }
 
@@ -377,13 +377,28 @@ fn select_first(census_group: &CensusGroup) -> Option<SvcMember<'_>> {
     }
 }
 
+/// Same as `select_first`, but never picks a member that has been
+/// drained, so that `bind.<name>.first` agrees with
+/// `bind.<name>.members`.
+fn select_first_serving(census_group: &CensusGroup) -> Option<SvcMember<'_>> {
+    match census_group.leader().filter(|member| !member.draining) {
+        Some(member) => Some(SvcMember::new(member)),
+        None => {
+            census_group.members()
+                        .find(|member| !member.draining)
+                        .map(SvcMember::new)
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{census::CensusMember,
-                manager::service::Cfg,
+                manager::service::{Cfg,
+                                   HealthCheckResult},
                 test_helpers::*};
     use habitat_butterfly::rumor::service::SysInfo;
     use habitat_common::templating::{config::PackageConfigPaths,
@@ -478,6 +493,9 @@ two = 2
                                            suspect: false,
                                            confirmed: false,
                                            departed: false,
+                                           ready: true,
+                                           draining: false,
+                                           health: Some(HealthCheckResult::Ok),
                                            cfg: toml::value::Table::new(), };
         SvcMember::new_owned(census_member)
     }
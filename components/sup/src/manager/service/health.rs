@@ -1,12 +1,16 @@
 use crate::{error::Error,
-            manager::service::{hook_runner,
-                               hooks::HealthCheckHook,
+            manager::service::{hook_runner::HookRunner,
+                               hooks::{HealthCheckHook,
+                                       ReadyHook},
+                               probe_runner::ProbeRunner,
                                supervisor::Supervisor,
                                ProcessOutput,
                                ProcessState}};
+use habitat_butterfly::rumor::service::Health as HealthRumor;
 use habitat_common::{outputln,
                      templating::package::Pkg};
 use habitat_core::service::{HealthCheckInterval,
+                            LivenessPolicy,
                             ServiceGroup};
 use log::{debug,
           error,
@@ -18,7 +22,8 @@ use std::{cmp,
           fmt,
           sync::{Arc,
                  Mutex},
-          time::Duration};
+          time::{Duration,
+                 Instant}};
 use tokio::{sync::mpsc::{self,
                          UnboundedReceiver},
             time};
@@ -49,6 +54,32 @@ impl TryFrom<i32> for HealthCheckResult {
     }
 }
 
+impl Default for HealthCheckResult {
+    fn default() -> Self { HealthCheckResult::Unknown }
+}
+
+impl From<HealthCheckResult> for HealthRumor {
+    fn from(result: HealthCheckResult) -> Self {
+        match result {
+            HealthCheckResult::Ok => HealthRumor::Ok,
+            HealthCheckResult::Warning => HealthRumor::Warning,
+            HealthCheckResult::Critical => HealthRumor::Critical,
+            HealthCheckResult::Unknown => HealthRumor::Unknown,
+        }
+    }
+}
+
+impl From<HealthRumor> for HealthCheckResult {
+    fn from(health: HealthRumor) -> Self {
+        match health {
+            HealthRumor::Ok => HealthCheckResult::Ok,
+            HealthRumor::Warning => HealthCheckResult::Warning,
+            HealthRumor::Critical => HealthCheckResult::Critical,
+            HealthRumor::Unknown => HealthCheckResult::Unknown,
+        }
+    }
+}
+
 impl fmt::Display for HealthCheckResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let msg = match *self {
@@ -67,11 +98,14 @@ pub enum HealthCheckHookStatus {
     FailedToRun(Duration),
     FailedToStart,
     NoHook,
+    /// The service's health probes ran instead of a hook.
+    Probed(Duration),
 }
 
 impl HealthCheckHookStatus {
     pub fn maybe_duration(&self) -> Option<Duration> {
-        if let Self::Ran(_, duration) | Self::FailedToRun(duration) = self {
+        if let Self::Ran(_, duration) | Self::FailedToRun(duration) | Self::Probed(duration) = self
+        {
             Some(*duration)
         } else {
             None
@@ -93,25 +127,72 @@ impl HealthCheckHookStatus {
 /// `result` is a computed value from `status` and other conditions (eg supervisor status if there
 /// is not a health check hook)
 /// `interval` the computed interval to wait until running the next health check
+/// `ready` whether the service is accepting traffic (see `check_ready`)
 pub struct HealthCheckBundle {
     pub status:   HealthCheckHookStatus,
     pub result:   HealthCheckResult,
     pub interval: HealthCheckInterval,
+    pub ready:    bool,
 }
 
-/// Run the health check hook and get the hook status and result.
+/// Counts consecutive `Critical` health check results against a
+/// service's `LivenessPolicy` to decide when it should be restarted.
+#[derive(Debug)]
+pub struct LivenessTracker {
+    failure_threshold:    Option<u32>,
+    grace_period_ends:    Instant,
+    consecutive_critical: u32,
+}
+
+impl LivenessTracker {
+    /// Create a tracker for a service whose process started at `started_at`.
+    pub fn new(policy: LivenessPolicy, started_at: Instant) -> Self {
+        Self { failure_threshold:    policy.failure_threshold,
+               grace_period_ends:    started_at + policy.grace_period(),
+               consecutive_critical: 0, }
+    }
+
+    /// Record a health check result received at `now`. Returns `true`
+    /// if the policy's threshold was reached and the service should be
+    /// restarted.
+    pub fn record(&mut self, result: HealthCheckResult, now: Instant) -> bool {
+        let threshold = match self.failure_threshold {
+            Some(threshold) if threshold > 0 => threshold,
+            _ => return false,
+        };
+        if now < self.grace_period_ends {
+            return false;
+        }
+        if result == HealthCheckResult::Critical {
+            self.consecutive_critical += 1;
+        } else {
+            self.consecutive_critical = 0;
+        }
+        if self.consecutive_critical >= threshold {
+            self.consecutive_critical = 0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Run the health check hook and get the hook status and result. If the service has health
+/// probes they are run instead of the hook.
 async fn check(supervisor: Arc<Mutex<Supervisor>>,
-               hook: Option<Arc<HealthCheckHook>>,
+               hook: Option<HookRunner<HealthCheckHook>>,
+               probes: Option<ProbeRunner>,
                service_group: ServiceGroup,
-               package: Pkg,
-               password: Option<String>)
+               package: Pkg)
                -> (HealthCheckHookStatus, HealthCheckResult) {
+    if let Some(probes) = probes {
+        let started = Instant::now();
+        let result = probes.run().await;
+        return (HealthCheckHookStatus::Probed(started.elapsed()), result);
+    }
+
     let status = if let Some(hook) = hook {
-        let result = hook_runner::HookRunner::new(hook,
-                                                  service_group.clone(),
-                                                  package.clone(),
-                                                  password).into_future()
-                                                           .await;
+        let result = hook.into_future().await;
         match result {
             Ok((output, duration)) => HealthCheckHookStatus::Ran(output, duration),
             Err(Error::WithDuration(e, duration)) => {
@@ -151,6 +232,7 @@ async fn check(supervisor: Arc<Mutex<Supervisor>>,
             // unknown.
             HealthCheckResult::Unknown
         }
+        HealthCheckHookStatus::Probed(_) => unreachable!("probes are handled above"),
         HealthCheckHookStatus::NoHook => {
             //  There was no hook to run. Use the supervisor status as a healthcheck.
             match supervisor.lock()
@@ -158,7 +240,7 @@ async fn check(supervisor: Arc<Mutex<Supervisor>>,
                             .status()
             {
                 ProcessState::Up => HealthCheckResult::Ok,
-                ProcessState::Down => HealthCheckResult::Critical,
+                ProcessState::Down | ProcessState::Failed => HealthCheckResult::Critical,
             }
         }
     };
@@ -166,16 +248,44 @@ async fn check(supervisor: Arc<Mutex<Supervisor>>,
     (status, result)
 }
 
+/// Run the ready hook to find out whether the service is accepting traffic. A service whose
+/// process is not running is never ready, and one without a ready hook is ready as long as its
+/// process is running.
+async fn check_ready(supervisor: Arc<Mutex<Supervisor>>,
+                     hook: Option<HookRunner<ReadyHook>>,
+                     service_group: ServiceGroup)
+                     -> bool {
+    let status = supervisor.lock()
+                           .expect("couldn't unlock supervisor")
+                           .status();
+    if status != ProcessState::Up {
+        return false;
+    }
+    match hook {
+        Some(hook) => {
+            match hook.into_future().await {
+                Ok((ready, _)) => ready,
+                Err(e) => {
+                    error!("Error running ready hook for {}: {:?}", service_group, e);
+                    false
+                }
+            }
+        }
+        None => true,
+    }
+}
+
 /// Start a task to repeatedly check the service health, followed by an appropriate delay, forever.
 /// The function returns the receiving end of a channel that acts as a stream of
 /// `HealthCheckBundle`s. When this receiving end is dropped or closed health checking will be
 /// stopped.
 pub fn check_repeatedly(supervisor: Arc<Mutex<Supervisor>>,
-                        hook: Option<Arc<HealthCheckHook>>,
+                        hook: Option<HookRunner<HealthCheckHook>>,
+                        ready_hook: Option<HookRunner<ReadyHook>>,
+                        probes: Option<ProbeRunner>,
                         nominal_interval: HealthCheckInterval,
                         service_group: ServiceGroup,
-                        package: Pkg,
-                        password: Option<String>)
+                        package: Pkg)
                         -> UnboundedReceiver<HealthCheckBundle> {
     // TODO (CM): If we wanted to keep track of how many times
     // a health check has failed in the past X executions, or
@@ -189,10 +299,13 @@ pub fn check_repeatedly(supervisor: Arc<Mutex<Supervisor>>,
         let mut first_ok_health_check_recorded = false;
         loop {
             let (status, result) = check(Arc::clone(&supervisor),
-                                         hook.as_ref().map(Arc::clone),
+                                         hook.clone(),
+                                         probes.clone(),
                                          service_group.clone(),
-                                         package.clone(),
-                                         password.clone()).await;
+                                         package.clone()).await;
+            let ready = check_ready(Arc::clone(&supervisor),
+                                    ready_hook.clone(),
+                                    service_group.clone()).await;
 
             let interval = if result == HealthCheckResult::Ok {
                 if !first_ok_health_check_recorded {
@@ -223,7 +336,8 @@ pub fn check_repeatedly(supervisor: Arc<Mutex<Supervisor>>,
             // executing health checks.
             if tx.send(HealthCheckBundle { status,
                                            result,
-                                           interval })
+                                           interval,
+                                           ready })
                  .is_err()
             {
                 break;
@@ -240,3 +354,49 @@ pub fn check_repeatedly(supervisor: Arc<Mutex<Supervisor>>,
 
     rx
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn liveness_tracker_restarts_after_consecutive_critical_results() {
+        let started_at = Instant::now();
+        let policy = LivenessPolicy { failure_threshold: Some(3),
+                                      grace_period:      None, };
+        let mut tracker = LivenessTracker::new(policy, started_at);
+
+        assert!(!tracker.record(HealthCheckResult::Critical, started_at));
+        assert!(!tracker.record(HealthCheckResult::Critical, started_at));
+        // Any other result breaks the streak
+        assert!(!tracker.record(HealthCheckResult::Warning, started_at));
+        assert!(!tracker.record(HealthCheckResult::Critical, started_at));
+        assert!(!tracker.record(HealthCheckResult::Critical, started_at));
+        assert!(tracker.record(HealthCheckResult::Critical, started_at));
+        // The count starts over after a restart was requested
+        assert!(!tracker.record(HealthCheckResult::Critical, started_at));
+    }
+
+    #[test]
+    fn liveness_tracker_ignores_results_during_grace_period() {
+        let started_at = Instant::now();
+        let policy = LivenessPolicy { failure_threshold: Some(1),
+                                      grace_period:      Some(60), };
+        let mut tracker = LivenessTracker::new(policy, started_at);
+
+        assert!(!tracker.record(HealthCheckResult::Critical,
+                                started_at + Duration::from_secs(59)));
+        assert!(tracker.record(HealthCheckResult::Critical,
+                               started_at + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn liveness_tracker_never_restarts_without_threshold() {
+        let started_at = Instant::now();
+        let mut tracker = LivenessTracker::new(LivenessPolicy::default(), started_at);
+
+        for _ in 0..10 {
+            assert!(!tracker.record(HealthCheckResult::Critical, started_at));
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use super::health::{HealthCheckHookStatus,
+                    HealthCheckResult};
+use crate::util::history::BoundedHistory;
+use serde::Serialize;
+use std::{collections::VecDeque,
+          sync::{Arc,
+                 Mutex},
+          time::{Duration,
+                 SystemTime}};
+
+/// A finished health check of a service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthCheckRecord {
+    /// When the check finished, in seconds since the epoch.
+    pub checked_at:  u64,
+    pub result:      HealthCheckResult,
+    /// How long the health check hook or health probes took, in
+    /// milliseconds. Unset if neither ran.
+    pub duration_ms: Option<u64>,
+    /// The health check hook's exit code, if it ran and wasn't
+    /// terminated by a signal.
+    pub exit_code:   Option<i32>,
+    /// The start of what the hook wrote to standard output, up to
+    /// `HAB_HOOK_STANDARD_STREAM_BYTE_LIMIT` bytes.
+    pub stdout:      String,
+    /// The start of what the hook wrote to standard error, likewise.
+    pub stderr:      String,
+}
+
+impl HealthCheckRecord {
+    fn new(checked_at: u64, result: HealthCheckResult, status: &HealthCheckHookStatus) -> Self {
+        let (exit_code, stdout, stderr) = match status {
+            HealthCheckHookStatus::Ran(output, _) => {
+                (output.exit_status().code(),
+                 output.stdout().unwrap_or_default().to_string(),
+                 output.stderr().unwrap_or_default().to_string())
+            }
+            _ => (None, String::new(), String::new()),
+        };
+        HealthCheckRecord { checked_at,
+                            result,
+                            duration_ms: status.maybe_duration()
+                                               .map(|duration| duration.as_millis() as u64),
+                            exit_code,
+                            stdout,
+                            stderr }
+    }
+
+    /// The record as a check for the control gateway.
+    pub fn to_health_check(&self) -> habitat_sup_protocol::types::HealthCheck {
+        habitat_sup_protocol::types::HealthCheck { checked_at:  self.checked_at as i64,
+                                                   result:      self.result.to_string(),
+                                                   duration_ms: self.duration_ms,
+                                                   exit_code:   self.exit_code,
+                                                   stdout:      Some(self.stdout.clone()),
+                                                   stderr:      Some(self.stderr.clone()), }
+    }
+}
+
+/// A change in whether a service's health is flapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlappingChange {
+    pub flapping:    bool,
+    /// How many times the health check result changed within
+    /// `HealthHistory::FLAP_WINDOW`.
+    pub transitions: usize,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    checks:      BoundedHistory<HealthCheckRecord, { HealthHistory::MAX_CHECKS }>,
+    /// When the result changed within the flapping window, oldest
+    /// first, in seconds since the epoch.
+    transitions: VecDeque<u64>,
+    flapping:    bool,
+}
+
+/// The recent health checks of a service, along with whether its health
+/// is flapping, that is changing too often to be trusted. Clones share
+/// the same history, so the health check task can hold one.
+#[derive(Debug, Clone, Default)]
+pub struct HealthHistory(Arc<Mutex<Inner>>);
+
+impl HealthHistory {
+    /// How many changes of the result within `FLAP_WINDOW` make a
+    /// service's health flapping. It stops flapping once there are
+    /// fewer.
+    ///
+    /// This and `FLAP_WINDOW` are deliberately not configurable, so
+    /// that flapping means the same thing for every service and
+    /// whatever consumes the `HealthFlappingEvent`s or the health
+    /// endpoint doesn't need to know how each service was loaded.
+    const FLAP_THRESHOLD: usize = 4;
+    /// How far back changes of the result count toward flapping.
+    pub const FLAP_WINDOW: Duration = Duration::from_secs(10 * 60);
+    /// How many checks are kept.
+    const MAX_CHECKS: usize = 20;
+
+    /// Records a check that finished at `checked_at`. Returns the
+    /// change if the service's health started or stopped flapping.
+    pub fn record(&self,
+                  checked_at: SystemTime,
+                  result: HealthCheckResult,
+                  status: &HealthCheckHookStatus)
+                  -> Option<FlappingChange> {
+        let checked_at = checked_at.duration_since(SystemTime::UNIX_EPOCH)
+                                   .unwrap_or_default()
+                                   .as_secs();
+        self.push(HealthCheckRecord::new(checked_at, result, status))
+    }
+
+    fn push(&self, record: HealthCheckRecord) -> Option<FlappingChange> {
+        let mut inner = self.0.lock().expect("Could not unlock health history");
+        let checked_at = record.checked_at;
+        if matches!(inner.checks.latest(), Some(last) if last.result != record.result) {
+            inner.transitions.push_back(checked_at);
+        }
+        inner.checks.push(record);
+
+        let window_start = checked_at.saturating_sub(Self::FLAP_WINDOW.as_secs());
+        while matches!(inner.transitions.front(), Some(at) if *at < window_start) {
+            inner.transitions.pop_front();
+        }
+        let transitions = inner.transitions.len();
+        let flapping = transitions >= Self::FLAP_THRESHOLD;
+        if flapping == inner.flapping {
+            None
+        } else {
+            inner.flapping = flapping;
+            Some(FlappingChange { flapping,
+                                  transitions })
+        }
+    }
+
+    /// The recorded checks, most recent first.
+    pub fn checks(&self) -> Vec<HealthCheckRecord> {
+        self.0
+            .lock()
+            .expect("Could not unlock health history")
+            .checks
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    pub fn is_flapping(&self) -> bool {
+        self.0
+            .lock()
+            .expect("Could not unlock health history")
+            .flapping
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(checked_at: u64, result: HealthCheckResult) -> HealthCheckRecord {
+        HealthCheckRecord { checked_at,
+                            result,
+                            duration_ms: Some(20),
+                            exit_code: None,
+                            stdout: String::new(),
+                            stderr: String::new() }
+    }
+
+    #[test]
+    fn health_history_keeps_the_most_recent_checks() {
+        let history = HealthHistory::default();
+        for checked_at in 0..30 {
+            history.push(record(checked_at, HealthCheckResult::Ok));
+        }
+
+        let checks = history.clone().checks();
+        assert_eq!(checks.len(), HealthHistory::MAX_CHECKS);
+        assert_eq!(checks[0], record(29, HealthCheckResult::Ok));
+    }
+
+    #[test]
+    fn health_history_detects_flapping() {
+        let history = HealthHistory::default();
+        assert_eq!(history.push(record(0, HealthCheckResult::Ok)), None);
+        assert_eq!(history.push(record(30, HealthCheckResult::Critical)), None);
+        assert_eq!(history.push(record(60, HealthCheckResult::Critical)), None);
+        assert_eq!(history.push(record(90, HealthCheckResult::Ok)), None);
+        assert_eq!(history.push(record(120, HealthCheckResult::Warning)), None);
+        assert!(!history.is_flapping());
+        assert_eq!(history.push(record(150, HealthCheckResult::Ok)),
+                   Some(FlappingChange { flapping:    true,
+                                         transitions: 4, }));
+        assert!(history.is_flapping());
+
+        // Still flapping while the changes are within the window
+        assert_eq!(history.push(record(600, HealthCheckResult::Ok)), None);
+        // Until the oldest of them falls out of it
+        assert_eq!(history.push(record(631, HealthCheckResult::Ok)),
+                   Some(FlappingChange { flapping:    false,
+                                         transitions: 3, }));
+        assert!(!history.is_flapping());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::util::history::BoundedHistory;
+use habitat_common::templating::hooks::{Hook,
+                                        HookOutput,
+                                        HookRun};
+use serde::Serialize;
+use std::{collections::BTreeMap,
+          sync::{Arc,
+                 Mutex},
+          time::{Duration,
+                 SystemTime}};
+
+/// A finished run of one of a service's hooks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HookRunRecord {
+    /// When the run started, in seconds since the epoch.
+    pub started_at:  u64,
+    /// How long the run took, in milliseconds.
+    pub duration_ms: u64,
+    /// The hook's exit code, if it wasn't terminated by a signal.
+    pub exit_code:   Option<i32>,
+    /// Set if the hook was killed for running past its timeout.
+    pub timed_out:   bool,
+    /// The start of what the hook wrote to standard output, up to
+    /// `HAB_HOOK_STANDARD_STREAM_BYTE_LIMIT` bytes.
+    pub stdout:      String,
+    /// The start of what the hook wrote to standard error, likewise.
+    pub stderr:      String,
+}
+
+impl HookRunRecord {
+    /// The record as a run of `hook` for the control gateway.
+    pub fn to_hook_run(&self, hook: &str) -> habitat_sup_protocol::types::HookRun {
+        habitat_sup_protocol::types::HookRun { hook:        hook.to_string(),
+                                               started_at:  self.started_at as i64,
+                                               duration_ms: self.duration_ms,
+                                               exit_code:   self.exit_code,
+                                               timed_out:   self.timed_out,
+                                               stdout:      Some(self.stdout.clone()),
+                                               stderr:      Some(self.stderr.clone()), }
+    }
+}
+
+type Runs = BoundedHistory<HookRunRecord, { HookHistory::MAX_RUNS }>;
+
+/// The recent runs of each of a service's hooks, keyed by the hook's
+/// file name. Clones share the same history, so the tasks running the
+/// hooks can each hold one.
+#[derive(Debug, Clone, Default)]
+pub struct HookHistory(Arc<Mutex<BTreeMap<&'static str, Runs>>>);
+
+impl HookHistory {
+    /// How many finished runs of each hook are kept.
+    const MAX_RUNS: usize = 10;
+
+    /// Records a run of `hook` that started at `started_at` and took
+    /// `duration`, along with the output it left in its log files.
+    pub fn record<H: Hook>(&self,
+                           hook: &H,
+                           started_at: SystemTime,
+                           duration: Duration,
+                           run: &HookRun<H::ExitValue>) {
+        let output = HookOutput::new(hook.stdout_log_path(), hook.stderr_log_path());
+        let record = HookRunRecord { started_at:  started_at.duration_since(SystemTime::UNIX_EPOCH)
+                                                            .unwrap_or_default()
+                                                            .as_secs(),
+                                     duration_ms: duration.as_millis() as u64,
+                                     exit_code:   run.exit_code,
+                                     timed_out:   run.timed_out,
+                                     stdout:      output.stdout_str().unwrap_or_default(),
+                                     stderr:      output.stderr_str().unwrap_or_default(), };
+        self.push(H::FILE_NAME, record);
+    }
+
+    fn push(&self, hook: &'static str, record: HookRunRecord) {
+        let mut history = self.0.lock().expect("Could not unlock hook history");
+        history.entry(hook).or_default().push(record);
+    }
+
+    /// The recorded runs of each hook that has run, most recent first.
+    pub fn runs(&self) -> BTreeMap<String, Vec<HookRunRecord>> {
+        self.0
+            .lock()
+            .expect("Could not unlock hook history")
+            .iter()
+            .map(|(hook, runs)| (hook.to_string(), runs.iter().cloned().collect()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(started_at: u64) -> HookRunRecord {
+        HookRunRecord { started_at,
+                        duration_ms: 20,
+                        exit_code: Some(0),
+                        timed_out: false,
+                        stdout: String::new(),
+                        stderr: String::new() }
+    }
+
+    #[test]
+    fn hook_history_keeps_the_most_recent_runs_of_each_hook() {
+        let history = HookHistory::default();
+        for started_at in 0..15 {
+            history.push("reconfigure", record(started_at));
+        }
+        history.clone().push("init", record(100));
+
+        let runs = history.runs();
+        assert_eq!(runs.keys().collect::<Vec<_>>(), vec!["init", "reconfigure"]);
+        assert_eq!(runs["init"], vec![record(100)]);
+        assert_eq!(runs["reconfigure"].len(), HookHistory::MAX_RUNS);
+        assert_eq!(runs["reconfigure"][0].started_at, 14);
+    }
+}
@@ -7,8 +7,12 @@
 //! executed. If that were to be re-cast in terms of Rust's
 //! `std::process::Command`, we could consider it. In the meantime,
 //! this seems to do the trick.
+//!
+//! Every run is recorded in the service's hook history, and a hook
+//! still running after the timeout its service gives it is killed.
 
-use super::{hook_timer,
+use super::{hook_history::HookHistory,
+            hook_timer,
             Pkg};
 use crate::error::{Error,
                    Result};
@@ -19,7 +23,8 @@ use log::{debug,
 use std::{clone::Clone,
           sync::Arc,
           time::{Duration,
-                 Instant}};
+                 Instant,
+                 SystemTime}};
 use tokio::task;
 
 pub struct HookRunner<H: Hook + Sync> {
@@ -27,6 +32,9 @@ pub struct HookRunner<H: Hook + Sync> {
     service_group: ServiceGroup,
     pkg:           Pkg,
     passwd:        Option<String>,
+    history:       HookHistory,
+    /// The longest the hook may run for this run, on top of its package's timeout.
+    time_limit:    Option<Duration>,
 }
 
 // We cannot use `#[derive(Clone)]` here because it unnecessarily requires `H` to be
@@ -36,7 +44,9 @@ impl<H: Hook + Sync> Clone for HookRunner<H> {
         Self { hook:          self.hook.clone(),
                service_group: self.service_group.clone(),
                pkg:           self.pkg.clone(),
-               passwd:        self.passwd.clone(), }
+               passwd:        self.passwd.clone(),
+               history:       self.history.clone(),
+               time_limit:    self.time_limit, }
     }
 }
 
@@ -45,12 +55,35 @@ impl<H> HookRunner<H> where H: Hook + Sync + 'static
     pub fn new(hook: Arc<H>,
                service_group: ServiceGroup,
                pkg: Pkg,
-               passwd: Option<String>)
+               passwd: Option<String>,
+               history: HookHistory)
                -> HookRunner<H> {
         HookRunner { hook,
                      service_group,
                      pkg,
-                     passwd }
+                     passwd,
+                     history,
+                     time_limit: None }
+    }
+
+    /// Kill the hook if it is still running after `time_limit`, even if its package's timeout
+    /// allows it to run longer.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /// How long the hook may run, as configured for the service's
+    /// package and limited by any time limit given to this runner.
+    fn timeout(&self) -> Option<Duration> {
+        let timeout = self.pkg
+                          .hook_timeouts
+                          .get(H::FILE_NAME)
+                          .map(|secs| Duration::from_secs(*secs));
+        match (timeout, self.time_limit) {
+            (Some(timeout), Some(time_limit)) => Some(timeout.min(time_limit)),
+            (timeout, time_limit) => timeout.or(time_limit),
+        }
     }
 
     pub async fn retryable_future(self) {
@@ -69,19 +102,25 @@ impl<H> HookRunner<H> where H: Hook + Sync + 'static
     }
 
     pub async fn into_future(self) -> Result<(H::ExitValue, Duration)> {
-        // TODO (CM): May want to consider adding a configurable
-        // timeout to how long this hook is allowed to run.
-        task::spawn_blocking(move || {
-            // _timer is for Prometheus metrics, but we also want
-            // the runtime for other purposes. Unfortunately,
-            // we're not able to use the same timer for both :(
-            let _timer = hook_timer(H::FILE_NAME);
-            let start = Instant::now();
-            let result = self.hook
-                             .run(&self.service_group, &self.pkg, self.passwd.as_ref());
-            let run_time = start.elapsed();
-            let exit_value = result.map_err(|e| Error::from(e).with_duration(run_time))?;
-            Ok((exit_value, run_time))
-        }).await?
+        task::spawn_blocking(move || self.run()).await?
+    }
+
+    /// Run the hook to completion on the current thread.
+    pub fn run(self) -> Result<(H::ExitValue, Duration)> {
+        // _timer is for Prometheus metrics, but we also want
+        // the runtime for other purposes. Unfortunately,
+        // we're not able to use the same timer for both :(
+        let _timer = hook_timer(H::FILE_NAME);
+        let started_at = SystemTime::now();
+        let start = Instant::now();
+        let result = self.hook.run_with_timeout(&self.service_group,
+                                                &self.pkg,
+                                                self.passwd.as_ref(),
+                                                self.timeout());
+        let run_time = start.elapsed();
+        let run = result.map_err(|e| Error::from(e).with_duration(run_time))?;
+        self.history
+            .record(self.hook.as_ref(), started_at, run_time, &run);
+        Ok((run.value, run_time))
     }
 }
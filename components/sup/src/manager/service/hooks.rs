@@ -6,6 +6,7 @@ use habitat_common::{error::Result,
                                           ExitCode,
                                           Hook,
                                           HookOutput,
+                                          HookRun,
                                           RenderPair},
                                   package::Pkg,
                                   TemplateRenderer},
@@ -20,7 +21,8 @@ use std::{self,
           io::BufRead,
           path::{Path,
                  PathBuf},
-          sync::Arc};
+          sync::Arc,
+          time::Duration};
 
 static LOGKEY: &str = "HK";
 
@@ -50,6 +52,10 @@ impl ProcessOutput {
 
     pub fn exit_status(&self) -> ExitStatus { self.exit_status }
 
+    pub fn stdout(&self) -> Option<&str> { self.standard_streams.stdout.as_deref() }
+
+    pub fn stderr(&self) -> Option<&str> { self.standard_streams.stderr.as_deref() }
+
     pub fn standard_streams(self) -> StandardStreams { self.standard_streams }
 }
 
@@ -132,19 +138,25 @@ impl Hook for HealthCheckHook {
     }
 
     #[cfg(windows)]
-    fn run<T>(&self,
-              service_group: &str,
-              pkg: &Pkg,
-              svc_encrypted_password: Option<T>)
-              -> Result<Self::ExitValue>
+    fn run_with_timeout<T>(&self,
+                           service_group: &str,
+                           pkg: &Pkg,
+                           svc_encrypted_password: Option<T>,
+                           timeout: Option<Duration>)
+                           -> Result<HookRun<Self::ExitValue>>
         where T: ToString
     {
         if let Some(client) = &self.pipe_client {
+            // The hook runs in a long lived PowerShell process behind the pipe, so it is not
+            // killed on timeout
             match client.exec_hook(service_group, pkg, svc_encrypted_password) {
                 Ok(exit) => {
                     let hook_output =
                         HookOutput::new(self.stdout_log_path(), self.stderr_log_path());
-                    Ok(self.handle_exit(pkg, &hook_output, ExitStatus::from(exit)))
+                    let status = ExitStatus::from(exit);
+                    Ok(HookRun { value:     self.handle_exit(pkg, &hook_output, status),
+                                 exit_code: status.code(),
+                                 timed_out: false, })
                 }
                 Err(err) => {
                     outputln!(preamble service_group,
@@ -153,7 +165,7 @@ impl Hook for HealthCheckHook {
                 }
             }
         } else {
-            self.run_impl(service_group, pkg, svc_encrypted_password)
+            self.run_impl(service_group, pkg, svc_encrypted_password, timeout)
         }
     }
 
@@ -177,6 +189,46 @@ impl Hook for HealthCheckHook {
     fn stderr_log_path(&self) -> &Path { &self.stderr_log_path }
 }
 
+/// Reports whether the service is accepting traffic. It is run
+/// alongside the health check, and a service is ready whenever it
+/// exits with a status code of 0.
+#[derive(Debug, Serialize)]
+pub struct ReadyHook {
+    render_pair:     RenderPair,
+    stdout_log_path: PathBuf,
+    stderr_log_path: PathBuf,
+}
+
+impl Hook for ReadyHook {
+    type ExitValue = bool;
+
+    const FILE_NAME: &'static str = "ready";
+
+    fn new(package_name: &str, pair: RenderPair, _feature_flags: FeatureFlag) -> Self {
+        ReadyHook { render_pair:     pair,
+                    stdout_log_path: hooks::stdout_log_path::<Self>(package_name),
+                    stderr_log_path: hooks::stderr_log_path::<Self>(package_name), }
+    }
+
+    fn handle_exit(&self, pkg: &Pkg, _: &HookOutput, status: ExitStatus) -> Self::ExitValue {
+        match status.code() {
+            Some(code) => code == 0,
+            None => {
+                Self::output_termination_message(&pkg.name, status);
+                false
+            }
+        }
+    }
+
+    fn path(&self) -> &Path { &self.render_pair.path }
+
+    fn renderer(&self) -> &TemplateRenderer { &self.render_pair.renderer }
+
+    fn stdout_log_path(&self) -> &Path { &self.stdout_log_path }
+
+    fn stderr_log_path(&self) -> &Path { &self.stderr_log_path }
+}
+
 #[derive(Debug, Serialize)]
 pub struct InitHook {
     render_pair:     RenderPair,
@@ -239,7 +291,12 @@ impl Hook for RunHook {
                   stderr_log_path: hooks::stderr_log_path::<Self>(package_name), }
     }
 
-    fn run<T>(&self, _: &str, _: &Pkg, _: Option<T>) -> Result<Self::ExitValue>
+    fn run_with_timeout<T>(&self,
+                           _: &str,
+                           _: &Pkg,
+                           _: Option<T>,
+                           _: Option<Duration>)
+                           -> Result<HookRun<Self::ExitValue>>
         where T: ToString
     {
         panic!("The run hook is a an exception to the lifetime of a service. It should only be \
@@ -471,6 +528,49 @@ impl Hook for SuitabilityHook {
     fn stderr_log_path(&self) -> &Path { &self.stderr_log_path }
 }
 
+#[derive(Debug, Serialize)]
+pub struct PreStopHook {
+    render_pair:     RenderPair,
+    stdout_log_path: PathBuf,
+    stderr_log_path: PathBuf,
+}
+
+impl Hook for PreStopHook {
+    type ExitValue = bool;
+
+    const FILE_NAME: &'static str = "pre-stop";
+
+    fn new(package_name: &str, pair: RenderPair, _feature_flags: FeatureFlag) -> Self {
+        PreStopHook { render_pair:     pair,
+                      stdout_log_path: hooks::stdout_log_path::<Self>(package_name),
+                      stderr_log_path: hooks::stderr_log_path::<Self>(package_name), }
+    }
+
+    fn handle_exit(&self, pkg: &Pkg, _: &HookOutput, status: ExitStatus) -> Self::ExitValue {
+        let pkg_name = &pkg.name;
+        match status.code() {
+            Some(0) => true,
+            Some(code) => {
+                outputln!(preamble pkg_name, "Pre stop failed! '{}' exited with \
+                    status code {}", Self::FILE_NAME, code);
+                false
+            }
+            None => {
+                Self::output_termination_message(pkg_name, status);
+                false
+            }
+        }
+    }
+
+    fn path(&self) -> &Path { &self.render_pair.path }
+
+    fn renderer(&self) -> &TemplateRenderer { &self.render_pair.renderer }
+
+    fn stdout_log_path(&self) -> &Path { &self.stdout_log_path }
+
+    fn stderr_log_path(&self) -> &Path { &self.stderr_log_path }
+}
+
 #[derive(Debug, Serialize)]
 pub struct PostStopHook {
     render_pair:     RenderPair,
@@ -518,6 +618,7 @@ impl Hook for PostStopHook {
 #[derive(Default)]
 pub struct HookCompileTable {
     health_check: bool,
+    ready:        bool,
     init:         bool,
     file_updated: bool,
     reload:       bool,
@@ -525,6 +626,7 @@ pub struct HookCompileTable {
     suitability:  bool,
     run:          bool,
     post_run:     bool,
+    pre_stop:     bool,
     post_stop:    bool,
 }
 
@@ -543,6 +645,7 @@ impl HookCompileTable {
 
     pub fn changed(&self) -> bool {
         let Self { health_check,
+                   ready,
                    init,
                    file_updated,
                    reload,
@@ -550,8 +653,10 @@ impl HookCompileTable {
                    suitability,
                    run,
                    post_run,
+                   pre_stop,
                    post_stop, } = self;
         *health_check
+        || *ready
         || *init
         || *file_updated
         || *reload
@@ -559,6 +664,7 @@ impl HookCompileTable {
         || *suitability
         || *run
         || *post_run
+        || *pre_stop
         || *post_stop
     }
 }
@@ -575,6 +681,7 @@ pub struct HookQueryModel {
 #[derive(Debug, Clone, Serialize)]
 pub struct HookTableQueryModel {
     pub health_check: Option<HookQueryModel>,
+    pub ready:        Option<HookQueryModel>,
     pub init:         Option<HookQueryModel>,
     pub file_updated: Option<HookQueryModel>,
     pub reload:       Option<HookQueryModel>,
@@ -582,6 +689,7 @@ pub struct HookTableQueryModel {
     pub suitability:  Option<HookQueryModel>,
     pub run:          Option<HookQueryModel>,
     pub post_run:     Option<HookQueryModel>,
+    pub pre_stop:     Option<HookQueryModel>,
     pub post_stop:    Option<HookQueryModel>,
 }
 
@@ -589,6 +697,7 @@ impl HookTableQueryModel {
     pub fn new(hook_table: &HookTable) -> HookTableQueryModel {
         HookTableQueryModel {
             health_check: hook_table.health_check.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() }),
+            ready: hook_table.ready.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() }),
             init: hook_table.init.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() }),
             file_updated: hook_table.file_updated.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() }),
             reload: hook_table.reload.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() }),
@@ -596,23 +705,26 @@ impl HookTableQueryModel {
             suitability: hook_table.suitability.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() }),
             run: hook_table.run.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() }),
             post_run: hook_table.post_run.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() }),
+            pre_stop: hook_table.pre_stop.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() }),
             post_stop: hook_table.post_stop.as_ref().map(|hook| HookQueryModel { render_pair: hook.render_pair.path.clone(), stdout_log_path: hook.stdout_log_path.clone(), stderr_log_path: hook.stderr_log_path.clone() })
         }
     }
 }
 
-// Hooks wrapped in Arcs represent a possibly-temporary state while we
-// refactor hooks to be able to run asynchronously.
+// Hooks are wrapped in Arcs so they can be handed to a `HookRunner`. The
+// run hook is the exception, since it is run as the service's process.
 #[derive(Debug, Default, Serialize)]
 pub struct HookTable {
     pub health_check: Option<Arc<HealthCheckHook>>,
+    pub ready:        Option<Arc<ReadyHook>>,
     pub init:         Option<Arc<InitHook>>,
-    pub file_updated: Option<FileUpdatedHook>,
-    pub reload:       Option<ReloadHook>,
-    pub reconfigure:  Option<ReconfigureHook>,
-    pub suitability:  Option<SuitabilityHook>,
+    pub file_updated: Option<Arc<FileUpdatedHook>>,
+    pub reload:       Option<Arc<ReloadHook>>,
+    pub reconfigure:  Option<Arc<ReconfigureHook>>,
+    pub suitability:  Option<Arc<SuitabilityHook>>,
     pub run:          Option<RunHook>,
     pub post_run:     Option<Arc<PostRunHook>>,
+    pub pre_stop:     Option<Arc<PreStopHook>>,
     pub post_stop:    Option<Arc<PostStopHook>>,
 }
 
@@ -629,24 +741,38 @@ impl HookTable {
         let mut table = HookTable::default();
         if let Ok(meta) = std::fs::metadata(templates.as_ref()) {
             if meta.is_dir() {
-                table.file_updated =
-                    FileUpdatedHook::load(package_name, &hooks_path, &templates, feature_flags);
+                table.file_updated = FileUpdatedHook::load(package_name,
+                                                           &hooks_path,
+                                                           &templates,
+                                                           feature_flags).map(Arc::new);
                 table.health_check = HealthCheckHook::load(package_name,
                                                            &hooks_path,
                                                            &templates,
                                                            feature_flags).map(Arc::new);
-                table.suitability =
-                    SuitabilityHook::load(package_name, &hooks_path, &templates, feature_flags);
+                table.ready =
+                    ReadyHook::load(package_name, &hooks_path, &templates, feature_flags).map(Arc::new);
+                table.suitability = SuitabilityHook::load(package_name,
+                                                          &hooks_path,
+                                                          &templates,
+                                                          feature_flags).map(Arc::new);
                 table.init = InitHook::load(package_name, &hooks_path, &templates, feature_flags).map(Arc::new);
-                table.reload =
-                    ReloadHook::load(package_name, &hooks_path, &templates, feature_flags);
-                table.reconfigure =
-                    ReconfigureHook::load(package_name, &hooks_path, &templates, feature_flags);
+                table.reload = ReloadHook::load(package_name,
+                                                &hooks_path,
+                                                &templates,
+                                                feature_flags).map(Arc::new);
+                table.reconfigure = ReconfigureHook::load(package_name,
+                                                          &hooks_path,
+                                                          &templates,
+                                                          feature_flags).map(Arc::new);
                 table.run = RunHook::load(package_name, &hooks_path, &templates, feature_flags);
                 table.post_run = PostRunHook::load(package_name,
                                                    &hooks_path,
                                                    &templates,
                                                    feature_flags).map(Arc::new);
+                table.pre_stop = PreStopHook::load(package_name,
+                                                   &hooks_path,
+                                                   &templates,
+                                                   feature_flags).map(Arc::new);
                 table.post_stop = PostStopHook::load(package_name,
                                                      &hooks_path,
                                                      &templates,
@@ -667,22 +793,25 @@ impl HookTable {
         debug!("{:?}", self);
         let mut changed = HookCompileTable::new();
         if let Some(ref hook) = self.file_updated {
-            changed.file_updated = self.compile_one(hook, service_group, ctx);
+            changed.file_updated = self.compile_one(hook.as_ref(), service_group, ctx);
         }
         if let Some(ref hook) = self.health_check {
             changed.health_check = self.compile_one(hook.as_ref(), service_group, ctx);
         }
+        if let Some(ref hook) = self.ready {
+            changed.ready = self.compile_one(hook.as_ref(), service_group, ctx);
+        }
         if let Some(ref hook) = self.init {
             changed.init = self.compile_one(hook.as_ref(), service_group, ctx);
         }
         if let Some(ref hook) = self.reload {
-            changed.reload |= self.compile_one(hook, service_group, ctx);
+            changed.reload |= self.compile_one(hook.as_ref(), service_group, ctx);
         }
         if let Some(ref hook) = self.reconfigure {
-            changed.reconfigure = self.compile_one(hook, service_group, ctx);
+            changed.reconfigure = self.compile_one(hook.as_ref(), service_group, ctx);
         }
         if let Some(ref hook) = self.suitability {
-            changed.suitability = self.compile_one(hook, service_group, ctx);
+            changed.suitability = self.compile_one(hook.as_ref(), service_group, ctx);
         }
         if let Some(ref hook) = self.run {
             changed.run = self.compile_one(hook, service_group, ctx);
@@ -690,12 +819,67 @@ impl HookTable {
         if let Some(ref hook) = self.post_run {
             changed.post_run = self.compile_one(hook.as_ref(), service_group, ctx);
         }
+        if let Some(ref hook) = self.pre_stop {
+            changed.pre_stop = self.compile_one(hook.as_ref(), service_group, ctx);
+        }
         if let Some(ref hook) = self.post_stop {
             changed.post_stop = self.compile_one(hook.as_ref(), service_group, ctx);
         }
         changed
     }
 
+    /// Render all loaded hooks from the table without writing them.
+    ///
+    /// Returns the path each hook compiles to along with its content or
+    /// the error rendering it.
+    pub fn render<T>(&self, ctx: &T) -> Vec<(PathBuf, Result<String>)>
+        where T: Serialize
+    {
+        let mut rendered = Vec::new();
+        if let Some(ref hook) = self.file_updated {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        if let Some(ref hook) = self.health_check {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        if let Some(ref hook) = self.ready {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        if let Some(ref hook) = self.init {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        if let Some(ref hook) = self.reload {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        if let Some(ref hook) = self.reconfigure {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        if let Some(ref hook) = self.suitability {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        if let Some(ref hook) = self.run {
+            rendered.push(Self::render_one(hook, ctx));
+        }
+        if let Some(ref hook) = self.post_run {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        if let Some(ref hook) = self.pre_stop {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        if let Some(ref hook) = self.post_stop {
+            rendered.push(Self::render_one(hook.as_ref(), ctx));
+        }
+        rendered
+    }
+
+    fn render_one<H, T>(hook: &H, ctx: &T) -> (PathBuf, Result<String>)
+        where H: Hook,
+              T: Serialize
+    {
+        // Like `Hook::compile`, never use a deprecated file name
+        (hook.path().with_file_name(H::FILE_NAME), hook.renderer().render(H::FILE_NAME, ctx))
+    }
+
     fn compile_one<H, T>(&self, hook: &H, service_group: &str, ctx: &T) -> bool
         where H: Hook,
               T: Serialize
@@ -766,12 +950,14 @@ mod tests {
 
     as_ref_path_impl!(FileUpdatedHook
                       HealthCheckHook
+                      ReadyHook
                       InitHook
                       PostRunHook
                       ReloadHook
                       ReconfigureHook
                       RunHook
                       SuitabilityHook
+                      PreStopHook
                       PostStopHook);
 
     fn hook_templates_path() -> PathBuf {
@@ -899,12 +1085,28 @@ mod tests {
                                                        .expect("no init hook??"));
         let expected_init_hook = "#!/bin/bash\n\necho \"The message is Hello\"\n";
         let expected_run_hook = "#!/bin/bash\n\necho \"Running a program\"\n";
+        let expected_ready_hook = "#!/bin/bash\n\ntest -n \"Hello\"\n";
         assert_eq!(init_hook_content, expected_init_hook);
 
         // Verify run hook
         let run_hook_content = file_content(hook_table.run.as_ref().expect("no run hook??"));
         assert_eq!(run_hook_content, expected_run_hook);
 
+        // Verify ready hook
+        let ready_hook_content = file_content(hook_table.ready
+                                                        .as_ref()
+                                                        .map(convert::AsRef::as_ref)
+                                                        .expect("no ready hook??"));
+        assert_eq!(ready_hook_content, expected_ready_hook);
+
+        // Verify pre-stop hook
+        let pre_stop_hook_content = file_content(hook_table.pre_stop
+                                                           .as_ref()
+                                                           .map(convert::AsRef::as_ref)
+                                                           .expect("no pre-stop hook??"));
+        assert_eq!(pre_stop_hook_content,
+                   "#!/bin/bash\n\necho \"Draining: Hello\"\n");
+
         // Recompiling again results in no changes
         assert!(!hook_table.compile(&service_group, &ctx).changed());
 
@@ -920,6 +1122,41 @@ mod tests {
         assert_eq!(run_hook_content, expected_run_hook);
     }
 
+    #[tokio::test]
+    async fn render_hook_table_does_not_write_hooks() {
+        let tmp_root = rendered_hooks_path();
+        let hooks_path = tmp_root.path().join("hooks");
+        fs::create_dir_all(&hooks_path).unwrap();
+        let service_group = service_group();
+        let template_path = hook_templates_path();
+        create_with_content(hooks_path.join("default.toml"), "message = \"Hello\"");
+
+        let pkg = pkg(&service_group).await;
+        let sys = Sys::new(true,
+                           GossipListenAddr::default(),
+                           ListenCtlAddr::default(),
+                           HttpListenAddr::default(),
+                           IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let cfg = Cfg::new(&pkg, Some(&hooks_path)).expect("Could not create config");
+        let mut ring = CensusRing::new("member-a");
+        let ctx = ctx(&service_group, &pkg, &sys, &cfg, &mut ring);
+
+        let hook_table = HookTable::load(&service_group,
+                                         &template_path,
+                                         &hooks_path,
+                                         FeatureFlag::empty());
+        let rendered = hook_table.render(&ctx);
+        assert_eq!(rendered.len(), 5);
+
+        let (init_path, init_content) =
+            rendered.iter()
+                    .find(|(path, _)| path.ends_with(InitHook::FILE_NAME))
+                    .expect("no init hook??");
+        assert_eq!(init_content.as_ref().expect("render init hook"),
+                   "#!/bin/bash\n\necho \"The message is Hello\"\n");
+        assert!(!init_path.exists());
+    }
+
     #[test]
     fn parse_suitability() {
         #[allow(clippy::string_lit_as_bytes)]
@@ -0,0 +1,160 @@
+use crate::util::history::BoundedHistory;
+use habitat_core::service::JobSchedule;
+use serde::Serialize;
+use std::time::SystemTime;
+
+/// A finished run of a job service.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JobRun {
+    /// When the run started, in seconds since the epoch.
+    pub started_at: u64,
+    /// How long the run took, in seconds.
+    pub duration:   u64,
+    /// The run hook's exit code, if it exited rather than being
+    /// killed and the Launcher knew it.
+    pub exit_code:  Option<i32>,
+}
+
+/// Keeps track of when a job service runs next and how its recent
+/// runs went.
+#[derive(Debug)]
+pub struct JobTracker {
+    schedule:      JobSchedule,
+    next_run_at:   Option<SystemTime>,
+    /// When the run in progress, if any, started.
+    running_since: Option<SystemTime>,
+    runs:          BoundedHistory<JobRun, { Self::MAX_RUNS }>,
+}
+
+impl JobTracker {
+    /// How many finished runs are remembered.
+    const MAX_RUNS: usize = 10;
+
+    pub fn new(schedule: JobSchedule, now: SystemTime) -> Self {
+        let next_run_at = schedule.next_after(now);
+        Self { schedule,
+               next_run_at,
+               running_since: None,
+               runs: BoundedHistory::default() }
+    }
+
+    /// Returns `true` if a run is due at `now`, and moves on to the
+    /// next time the job runs. Runs that came due while the Supervisor
+    /// wasn't looking are collapsed into one.
+    pub fn take_due(&mut self, now: SystemTime) -> bool {
+        match self.next_run_at {
+            Some(at) if at <= now => {
+                self.next_run_at = self.schedule.next_after(now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_running(&self) -> bool { self.running_since.is_some() }
+
+    pub fn run_started(&mut self, at: SystemTime) { self.running_since = Some(at); }
+
+    /// Records the end of the run in progress at `at`, returning the
+    /// finished run.
+    pub fn run_finished(&mut self, at: SystemTime, exit_code: Option<i32>) -> Option<&JobRun> {
+        let started_at = self.running_since.take()?;
+        let duration = at.duration_since(started_at).unwrap_or_default();
+        let run = JobRun { started_at: unix_secs(started_at),
+                           duration: duration.as_secs(),
+                           exit_code };
+        self.runs.push(run);
+        self.runs.latest()
+    }
+}
+
+fn unix_secs(at: SystemTime) -> u64 {
+    at.duration_since(SystemTime::UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_secs()
+}
+
+/// The queryable state of a job service.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobQueryModel {
+    pub schedule:    JobSchedule,
+    /// When the job runs next, in seconds since the epoch. Unset if it
+    /// never runs again.
+    pub next_run_at: Option<u64>,
+    pub running:     bool,
+    pub runs:        Vec<JobRun>,
+}
+
+impl JobQueryModel {
+    pub fn new(tracker: &JobTracker) -> Self {
+        Self { schedule:    tracker.schedule.clone(),
+               next_run_at: tracker.next_run_at.map(unix_secs),
+               running:     tracker.is_running(),
+               runs:        tracker.runs.iter().cloned().collect(), }
+    }
+}
+
+impl From<&JobQueryModel> for habitat_sup_protocol::types::JobStatus {
+    fn from(job: &JobQueryModel) -> Self {
+        let runs = job.runs
+                      .iter()
+                      .map(|run| {
+                          habitat_sup_protocol::types::JobRun { started_at: run.started_at as i64,
+                                                                duration:   run.duration,
+                                                                exit_code:  run.exit_code, }
+                      })
+                      .collect();
+        Self { schedule: job.schedule.to_string(),
+               next_run_at: job.next_run_at.map(|at| at as i64),
+               runs }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> SystemTime { SystemTime::UNIX_EPOCH + Duration::from_secs(secs) }
+
+    fn hourly() -> JobSchedule { "0 * * * *".parse().unwrap() }
+
+    #[test]
+    fn job_tracker_comes_due_on_schedule() {
+        let mut tracker = JobTracker::new(hourly(), at(60));
+        assert_eq!(tracker.next_run_at, Some(at(3600)));
+        assert!(!tracker.take_due(at(3599)));
+        assert!(tracker.take_due(at(3600)));
+        assert_eq!(tracker.next_run_at, Some(at(7200)));
+        assert!(!tracker.take_due(at(3601)));
+    }
+
+    #[test]
+    fn job_tracker_collapses_missed_runs() {
+        let mut tracker = JobTracker::new(hourly(), at(60));
+        assert!(tracker.take_due(at(4 * 3600 + 5)));
+        assert_eq!(tracker.next_run_at, Some(at(5 * 3600)));
+    }
+
+    #[test]
+    fn job_tracker_records_runs() {
+        let mut tracker = JobTracker::new(hourly(), at(0));
+        assert_eq!(tracker.run_finished(at(10), Some(0)), None);
+
+        tracker.run_started(at(3600));
+        assert!(tracker.is_running());
+        assert_eq!(tracker.run_finished(at(3642), Some(2)),
+                   Some(&JobRun { started_at: 3600,
+                                  duration:   42,
+                                  exit_code:  Some(2), }));
+        assert!(!tracker.is_running());
+
+        for hour in 2..20 {
+            tracker.run_started(at(hour * 3600));
+            tracker.run_finished(at(hour * 3600 + 1), None);
+        }
+        let query = JobQueryModel::new(&tracker);
+        assert_eq!(query.runs.len(), JobTracker::MAX_RUNS);
+        assert_eq!(query.runs[0].started_at, 19 * 3600);
+    }
+}
@@ -0,0 +1,278 @@
+//! Runs a service's built-in health probes, which check its health
+//! in place of a `health-check` hook.
+//!
+//! HTTP and TCP probes are made directly from the Supervisor. Exec
+//! probes run their command the way hooks are run, as the service's
+//! user and with its package's environment. A probe that doesn't
+//! finish within `PROBE_TIMEOUT` counts as `Critical`.
+
+use super::{health::HealthCheckResult,
+            Pkg};
+use crate::error::Result;
+use habitat_common::outputln;
+use habitat_core::service::{HealthProbe,
+                            ServiceGroup};
+use std::{convert::TryFrom,
+          future::Future,
+          io,
+          process::Child,
+          time::Duration};
+use tokio::{io::{AsyncBufReadExt,
+                 AsyncReadExt,
+                 AsyncWriteExt,
+                 BufReader},
+            net::TcpStream,
+            task,
+            time};
+
+static LOGKEY: &str = "PR";
+
+/// How long a probe may take before it counts as failed.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait for a timed out exec probe's command to exit once it
+/// has been killed.
+const REAP_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The most of an HTTP response that is read looking for its status line.
+const MAX_STATUS_LINE_BYTES: u64 = 1024;
+
+#[derive(Clone, Debug)]
+pub struct ProbeRunner {
+    probes:        Vec<HealthProbe>,
+    service_group: ServiceGroup,
+    pkg:           Pkg,
+    passwd:        Option<String>,
+}
+
+impl ProbeRunner {
+    pub fn new(probes: Vec<HealthProbe>,
+               service_group: ServiceGroup,
+               pkg: Pkg,
+               passwd: Option<String>)
+               -> Self {
+        ProbeRunner { probes,
+                      service_group,
+                      pkg,
+                      passwd }
+    }
+
+    /// Run every probe in turn. The service is only as healthy as its
+    /// least healthy probe.
+    pub async fn run(&self) -> HealthCheckResult {
+        let mut worst = HealthCheckResult::Ok;
+        for probe in &self.probes {
+            let result = self.run_probe(probe).await;
+            if severity(result) > severity(worst) {
+                worst = result;
+            }
+        }
+        worst
+    }
+
+    async fn run_probe(&self, probe: &HealthProbe) -> HealthCheckResult {
+        let result = match probe {
+            HealthProbe::Http { host,
+                                port,
+                                path,
+                                status, } => {
+                let actual = within_timeout(http_status(host, *port, path)).await;
+                actual.and_then(|actual| {
+                          if actual == *status {
+                              Ok(HealthCheckResult::Ok)
+                          } else {
+                              Err(io::Error::new(io::ErrorKind::Other,
+                                                 format!("responded with status {}", actual)))
+                          }
+                      })
+            }
+            HealthProbe::Tcp { host, port } => {
+                let address = address(host, *port);
+                within_timeout(TcpStream::connect(address)).await
+                                                           .map(|_| HealthCheckResult::Ok)
+            }
+            HealthProbe::Exec { command } => self.exec(command).await,
+        };
+        result.unwrap_or_else(|err| {
+                  outputln!(preamble self.service_group, "Health probe {} failed, {}", probe, err);
+                  HealthCheckResult::Critical
+              })
+    }
+
+    /// Run an exec probe's command and read its exit code like that of
+    /// a health check hook. The command is killed if it runs too long.
+    async fn exec(&self, command: &[String]) -> io::Result<HealthCheckResult> {
+        let child = match spawn(command, &self.pkg, self.passwd.as_deref()) {
+            Ok(child) => child,
+            Err(err) => {
+                outputln!(preamble self.service_group,
+                          "Health probe command {} failed to start, {}",
+                          command.join(" "),
+                          err);
+                return Ok(HealthCheckResult::Unknown);
+            }
+        };
+        let pid = child.id();
+        let mut output = task::spawn_blocking(move || child.wait_with_output());
+        let output = match time::timeout(PROBE_TIMEOUT, &mut output).await {
+            Ok(output) => output??,
+            Err(elapsed) => {
+                if let Err(err) = kill(pid) {
+                    outputln!(preamble self.service_group,
+                              "Failed to kill health probe command {}, {}",
+                              command.join(" "),
+                              err);
+                }
+                // Reap the killed command so it doesn't linger as a zombie
+                if time::timeout(REAP_TIMEOUT, output).await.is_err() {
+                    outputln!(preamble self.service_group,
+                              "Health probe command {} did not exit after being killed",
+                              command.join(" "));
+                }
+                return Err(elapsed.into());
+            }
+        };
+        let result = output.status
+                           .code()
+                           .and_then(|code| HealthCheckResult::try_from(code).ok())
+                           .unwrap_or_else(|| {
+                               outputln!(preamble self.service_group,
+                                         "Health probe command {} exited with an unknown \
+                                          status, {}",
+                                         command.join(" "),
+                                         output.status);
+                               HealthCheckResult::Unknown
+                           });
+        Ok(result)
+    }
+}
+
+/// How bad a result is, for finding the worst of several.
+fn severity(result: HealthCheckResult) -> u8 {
+    match result {
+        HealthCheckResult::Ok => 0,
+        HealthCheckResult::Warning => 1,
+        HealthCheckResult::Unknown => 2,
+        HealthCheckResult::Critical => 3,
+    }
+}
+
+fn address(host: &str, port: u16) -> String { format!("{}:{}", host, port) }
+
+async fn within_timeout<T>(future: impl Future<Output = io::Result<T>>) -> io::Result<T> {
+    time::timeout(PROBE_TIMEOUT, future).await?
+}
+
+/// Make a plain HTTP GET request for `path` and return the status of
+/// the response.
+async fn http_status(host: &str, port: u16, path: &str) -> io::Result<u16> {
+    let mut stream = TcpStream::connect(address(host, port)).await?;
+    let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: hab-sup\r\nConnection: \
+                           close\r\n\r\n",
+                          path,
+                          address(host, port));
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream.take(MAX_STATUS_LINE_BYTES)).read_line(&mut status_line)
+                                                      .await?;
+    // The status line looks like "HTTP/1.1 200 OK"
+    let mut parts = status_line.split_whitespace();
+    match (parts.next(), parts.next().and_then(|status| status.parse().ok())) {
+        (Some(version), Some(status)) if version.starts_with("HTTP/") => Ok(status),
+        _ => {
+            Err(io::Error::new(io::ErrorKind::InvalidData,
+                               format!("not an HTTP response: {:?}",
+                                       status_line.trim_end())))
+        }
+    }
+}
+
+#[cfg(unix)]
+fn spawn(command: &[String], pkg: &Pkg, _: Option<&str>) -> Result<Child> {
+    use habitat_common::templating::hooks;
+    use habitat_core::os::process::exec::unix::hook_command;
+    use std::ops::Deref;
+
+    let ids = hooks::svc_user_ids(pkg)?;
+    let mut cmd = hook_command(&command[0], pkg.env.deref(), ids);
+    cmd.args(&command[1..]);
+    Ok(cmd.spawn()?)
+}
+
+#[cfg(windows)]
+fn spawn(command: &[String], pkg: &Pkg, svc_encrypted_password: Option<&str>) -> Result<Child> {
+    Ok(habitat_core::util::spawn_pwsh(&command.join(" "),
+                                      &pkg.env.to_hash_map(),
+                                      &pkg.svc_user,
+                                      svc_encrypted_password)?)
+}
+
+/// Commands lead their own process group, like hooks, so this takes
+/// down any processes they started along with them.
+#[cfg(unix)]
+fn kill(pid: u32) -> habitat_core::error::Result<()> {
+    use habitat_core::os::process::{signal,
+                                    Signal};
+
+    signal(-(pid as libc::pid_t), Signal::KILL)
+}
+
+#[cfg(windows)]
+fn kill(pid: u32) -> habitat_core::error::Result<()> { habitat_core::os::process::terminate(pid) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::{Read,
+                   Write},
+              net::TcpListener,
+              thread};
+
+    /// Serves one HTTP request with `status`, returning the port it
+    /// listens on.
+    fn serve_once(status: u16) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            assert!(stream.read(&mut request).unwrap() > 0);
+            write!(stream,
+                   "HTTP/1.1 {} Whatever\r\nContent-Length: 0\r\n\r\n",
+                   status).unwrap();
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn http_status_reads_the_status_line() {
+        let port = serve_once(204);
+        assert_eq!(http_status("127.0.0.1", port, "/healthz").await.unwrap(),
+                   204);
+    }
+
+    #[tokio::test]
+    async fn tcp_probe_fails_when_nothing_listens() {
+        let port = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        assert!(within_timeout(TcpStream::connect(address("127.0.0.1", port))).await
+                                                                              .is_err());
+    }
+
+    #[test]
+    fn the_worst_result_is_critical() {
+        let mut results = vec![HealthCheckResult::Critical,
+                               HealthCheckResult::Ok,
+                               HealthCheckResult::Unknown,
+                               HealthCheckResult::Warning];
+        results.sort_by_key(|result| severity(*result));
+        assert_eq!(results,
+                   vec![HealthCheckResult::Ok,
+                        HealthCheckResult::Warning,
+                        HealthCheckResult::Unknown,
+                        HealthCheckResult::Critical]);
+    }
+}
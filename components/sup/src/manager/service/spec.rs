@@ -5,10 +5,23 @@ use super::{BindingMode,
 use crate::error::{Error,
                    Result};
 use habitat_core::{fs::atomic_write,
-                   os::process::ShutdownTimeout,
-                   package::{PackageIdent,
-                             PackageInstall},
-                   service::{HealthCheckInterval,
+                   os::process::{ProcessControls,
+                                 ResourceLimits,
+                                 ShutdownTimeout,
+                                 Signal},
+                   package::{install::TIMED_HOOKS,
+                             Identifiable,
+                             PackageIdent,
+                             PackageInstall,
+                             VersionConstraint},
+                   service::{self,
+                             CanaryPolicy,
+                             HealthCheckInterval,
+                             HealthProbe,
+                             JobSchedule,
+                             LivenessPolicy,
+                             MaintenanceWindow,
+                             RolloutPolicy,
                              ServiceBind},
                    url::DEFAULT_BLDR_URL,
                    util,
@@ -20,7 +33,8 @@ use log::{debug,
 use serde::{self,
             Deserialize,
             Serialize};
-use std::{collections::HashSet,
+use std::{collections::{BTreeMap,
+                        HashSet},
           convert::TryFrom,
           fmt,
           fs::{self,
@@ -86,6 +100,8 @@ impl From<DesiredState> for i32 {
 pub struct ServiceSpec {
     #[serde(with = "util::serde::string")]
     pub ident:                  PackageIdent,
+    /// The name of a named instance of the package. Unset for the default instance.
+    pub instance:               Option<String>,
     pub group:                  String,
     pub bldr_url:               String,
     pub channel:                ChannelIdent,
@@ -99,6 +115,34 @@ pub struct ServiceSpec {
     pub desired_state:          DesiredState,
     pub shutdown_timeout:       Option<ShutdownTimeout>,
     pub svc_encrypted_password: Option<String>,
+    /// Services on this Supervisor that must be up, with an `Ok` or `Warning` health check
+    /// result, before this service is started. The service waits for them for as long as it
+    /// takes. Services are stopped in the reverse order.
+    #[serde(with = "ident_list")]
+    pub dependencies:           Vec<PackageIdent>,
+    /// When updates to the service may be applied. Updates may be applied at any time if empty.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub update_windows:         Vec<MaintenanceWindow>,
+    /// Keeps the service on its current release. Held services are not updated.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub update_hold:            bool,
+    /// Limits the versions the service may be updated to.
+    pub update_pin:             Option<VersionConstraint>,
+    /// Runs the service as a job on this schedule instead of keeping it running.
+    pub schedule:               Option<JobSchedule>,
+    /// Only runs the job on the elected leader of the service group.
+    pub job_leader_only:        bool,
+    /// Ports the package exposes on which the Launcher holds listening sockets and hands them to
+    /// the service. The sockets stay open while the service restarts or updates.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub listen_ports:           Vec<u16>,
+    /// The signal sent to the service when its configuration changes, instead of restarting it.
+    /// Takes precedence over the package's reload signal.
+    pub reload_signal:          Option<Signal>,
+    /// Checks the Supervisor runs itself in place of the health check hook. They replace the
+    /// package's health probes.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub health_probes:          Vec<HealthProbe>,
     // it is important that the health check interval
     // is the last field to be serialized because it
     // is serialized as a table. Individual values
@@ -108,11 +152,37 @@ pub struct ServiceSpec {
     // https://github.com/habitat-sh/habitat/issues/6469
     // and eliminate the need to keep this field last.
     pub health_check_interval:  HealthCheckInterval,
+    /// Limits enforced on the service's process tree by the Launcher. Like the health check
+    /// interval, this is serialized as a table and must come after all plain values.
+    #[serde(skip_serializing_if = "ResourceLimits::is_empty")]
+    pub resource_limits:        ResourceLimits,
+    /// Rlimits, scheduling priorities, and CPU affinity for the service. Anything not set here
+    /// falls back to the defaults declared by the package.
+    #[serde(skip_serializing_if = "ProcessControls::is_empty")]
+    pub process_controls:       ProcessControls,
+    /// When to restart the service because its health checks keep failing.
+    #[serde(skip_serializing_if = "LivenessPolicy::is_empty")]
+    pub liveness:               LivenessPolicy,
+    /// How updates are rolled out when the update strategy is canary.
+    #[serde(skip_serializing_if = "CanaryPolicy::is_empty")]
+    pub canary:                 CanaryPolicy,
+    /// How rolling and canary updates move through the service group.
+    #[serde(skip_serializing_if = "RolloutPolicy::is_empty")]
+    pub rollout:                RolloutPolicy,
+    /// Environment variables set for the service's process and hooks. They take precedence over
+    /// the package's environment. Serialized as a table, like the policies above.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub env:                    BTreeMap<String, String>,
+    /// How long each hook may run, in seconds, keyed by the hook's file name. They take
+    /// precedence over the package's hook timeouts. Serialized as a table, like the environment.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub hook_timeouts:          BTreeMap<String, u64>,
 }
 
 impl ServiceSpec {
     pub fn new(ident: PackageIdent) -> Self {
         Self { ident,
+               instance: None,
                group: DEFAULT_GROUP.to_string(),
                bldr_url: DEFAULT_BLDR_URL.to_string(),
                channel: ChannelIdent::stable(),
@@ -125,7 +195,23 @@ impl ServiceSpec {
                desired_state: DesiredState::default(),
                health_check_interval: HealthCheckInterval::default(),
                svc_encrypted_password: None,
-               shutdown_timeout: None }
+               shutdown_timeout: None,
+               dependencies: Vec::new(),
+               update_windows: Vec::new(),
+               update_hold: false,
+               update_pin: None,
+               schedule: None,
+               job_leader_only: false,
+               listen_ports: Vec::new(),
+               reload_signal: None,
+               health_probes: Vec::new(),
+               resource_limits: ResourceLimits::default(),
+               process_controls: ProcessControls::default(),
+               liveness: LivenessPolicy::default(),
+               canary: CanaryPolicy::default(),
+               rollout: RolloutPolicy::default(),
+               env: BTreeMap::new(),
+               hook_timeouts: BTreeMap::new() }
     }
 
     // This should only be used to provide a default value when deserializing. We intentially do not
@@ -169,11 +255,16 @@ impl ServiceSpec {
         Ok(())
     }
 
-    pub fn ident_file(ident: &PackageIdent) -> PathBuf {
-        PathBuf::from(format!("{}.{}", ident.name, SPEC_FILE_EXT))
+    pub fn service_file(service_name: &str) -> PathBuf {
+        PathBuf::from(format!("{}.{}", service_name, SPEC_FILE_EXT))
     }
 
-    pub fn file(&self) -> PathBuf { Self::ident_file(&self.ident) }
+    pub fn file(&self) -> PathBuf { Self::service_file(&self.service_name()) }
+
+    /// The name the service is known by on this Supervisor. See `service::service_name`.
+    pub fn service_name(&self) -> String {
+        service::service_name(&self.ident.name, self.instance.as_deref())
+    }
 
     /// Validates that all required package binds are present in service binds and all remaining
     /// service binds are optional package binds.
@@ -182,6 +273,9 @@ impl ServiceSpec {
     ///
     /// * If any required package binds are missing in service binds
     /// * If any given service binds are in neither required nor optional package binds
+    /// * If the service listens on ports the package doesn't expose
+    /// * If any hook timeouts are for hooks that can't have one, or are zero
+    /// * If the job is to run only on the leader but the topology isn't leader
     pub fn validate(&self, package: &PackageInstall) -> Result<()> {
         let mut svc_binds: HashSet<&str> = self.binds.iter().map(ServiceBind::name).collect();
         let mut missing_req_binds = Vec::new();
@@ -215,6 +309,36 @@ impl ServiceSpec {
                                                     .collect()));
         }
 
+        // Only ports the package exposes may be listened on.
+        let exposes = package.exposes()?;
+        let unexposed = self.listen_ports
+                            .iter()
+                            .filter(|port| !exposes.contains(&port.to_string()))
+                            .copied()
+                            .collect::<Vec<_>>();
+        if !unexposed.is_empty() {
+            return Err(Error::InvalidListenPorts(unexposed));
+        }
+
+        // Only hooks the Supervisor runs with a timeout may be given one.
+        let invalid_timeouts =
+            self.hook_timeouts
+                .iter()
+                .filter(|(hook, secs)| **secs == 0 || !TIMED_HOOKS.contains(&hook.as_str()))
+                .map(|(hook, secs)| format!("{}={}", hook, secs))
+                .collect::<Vec<_>>();
+        if !invalid_timeouts.is_empty() {
+            return Err(Error::InvalidHookTimeouts(invalid_timeouts));
+        }
+
+        self.validate_job()
+    }
+
+    /// Only a service group with the leader topology has a leader to run the job on.
+    fn validate_job(&self) -> Result<()> {
+        if self.job_leader_only && self.topology != Topology::Leader {
+            return Err(Error::JobLeaderOnlyWithoutLeader);
+        }
         Ok(())
     }
 
@@ -223,6 +347,10 @@ impl ServiceSpec {
             svc_load.ident
                     .ok_or_else(|| net::err(net::ErrCode::BadPayload, "No ident specified"))?
                     .into();
+        if let Some(instance) = svc_load.instance {
+            service::validate_instance_name(&instance)?;
+            self.instance = Some(instance);
+        }
         if let Some(group) = svc_load.group {
             self.group = group;
         }
@@ -282,10 +410,64 @@ impl ServiceSpec {
         if let Some(shutdown_timeout) = svc_load.shutdown_timeout {
             self.shutdown_timeout = Some(ShutdownTimeout::from(shutdown_timeout));
         }
+        if let Some(list) = svc_load.dependencies {
+            self.dependencies = list.into();
+        }
+        if let Some(limits) = svc_load.resource_limits {
+            self.resource_limits = limits.into();
+        }
+        if let Some(controls) = svc_load.process_controls {
+            let controls: ProcessControls = controls.into();
+            controls.validate()?;
+            self.process_controls = controls;
+        }
+        if let Some(liveness) = svc_load.liveness {
+            self.liveness = liveness.into();
+        }
+        if let Some(canary) = svc_load.canary {
+            self.canary = CanaryPolicy::try_from(canary)?;
+        }
+        if let Some(rollout) = svc_load.rollout {
+            self.rollout = rollout.into();
+        }
+        if let Some(list) = svc_load.update_windows {
+            self.update_windows = Vec::try_from(list)?;
+        }
+        if let Some(env) = svc_load.env {
+            self.env = env.vars.into_iter().collect();
+        }
+        if let Some(timeouts) = svc_load.hook_timeouts {
+            self.hook_timeouts = timeouts.timeouts.into_iter().collect();
+        }
+        if let Some(schedule) = svc_load.schedule {
+            self.schedule = Some(JobSchedule::from_str(&schedule)?);
+        }
+        if let Some(job_leader_only) = svc_load.job_leader_only {
+            self.job_leader_only = job_leader_only;
+        }
+        if let Some(list) = svc_load.listen_ports {
+            let mut ports =
+                list.ports
+                    .into_iter()
+                    .map(u16::try_from)
+                    .collect::<result::Result<Vec<_>, _>>()
+                    .map_err(|_| net::err(net::ErrCode::BadPayload, "Invalid listen port"))?;
+            ports.sort_unstable();
+            ports.dedup();
+            self.listen_ports = ports;
+        }
+        if let Some(reload_signal) = svc_load.reload_signal {
+            self.reload_signal = Some(Signal::from_str(&reload_signal)?);
+        }
+        if let Some(list) = svc_load.health_probes {
+            self.health_probes = Vec::try_from(list)?;
+        }
         Ok(self)
     }
 
-    pub fn merge_svc_update(&mut self, svc_update: habitat_sup_protocol::ctl::SvcUpdate) {
+    pub fn merge_svc_update(&mut self,
+                            svc_update: habitat_sup_protocol::ctl::SvcUpdate)
+                            -> Result<()> {
         if let Some(group) = svc_update.group {
             self.group = group;
         }
@@ -343,6 +525,35 @@ impl ServiceSpec {
         if let Some(shutdown_timeout) = svc_update.shutdown_timeout {
             self.shutdown_timeout = Some(ShutdownTimeout::from(shutdown_timeout));
         }
+        if let Some(list) = svc_update.dependencies {
+            self.dependencies = list.into();
+        }
+        if let Some(update_hold) = svc_update.update_hold {
+            self.update_hold = update_hold;
+        }
+        if let Some(update_pin) = svc_update.update_pin {
+            if update_pin.is_empty() {
+                self.update_pin = None;
+            } else {
+                self.update_pin = Some(VersionConstraint::from_str(&update_pin)?);
+            }
+        }
+        if let Some(env) = svc_update.env {
+            self.env = env.vars.into_iter().collect();
+        }
+        if let Some(schedule) = svc_update.schedule {
+            if schedule.is_empty() {
+                self.schedule = None;
+            } else {
+                self.schedule = Some(JobSchedule::from_str(&schedule)?);
+            }
+        }
+        self.validate_job()
+    }
+
+    /// Returns `true` if `other` is one of the services this service depends on.
+    pub(crate) fn depends_on(&self, other: &PackageIdent) -> bool {
+        self.dependencies.iter().any(|d| other.satisfies(d))
     }
 
     /// Given an `old` and a `new` spec, figure out what operations
@@ -419,6 +630,7 @@ impl ServiceSpec {
                     // ensures that we look at everything.
                     let ServiceSpec {
                         ident,
+                        instance,
                         group,
                         bldr_url,
                         channel,
@@ -434,7 +646,23 @@ impl ServiceSpec {
                         desired_state: _,
                         shutdown_timeout,
                         svc_encrypted_password,
+                        dependencies,
+                        update_windows,
+                        update_hold,
+                        update_pin,
+                        schedule,
+                        job_leader_only,
+                        listen_ports,
+                        reload_signal,
+                        health_probes,
                         health_check_interval,
+                        resource_limits,
+                        process_controls,
+                        liveness,
+                        canary,
+                        rollout,
+                        env,
+                        hook_timeouts,
                     } = &running_spec;
 
                     // Currently, if any of these bits of data are
@@ -450,6 +678,7 @@ impl ServiceSpec {
                     // *must* restart, since that change may result in
                     // a different version of the service being run.
                     if ident != &disk_spec.ident
+                        || instance != &disk_spec.instance
                         || group != &disk_spec.group
                         // TODO (CM): This *might* not need to be here
                         || topology != &disk_spec.topology
@@ -464,6 +693,33 @@ impl ServiceSpec {
                         || svc_encrypted_password != &disk_spec.svc_encrypted_password
                         // TODO (CM): This probably doesn't need to be here, either
                         || health_check_interval != &disk_spec.health_check_interval
+                        // Limits are applied when the Launcher creates the
+                        // service's cgroup at spawn time
+                        || resource_limits != &disk_spec.resource_limits
+                        || process_controls != &disk_spec.process_controls
+                        // The policy is handed to the health checks when
+                        // the service starts
+                        || liveness != &disk_spec.liveness
+                        // The environment is handed to the service
+                        // process when it is spawned
+                        || env != &disk_spec.env
+                        // Turning a service into a job, or changing
+                        // when and where the job runs, starts it over
+                        || schedule != &disk_spec.schedule
+                        || job_leader_only != &disk_spec.job_leader_only
+                        // The Launcher binds the sockets when it spawns
+                        // the service
+                        || listen_ports != &disk_spec.listen_ports
+                        // The running service decides how to apply
+                        // configuration changes from the spec it was
+                        // started with
+                        || reload_signal != &disk_spec.reload_signal
+                        // Hooks are run with the timeouts the service
+                        // was started with
+                        || hook_timeouts != &disk_spec.hook_timeouts
+                        // The probes are handed to the health checks
+                        // when the service starts
+                        || health_probes != &disk_spec.health_probes
                     {
                         debug!("Reconciliation: '{}' queued for restart",
                                running_spec.ident);
@@ -475,9 +731,17 @@ impl ServiceSpec {
                             || channel != &disk_spec.channel
                             || update_strategy != &disk_spec.update_strategy
                             || update_condition != &disk_spec.update_condition
+                            || canary != &disk_spec.canary
+                            || rollout != &disk_spec.rollout
+                            || update_windows != &disk_spec.update_windows
+                            || update_hold != &disk_spec.update_hold
+                            || update_pin != &disk_spec.update_pin
                         {
                             ops.insert(RefreshOperation::RestartUpdater);
                         }
+                        if dependencies != &disk_spec.dependencies {
+                            ops.insert(RefreshOperation::UpdateDependencies);
+                        }
 
                         // We should have *something* to do down
                         // here, but if we don't, let's be explicit
@@ -504,6 +768,12 @@ pub(crate) enum RefreshOperation {
     /// This can happen if a user wants to change the channel a
     /// service is updating from, for instance.
     RestartUpdater,
+    /// Pick up a changed list of service dependencies.
+    ///
+    /// Dependencies only affect the order in which services are
+    /// started and stopped, so there is nothing to do to the running
+    /// process itself.
+    UpdateDependencies,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -540,6 +810,30 @@ impl FromStr for ServiceSpec {
     }
 }
 
+/// `Serialize` and `Deserialize` a list of `PackageIdent`s as a list of strings, rather than as
+/// an array of tables, so that it can appear anywhere in the spec file.
+mod ident_list {
+    use habitat_core::package::PackageIdent;
+    use serde::{de,
+                Deserialize,
+                Deserializer,
+                Serializer};
+
+    pub fn serialize<S>(idents: &[PackageIdent], s: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        s.collect_seq(idents.iter().map(ToString::to_string))
+    }
+
+    pub fn deserialize<'de, D>(d: D) -> Result<Vec<PackageIdent>, D::Error>
+        where D: Deserializer<'de>
+    {
+        Vec::<String>::deserialize(d)?.iter()
+                                      .map(|s| s.parse().map_err(de::Error::custom))
+                                      .collect()
+    }
+}
+
 impl TryFrom<habitat_sup_protocol::ctl::SvcLoad> for ServiceSpec {
     type Error = Error;
 
@@ -564,7 +858,8 @@ mod test {
     use tempfile::TempDir;
 
     use habitat_core::{package::PackageIdent,
-                       service::HealthCheckInterval};
+                       service::{CanarySize,
+                                 HealthCheckInterval}};
 
     use super::*;
     use crate::error::Error::*;
@@ -594,6 +889,7 @@ mod test {
     fn service_spec_from_str() {
         let toml = r#"
             ident = "origin/name/1.2.3/20170223130020"
+            instance = "reports"
             group = "jobs"
             bldr_url = "http://example.com/depot"
             topology = "leader"
@@ -601,15 +897,51 @@ mod test {
             update_condition = "latest"
             binds = ["cache:redis.cache@acmecorp", "db:postgres.app@acmecorp"]
             config_from = "/only/for/development"
+            dependencies = ["origin/db", "origin/cache/1.0.0"]
+            update_windows = ["Sat,Sun 01:00-05:00 UTC"]
+            update_hold = true
+            update_pin = ">=1.2, <2.0"
+            schedule = "30 2 * * Mon-Fri"
+            job_leader_only = true
+            listen_ports = [80, 443]
+            reload_signal = "HUP"
+            health_probes = ["tcp://localhost:6379", "exec:redis-cli ping"]
 
             [health_check_interval]
             secs = 5
             nanos = 0
+
+            [resource_limits]
+            memory_max = 536870912
+            cpu_max = 500
+
+            [process_controls]
+            nofile = 65536
+            cpu_affinity = "0-3"
+
+            [liveness]
+            failure_threshold = 3
+            grace_period = 60
+
+            [canary]
+            size = "10%"
+            bake_period = 600
+
+            [rollout]
+            batch_size = 5
+            health_timeout = 900
+
+            [env]
+            LOG_LEVEL = "debug"
+
+            [hook_timeouts]
+            init = 300
             "#;
         let spec = ServiceSpec::from_str(toml).unwrap();
 
         assert_eq!(spec.ident,
                    PackageIdent::from_str("origin/name/1.2.3/20170223130020").unwrap());
+        assert_eq!(spec.instance, Some(String::from("reports")));
         assert_eq!(spec.group, String::from("jobs"));
         assert_eq!(spec.bldr_url, String::from("http://example.com/depot"));
         assert_eq!(spec.topology, Topology::Leader);
@@ -620,8 +952,43 @@ mod test {
                         ServiceBind::from_str("db:postgres.app@acmecorp").unwrap(),]);
         assert_eq!(spec.config_from,
                    Some(PathBuf::from("/only/for/development")));
+        assert_eq!(spec.dependencies,
+                   vec![PackageIdent::from_str("origin/db").unwrap(),
+                        PackageIdent::from_str("origin/cache/1.0.0").unwrap(),]);
+        assert_eq!(spec.update_windows,
+                   vec![MaintenanceWindow::from_str("Sat,Sun 01:00-05:00 UTC").unwrap()]);
+        assert!(spec.update_hold);
+        assert_eq!(spec.update_pin,
+                   Some(VersionConstraint::from_str(">=1.2, <2.0").unwrap()));
+        assert_eq!(spec.schedule,
+                   Some(JobSchedule::from_str("30 2 * * Mon-Fri").unwrap()));
+        assert!(spec.job_leader_only);
+        assert_eq!(spec.listen_ports, vec![80, 443]);
+        assert_eq!(spec.reload_signal, Some(Signal::HUP));
+        assert_eq!(spec.health_probes,
+                   vec![HealthProbe::from_str("tcp://localhost:6379").unwrap(),
+                        HealthProbe::from_str("exec:redis-cli ping").unwrap()]);
         assert_eq!(spec.health_check_interval,
                    HealthCheckInterval::from_str("5").unwrap());
+        assert_eq!(spec.resource_limits,
+                   ResourceLimits { memory_max: Some(536_870_912),
+                                    cpu_max: Some(500),
+                                    ..Default::default() });
+        assert_eq!(spec.process_controls,
+                   ProcessControls { nofile: Some(65536),
+                                     cpu_affinity: Some("0-3".parse().unwrap()),
+                                     ..Default::default() });
+        assert_eq!(spec.liveness,
+                   LivenessPolicy { failure_threshold: Some(3),
+                                    grace_period:      Some(60), });
+        assert_eq!(spec.canary,
+                   CanaryPolicy { size:        Some(CanarySize::Percent(10)),
+                                  bake_period: Some(600), });
+        assert_eq!(spec.rollout,
+                   RolloutPolicy { batch_size:     Some(5),
+                                   health_timeout: Some(900), });
+        assert_eq!(spec.env.get("LOG_LEVEL").map(String::as_str), Some("debug"));
+        assert_eq!(spec.hook_timeouts.get("init"), Some(&300));
     }
 
     #[test]
@@ -678,9 +1045,16 @@ mod test {
 
     #[test]
     fn service_spec_to_toml_string() {
+        let update_window = MaintenanceWindow::from_str("* 02:00-04:00").unwrap();
+        let update_pin = VersionConstraint::from_str("1.2").unwrap();
+        let schedule = JobSchedule::from_str("0 3 * * * local").unwrap();
+        let health_probe = HealthProbe::from_str("http://localhost:8080/healthz 204").unwrap();
+        let env = vec![(String::from("LOG_LEVEL"), String::from("debug"))];
+        let hook_timeouts = vec![(String::from("reconfigure"), 30)];
         let spec =
             ServiceSpec { ident:                  PackageIdent::from_str("origin/name/1.2.3/\
                                                                           20170223130020").unwrap(),
+                          instance:               Some(String::from("reports")),
                           group:                  String::from("jobs"),
                           bldr_url:               String::from("http://example.com/depot"),
                           channel:                ChannelIdent::unstable(),
@@ -696,10 +1070,33 @@ mod test {
                           config_from:            Some(PathBuf::from("/only/for/development")),
                           desired_state:          DesiredState::Down,
                           svc_encrypted_password: None,
-                          shutdown_timeout:       Some(ShutdownTimeout::from_str("10").unwrap()), };
+                          shutdown_timeout:       Some(ShutdownTimeout::from_str("10").unwrap()),
+                          dependencies:
+                              vec![PackageIdent::from_str("origin/db").unwrap()],
+                          update_windows:         vec![update_window],
+                          update_hold:            true,
+                          update_pin:             Some(update_pin),
+                          schedule:               Some(schedule),
+                          job_leader_only:        true,
+                          listen_ports:           vec![8080],
+                          reload_signal:          Some(Signal::USR2),
+                          health_probes:          vec![health_probe],
+                          resource_limits:        ResourceLimits { pids_max: Some(64),
+                                                                   ..Default::default() },
+                          process_controls:       ProcessControls { nice: Some(-5),
+                                                                    ..Default::default() },
+                          liveness:               LivenessPolicy { failure_threshold: Some(5),
+                                                                   grace_period:      None, },
+                          canary:                 CanaryPolicy { bake_period: Some(60),
+                                                                 ..Default::default() },
+                          rollout:                RolloutPolicy { batch_size: Some(3),
+                                                                  ..Default::default() },
+                          env:                    env.into_iter().collect(),
+                          hook_timeouts:          hook_timeouts.into_iter().collect(), };
         let toml = spec.to_toml_string().unwrap();
 
         assert!(toml.contains(r#"ident = "origin/name/1.2.3/20170223130020""#,));
+        assert!(toml.contains(r#"instance = "reports""#));
         assert!(toml.contains(r#"group = "jobs""#));
         assert!(toml.contains(r#"bldr_url = "http://example.com/depot""#));
         assert!(toml.contains(r#"channel = "unstable""#));
@@ -714,6 +1111,29 @@ mod test {
         assert!(toml.contains(r#"secs = 123"#));
         assert!(toml.contains(r#"nanos = 0"#));
         assert!(toml.contains(r#"shutdown_timeout = 10"#));
+        assert!(toml.contains(r#"dependencies = ["origin/db"]"#));
+        assert!(toml.contains(r#"update_windows = ["* 02:00-04:00 UTC"]"#));
+        assert!(toml.contains(r#"update_hold = true"#));
+        assert!(toml.contains(r#"update_pin = "1.2""#));
+        assert!(toml.contains(r#"schedule = "0 3 * * * local""#));
+        assert!(toml.contains(r#"job_leader_only = true"#));
+        assert!(toml.contains(r#"listen_ports = [8080]"#));
+        assert!(toml.contains(r#"reload_signal = "USR2""#));
+        assert!(toml.contains(r#"health_probes = ["http://localhost:8080/healthz 204"]"#));
+        assert!(toml.contains(r#"[resource_limits]"#));
+        assert!(toml.contains(r#"pids_max = 64"#));
+        assert!(toml.contains(r#"[process_controls]"#));
+        assert!(toml.contains(r#"nice = -5"#));
+        assert!(toml.contains(r#"[liveness]"#));
+        assert!(toml.contains(r#"failure_threshold = 5"#));
+        assert!(toml.contains(r#"[canary]"#));
+        assert!(toml.contains(r#"bake_period = 60"#));
+        assert!(toml.contains(r#"[rollout]"#));
+        assert!(toml.contains(r#"batch_size = 3"#));
+        assert!(toml.contains(r#"[env]"#));
+        assert!(toml.contains(r#"LOG_LEVEL = "debug""#));
+        assert!(toml.contains(r#"[hook_timeouts]"#));
+        assert!(toml.contains(r#"reconfigure = 30"#));
     }
 
     #[test]
@@ -850,6 +1270,7 @@ mod test {
         let spec =
             ServiceSpec { ident:                  PackageIdent::from_str("origin/name/1.2.3/\
                                                                           20170223130020").unwrap(),
+                          instance:               None,
                           group:                  String::from("jobs"),
                           bldr_url:               String::from("http://example.com/depot"),
                           channel:                ChannelIdent::unstable(),
@@ -865,7 +1286,23 @@ mod test {
                           config_from:            Some(PathBuf::from("/only/for/development")),
                           desired_state:          DesiredState::Down,
                           svc_encrypted_password: None,
-                          shutdown_timeout:       Some(ShutdownTimeout::default()), };
+                          shutdown_timeout:       Some(ShutdownTimeout::default()),
+                          dependencies:           Vec::new(),
+                          update_windows:         Vec::new(),
+                          update_hold:            false,
+                          update_pin:             None,
+                          schedule:               None,
+                          job_leader_only:        false,
+                          listen_ports:           Vec::new(),
+                          reload_signal:          None,
+                          health_probes:          Vec::new(),
+                          resource_limits:        ResourceLimits::default(),
+                          process_controls:       ProcessControls::default(),
+                          liveness:               LivenessPolicy::default(),
+                          canary:                 CanaryPolicy::default(),
+                          rollout:                RolloutPolicy::default(),
+                          env:                    BTreeMap::new(),
+                          hook_timeouts:          BTreeMap::new(), };
         spec.to_file(&path).unwrap();
         let toml = string_from_file(path);
 
@@ -883,6 +1320,7 @@ mod test {
         assert!(toml.contains(r#"[health_check_interval]"#));
         assert!(toml.contains(r#"secs = 23"#));
         assert!(toml.contains(r#"nanos = 0"#));
+        assert!(!toml.contains("update_hold"));
     }
 
     #[test]
@@ -904,6 +1342,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn service_spec_depends_on() {
+        let mut spec = ServiceSpec::new(PackageIdent::from_str("origin/api").unwrap());
+        spec.dependencies = vec![PackageIdent::from_str("origin/db").unwrap(),
+                                 PackageIdent::from_str("origin/cache/1.0.0").unwrap(),];
+        let depends_on = |ident: &str| spec.depends_on(&PackageIdent::from_str(ident).unwrap());
+
+        assert!(depends_on("origin/db/2.0.0/20200101000000"));
+        assert!(depends_on("origin/cache/1.0.0/20200101000000"));
+        assert!(!depends_on("origin/cache/2.0.0/20200101000000"));
+        assert!(!depends_on("other/db"));
+    }
+
     #[test]
     fn service_spec_file_name() {
         let spec = ServiceSpec::new(PackageIdent::from_str("origin/hoopa/1.2.3").unwrap());
@@ -911,6 +1362,41 @@ mod test {
         assert_eq!(Path::new("hoopa.spec"), spec.file());
     }
 
+    #[test]
+    fn service_spec_file_name_of_instance() {
+        let mut spec = ServiceSpec::new(PackageIdent::from_str("origin/hoopa/1.2.3").unwrap());
+        spec.instance = Some(String::from("cache"));
+
+        assert_eq!("hoopa+cache", spec.service_name());
+        assert_eq!(Path::new("hoopa+cache.spec"), spec.file());
+    }
+
+    #[test]
+    fn service_spec_from_svc_load_with_instance() {
+        let ident = PackageIdent::from_str("core/redis").unwrap();
+        let svc_load = habitat_sup_protocol::ctl::SvcLoad { ident: Some(ident.into()),
+                                                            instance: Some("cache".to_string()),
+                                                            ..Default::default() };
+        let spec = ServiceSpec::try_from(svc_load.clone()).unwrap();
+        assert_eq!(spec.instance, Some("cache".to_string()));
+        assert_eq!(spec.service_name(), "redis+cache");
+
+        let svc_load = habitat_sup_protocol::ctl::SvcLoad { instance: Some("ca.che".to_string()),
+                                                            ..svc_load };
+        assert!(ServiceSpec::try_from(svc_load).is_err());
+    }
+
+    #[test]
+    fn service_spec_from_svc_load_with_out_of_range_priorities() {
+        let ident = PackageIdent::from_str("core/redis").unwrap();
+        let controls = habitat_sup_protocol::types::ProcessControls { ionice: Some(8),
+                                                                      ..Default::default() };
+        let svc_load = habitat_sup_protocol::ctl::SvcLoad { ident: Some(ident.into()),
+                                                            process_controls: Some(controls),
+                                                            ..Default::default() };
+        assert!(ServiceSpec::try_from(svc_load).is_err());
+    }
+
     fn testing_package_install() -> PackageInstall {
         let ident = if cfg!(target_os = "linux") {
             if cfg!(target_arch = "x86_64") {
@@ -1005,6 +1491,65 @@ mod test {
         }
     }
 
+    #[test]
+    /// Test when we're asking for a timeout on a hook that can't have one, or of zero
+    fn service_spec_error_invalid_hook_timeouts() {
+        let package = testing_package_install();
+
+        let mut spec = ServiceSpec::new(package.ident().clone());
+        spec.binds = vec![ServiceBind::from_str("database:postgres.app@acmecorp").unwrap()];
+        spec.hook_timeouts = vec![(String::from("init"), 0),
+                                  (String::from("post_run"), 30),
+                                  (String::from("reconfigure"), 30)].into_iter()
+                                                                    .collect();
+        match spec.validate(&package) {
+            Err(InvalidHookTimeouts(t)) => {
+                assert_eq!(vec!["init=0".to_string(), "post_run=30".to_string()], t)
+            }
+            Err(wrong) => panic!("Unexpected error returned: {:?}", wrong),
+            Ok(_) => panic!("Spec should not validate"),
+        }
+    }
+
+    #[test]
+    /// Test when we're asking to run a job only on the leader without the leader topology
+    fn service_spec_error_job_leader_only_without_leader() {
+        let package = testing_package_install();
+
+        let mut spec = ServiceSpec::new(package.ident().clone());
+        spec.binds = vec![ServiceBind::from_str("database:postgres.app@acmecorp").unwrap()];
+        spec.job_leader_only = true;
+        match spec.validate(&package) {
+            Err(JobLeaderOnlyWithoutLeader) => (),
+            Err(wrong) => panic!("Unexpected error returned: {:?}", wrong),
+            Ok(_) => panic!("Spec should not validate"),
+        }
+
+        spec.topology = Topology::Leader;
+        if let Err(e) = spec.validate(&package) {
+            panic!("Unexpected error returned: {:?}", e);
+        }
+    }
+
+    #[test]
+    /// Test when we're asking to listen on a port the package doesn't expose
+    fn service_spec_error_unexposed_listen_port() {
+        let package = testing_package_install();
+
+        let mut spec = ServiceSpec::new(package.ident().clone());
+        spec.binds = vec![ServiceBind::from_str("database:postgres.app@acmecorp").unwrap()];
+        spec.listen_ports = vec![8080];
+        match spec.validate(&package) {
+            Err(e) => {
+                match e {
+                    InvalidListenPorts(p) => assert_eq!(vec![8080], p),
+                    wrong => panic!("Unexpected error returned: {:?}", wrong),
+                }
+            }
+            Ok(_) => panic!("Spec should not validate"),
+        }
+    }
+
     /// This is to support backward compatibility with the old
     /// application/environment functionality that is being removed.
     #[test]
@@ -1147,6 +1692,10 @@ mod test {
                    restart,
                    ident,
                    "core/foo".parse().unwrap());
+        reconcile!(instance_causes_restart,
+                   restart,
+                   instance,
+                   Some("cache".to_string()));
         reconcile!(group_causes_restart, restart, group, "prod".to_string());
         reconcile!(topology_causes_restart, restart, topology, Topology::Leader);
         reconcile!(binds_causes_restart,
@@ -1173,6 +1722,46 @@ mod test {
                    restart,
                    health_check_interval,
                    10000.into());
+        reconcile!(resource_limits_causes_restart,
+                   restart,
+                   resource_limits,
+                   ResourceLimits { memory_max: Some(1024),
+                                    ..Default::default() });
+        reconcile!(process_controls_causes_restart,
+                   restart,
+                   process_controls,
+                   ProcessControls { nofile: Some(65536),
+                                     ..Default::default() });
+        reconcile!(liveness_causes_restart,
+                   restart,
+                   liveness,
+                   LivenessPolicy { failure_threshold: Some(3),
+                                    grace_period:      None, });
+        reconcile!(env_causes_restart,
+                   restart,
+                   env,
+                   BTreeMap::from_iter(vec![("LOG_LEVEL".to_string(), "debug".to_string())]));
+        reconcile!(schedule_causes_restart,
+                   restart,
+                   schedule,
+                   Some("0 * * * *".parse().unwrap()));
+        reconcile!(job_leader_only_causes_restart,
+                   restart,
+                   job_leader_only,
+                   true);
+        reconcile!(listen_ports_causes_restart, restart, listen_ports, vec![80]);
+        reconcile!(reload_signal_causes_restart,
+                   restart,
+                   reload_signal,
+                   Some(Signal::HUP));
+        reconcile!(hook_timeouts_causes_restart,
+                   restart,
+                   hook_timeouts,
+                   BTreeMap::from_iter(vec![("init".to_string(), 300)]));
+        reconcile!(health_probes_causes_restart,
+                   restart,
+                   health_probes,
+                   vec!["tcp://localhost:6379".parse().unwrap()]);
 
         reconcile!(bldr_url_causes_update,
                    update,
@@ -1194,5 +1783,37 @@ mod test {
                    update_condition,
                    UpdateCondition::TrackChannel,
                    vec![RefreshOperation::RestartUpdater]);
+        reconcile!(canary_causes_update,
+                   update,
+                   canary,
+                   CanaryPolicy { size:        Some(CanarySize::Percent(25)),
+                                  bake_period: None, },
+                   vec![RefreshOperation::RestartUpdater]);
+        reconcile!(rollout_causes_update,
+                   update,
+                   rollout,
+                   RolloutPolicy { batch_size:     Some(5),
+                                   health_timeout: None, },
+                   vec![RefreshOperation::RestartUpdater]);
+        reconcile!(update_windows_causes_update,
+                   update,
+                   update_windows,
+                   vec!["Sat 01:00-05:00".parse().unwrap()],
+                   vec![RefreshOperation::RestartUpdater]);
+        reconcile!(update_hold_causes_update,
+                   update,
+                   update_hold,
+                   true,
+                   vec![RefreshOperation::RestartUpdater]);
+        reconcile!(update_pin_causes_update,
+                   update,
+                   update_pin,
+                   Some("1.2".parse().unwrap()),
+                   vec![RefreshOperation::RestartUpdater]);
+        reconcile!(dependencies_causes_update,
+                   update,
+                   dependencies,
+                   vec!["core/db".parse().unwrap()],
+                   vec![RefreshOperation::UpdateDependencies]);
     }
 }
@@ -11,6 +11,7 @@ use crate::{error::{Error,
                       ShutdownConfig}};
 use anyhow::anyhow;
 use habitat_common::{outputln,
+                     service_log,
                      templating::package::Pkg,
                      types::UserInfo};
 #[cfg(unix)]
@@ -19,7 +20,9 @@ use habitat_core::{fs,
                    fs::{AtomicWriter,
                         Permissions},
                    os::process::{self,
-                                 Pid},
+                                 Pid,
+                                 ProcessControls,
+                                 ResourceLimits},
                    service::ServiceGroup};
 use habitat_launcher_client::LauncherCli;
 #[cfg(windows)]
@@ -284,7 +287,10 @@ impl Supervisor {
                  pkg: &Pkg,
                  group: &ServiceGroup,
                  launcher: &LauncherCli,
-                 svc_password: Option<&str>)
+                 svc_password: Option<&str>,
+                 resource_limits: ResourceLimits,
+                 process_controls: ProcessControls,
+                 listen_ports: &[u16])
                  -> Result<()> {
         let user_info = self.user_info(pkg, launcher)?;
         outputln!(preamble self.service_group,
@@ -315,7 +321,11 @@ impl Supervisor {
                                  &pkg.svc_run,
                                  user_info,
                                  svc_password, // Windows optional
-                                 (*pkg.env).clone())?;
+                                 (*pkg.env).clone(),
+                                 resource_limits,
+                                 process_controls,
+                                 &service_log::path(&pkg.svc_var_path),
+                                 listen_ports)?;
         if pid == 0 {
             warn!(target: "pidfile_tracing", "Spawned service for {} has a PID of 0!", group);
         }
@@ -336,6 +346,11 @@ impl Supervisor {
             if pid == 0 {
                 warn!(target: "pidfile_tracing", "Cowardly refusing to stop {}, because we think it has a PID of 0, which makes no sense",
                       service_group);
+            } else if !process::is_alive(pid) {
+                // Such as when its pre-stop hook already stopped it
+                debug!("{} (PID: {}) has already exited; not signalling it",
+                       service_group, pid);
+                Self::cleanup_pidfile(&self.pid_file);
             } else {
                 tokio::spawn(async move {
                     if terminator::terminate_service(pid, service_group.clone(),
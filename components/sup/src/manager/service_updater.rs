@@ -5,25 +5,36 @@ use self::{package_update_worker::PackageUpdateWorker,
            rolling_update_worker::RollingUpdateWorker};
 use crate::{census::CensusRing,
             manager::service::{Service,
-                               UpdateStrategy}};
+                               UpdateStrategy},
+            util::maintenance};
 use futures::future::{self,
                       AbortHandle};
 use habitat_common::outputln;
-use habitat_core::{package::PackageIdent,
+use habitat_core::{fs::AtomicWriter,
+                   package::PackageIdent,
                    service::ServiceGroup};
-use log::debug;
+use log::{debug,
+          warn};
 use parking_lot::{Mutex,
                   RwLock};
 use std::{self,
           cmp::Ordering,
           collections::HashMap,
           fmt,
+          fs,
           future::Future,
+          io::{self,
+               Write},
+          path::Path,
           sync::Arc,
           time::Duration};
 
 static LOGKEY: &str = "SU";
 
+/// The file in a service's directory recording the last release the service was rolled back from.
+/// Keeping it on disk means the release is not updated to again after the Supervisor restarts.
+const FAILED_RELEASE_FILE: &str = "FAILED_RELEASE";
+
 /// A handle to an update worker that automatically aborts the worker when dropped.
 struct Worker(AbortHandle);
 
@@ -87,6 +98,10 @@ impl ServiceUpdater {
         self.remove(&service.service_group);
         // Determine what kind of worker we should use
         let service_group = service.service_group.clone();
+        if service.spec_ref().update_hold {
+            debug!("Updates to {} are on hold", service);
+            return;
+        }
         match service.update_strategy() {
             UpdateStrategy::None => {
                 debug!("No updater registered for for {}", service);
@@ -101,6 +116,12 @@ impl ServiceUpdater {
                 let worker = self.rolling_worker(service, Arc::clone(&self.census_ring));
                 self.spawn_worker(service_group, worker);
             }
+            UpdateStrategy::Canary => {
+                debug!("Registering canary updater for {}", service);
+                // A canary update is a rolling update that holds back after the canaries
+                let worker = self.rolling_worker(service, Arc::clone(&self.census_ring));
+                self.spawn_worker(service_group, worker);
+            }
         };
     }
 
@@ -110,6 +131,21 @@ impl ServiceUpdater {
         self.updates.lock().remove(service_group);
     }
 
+    /// Record that a service was rolled back from `release` so that it is not updated to it again.
+    /// The service is updated once a different release appears. This should be called before the
+    /// service is registered again. The release is recorded in the service's directory so that it
+    /// is remembered across Supervisor restarts.
+    pub fn record_failed_release(&mut self, service: &Service, release: &PackageIdent) {
+        let path = service.pkg.svc_path.join(FAILED_RELEASE_FILE);
+        if let Err(err) = Self::write_failed_release(&path, release) {
+            warn!("Failed to record failed release {} of {} in {}, err: {}",
+                  release,
+                  service,
+                  path.display(),
+                  err);
+        }
+    }
+
     /// Check if this service has an update. If it does return the package ident of the update.
     ///
     /// Once an update is detected, this function will always return an update until the service is
@@ -129,11 +165,14 @@ impl ServiceUpdater {
         let service_group = service.service_group.clone();
         let full_ident = service.pkg.ident.clone();
         let updates = Arc::clone(&self.updates);
-        let package_update_worker = PackageUpdateWorker::new(service, self.period);
+        let windows = service.spec_ref().update_windows.clone();
+        let package_update_worker =
+            PackageUpdateWorker::new(service, self.failed_release(service), self.period);
         async move {
             let new_ident = package_update_worker.update().await;
             debug!("'{}' at-once updater found update from '{}' to '{}'",
                    service_group, full_ident, new_ident);
+            maintenance::wait_for_window(&windows).await;
             Self::update_message(&new_ident, full_ident.as_ref());
             updates.lock().insert(service_group, new_ident);
         }
@@ -151,17 +190,42 @@ impl ServiceUpdater {
         let service_group = service.service_group.clone();
         let full_ident = service.pkg.ident.clone();
         let updates = Arc::clone(&self.updates);
-        let worker =
-            RollingUpdateWorker::new(service, census_ring, self.butterfly.clone(), self.period);
+        let windows = service.spec_ref().update_windows.clone();
+        let worker = RollingUpdateWorker::new(service,
+                                              census_ring,
+                                              self.butterfly.clone(),
+                                              self.failed_release(service),
+                                              self.period);
         async move {
             let new_ident = worker.run().await;
             debug!("'{}' rolling updater found update from '{}' to '{}'",
                    service_group, full_ident, new_ident);
+            maintenance::wait_for_window(&windows).await;
             Self::update_message(&new_ident, full_ident.as_ref());
             updates.lock().insert(service_group, new_ident);
         }
     }
 
+    fn write_failed_release(path: &Path, release: &PackageIdent) -> io::Result<()> {
+        let w = AtomicWriter::new(path)?;
+        w.with_writer(|f| f.write_all(release.to_string().as_bytes()))
+    }
+
+    fn failed_release(&self, service: &Service) -> Option<PackageIdent> {
+        let path = service.pkg.svc_path.join(FAILED_RELEASE_FILE);
+        let release = fs::read_to_string(&path).ok()?;
+        match release.trim().parse() {
+            Ok(release) => Some(release),
+            Err(err) => {
+                warn!("Ignoring failed release of {} recorded in {}, err: {}",
+                      service,
+                      path.display(),
+                      err);
+                None
+            }
+        }
+    }
+
     fn update_message(new_ident: &IncarnatedPackageIdent, current_ident: &PackageIdent) {
         match &new_ident.ident.cmp(current_ident) {
             Ordering::Greater => outputln!("Updating from {} to {}", current_ident, new_ident),
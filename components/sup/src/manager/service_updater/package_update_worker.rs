@@ -1,10 +1,12 @@
 use super::IncarnatedPackageIdent;
-use crate::{manager::service::Service,
+use crate::{error::Result,
+            manager::service::Service,
             util};
 use habitat_core::{self,
                    package::{FullyQualifiedPackageIdent,
                              Identifiable,
-                             PackageIdent},
+                             PackageIdent,
+                             VersionConstraint},
                    service::ServiceGroup,
                    ChannelIdent};
 use habitat_sup_protocol::types::UpdateCondition;
@@ -54,11 +56,14 @@ impl PackageUpdateWorkerPeriod {
 
 /// When `run`, a `PackageUpdateWorker` returns a future that continuously checks for a change in
 /// version of the package being run by a service. If a change is detected, the package is installed
-/// and its identifier returned.
+/// and its identifier returned. A release the service was rolled back from is never updated to,
+/// and neither is a release outside the service's version pin.
 pub struct PackageUpdateWorker {
     service_group:    ServiceGroup,
     ident:            PackageIdent,
     full_ident:       FullyQualifiedPackageIdent,
+    failed_release:   Option<PackageIdent>,
+    update_pin:       Option<VersionConstraint>,
     update_condition: UpdateCondition,
     channel:          ChannelIdent,
     builder_url:      String,
@@ -66,10 +71,12 @@ pub struct PackageUpdateWorker {
 }
 
 impl PackageUpdateWorker {
-    pub fn new(service: &Service, period: Duration) -> Self {
+    pub fn new(service: &Service, failed_release: Option<PackageIdent>, period: Duration) -> Self {
         Self { service_group: service.service_group.clone(),
                ident: service.spec_ident(),
                full_ident: service.pkg.ident.clone(),
+               failed_release,
+               update_pin: service.spec_ref().update_pin.clone(),
                update_condition: service.update_condition(),
                channel: service.channel(),
                builder_url: service.bldr_url(),
@@ -78,6 +85,28 @@ impl PackageUpdateWorker {
 }
 
 impl PackageUpdateWorker {
+    /// Returns `true` if the service's version pin allows updating to `ident`.
+    fn pin_allows(&self, ident: &PackageIdent) -> bool {
+        match (&self.update_pin, &ident.version) {
+            (Some(pin), Some(version)) => pin.matches(version),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    /// Returns the release in the channel to update to without installing it. That is the head of
+    /// the channel or, if the head is outside the service's version pin, the newest release in the
+    /// channel within the pin.
+    async fn release_in_channel(&self, ident: &PackageIdent) -> Result<Option<PackageIdent>> {
+        let head = util::pkg::channel_head(&self.builder_url, ident, &self.channel).await?;
+        match &self.update_pin {
+            Some(pin) if !self.pin_allows(&head) => {
+                util::pkg::newest_in_channel(&self.builder_url, ident, &self.channel, pin).await
+            }
+            _ => Ok(Some(head)),
+        }
+    }
+
     /// Use the specified package ident to search for packages.
     ///
     /// If a fully qualified package ident is used, the future will only resolve when that exact
@@ -86,7 +115,44 @@ impl PackageUpdateWorker {
     pub async fn update_to(&self, ident: IncarnatedPackageIdent) -> IncarnatedPackageIdent {
         let period = PackageUpdateWorkerPeriod::get().unwrap_or(self.period);
         loop {
-            let install_source = ident.ident.clone().into();
+            // Resolve the release to update to before installing anything. A rolling update may
+            // ask for, or the channel may still hold, the exact release this service was rolled
+            // back from. Wait for a different release instead of installing it.
+            let release = if ident.ident.fully_qualified() {
+                ident.ident.clone()
+            } else {
+                match self.release_in_channel(&ident.ident).await {
+                    Ok(Some(release)) => release,
+                    Ok(None) => {
+                        debug!("'{}' package update worker found no release of '{}' in channel \
+                                '{}' within the version pin",
+                               self.service_group, ident.ident, self.channel);
+                        time::sleep(period).await;
+                        continue;
+                    }
+                    Err(err) => {
+                        warn!("'{}' package update worker failed to find '{}' in channel '{}', \
+                               err: {}",
+                              self.service_group, self.ident, self.channel, err);
+                        time::sleep(period).await;
+                        continue;
+                    }
+                }
+            };
+            if self.failed_release.as_ref() == Some(&release) {
+                debug!("'{}' package update worker is not updating to '{}', the service was \
+                        rolled back from it",
+                       self.service_group, release);
+                time::sleep(period).await;
+                continue;
+            }
+            if ident.ident.fully_qualified() && !self.pin_allows(&ident.ident) {
+                debug!("'{}' package update worker is not updating to '{}', it is outside the \
+                        version pin",
+                       self.service_group, ident.ident);
+                time::sleep(period).await;
+                continue;
+            }
 
             // Rolling updating followers will always update to a fully qulified ident
             // if we are fully qualified, just update to that version and do not check
@@ -95,21 +161,16 @@ impl PackageUpdateWorker {
             // be in the channel which can cause this loop to run indefinitely. Just
             // finish up this update and let the leader roll the followers back when it
             // determines the new head.
-            let package_result = if ident.ident.fully_qualified() {
-                util::pkg::install_no_ui(&self.builder_url, &install_source, &self.channel).await
-            } else {
-                match self.update_condition {
-                    UpdateCondition::Latest => {
-                        let install_source = ident.ident.clone().into();
-                        util::pkg::install_no_ui(&self.builder_url, &install_source, &self.channel).await
-                    }
-                    UpdateCondition::TrackChannel => {
-                        util::pkg::install_channel_head(&self.builder_url,
-                                                        &ident.ident,
-                                                        &self.channel).await
-                    }
+            let install_source = match self.update_condition {
+                UpdateCondition::Latest
+                    if !ident.ident.fully_qualified() && self.update_pin.is_none() =>
+                {
+                    ident.ident.clone().into()
                 }
+                _ => release.into(),
             };
+            let package_result =
+                util::pkg::install_no_ui(&self.builder_url, &install_source, &self.channel).await;
             match package_result {
                 Ok(package) => {
                     // while this is likely a very slim edge case, if the fully qualified ident
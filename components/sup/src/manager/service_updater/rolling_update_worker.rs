@@ -1,11 +1,17 @@
 use super::{package_update_worker::PackageUpdateWorker,
             IncarnatedPackageIdent};
 use crate::{census::{CensusGroup,
+                     CensusMember,
                      CensusRing},
-            manager::service::{Service,
-                               Topology}};
+            manager::service::{HealthCheckResult,
+                               Service,
+                               Topology,
+                               UpdateStrategy}};
 use habitat_common::owning_refs::RwLockReadGuardRef;
-use habitat_core::service::ServiceGroup;
+use habitat_core::{package::PackageIdent,
+                   service::{CanaryPolicy,
+                             RolloutPolicy,
+                             ServiceGroup}};
 use log::{debug,
           error,
           trace,
@@ -13,7 +19,8 @@ use log::{debug,
 use parking_lot::RwLock;
 use std::{self,
           sync::Arc,
-          time::Duration};
+          time::{Duration,
+                 Instant}};
 use tokio::{self,
             time};
 
@@ -50,13 +57,31 @@ enum FollowerUpdateTurnEvent {
     UpdateTo(IncarnatedPackageIdent),
 }
 
+/// A follower waiting for the previous batch of a health gated rollout to become healthy.
+#[derive(Default)]
+struct HealthWait {
+    /// When the previous batch was first seen running the leader's package
+    since:     Option<Instant>,
+    /// The leader package incarnation the update halted at, if it did
+    halted_at: Option<u64>,
+}
+
 /// The worker for handling rolling updates.
 ///
 /// The basic behavior of the update is to elect an update leader. The leader waits for an update.
-/// When an update is detected, the leader is updated and each follower takes a turn to update.
+/// When an update is detected, the leader is updated and the followers take turns to update in
+/// batches. Each batch waits for the previous one, and the first batch waits for the leader. When
+/// the rollout has a health timeout a batch also waits for the previous one to report an `Ok`
+/// health check, and the update halts if that doesn't happen in time.
+///
+/// With the canary strategy the first followers to update, together with the leader, are the
+/// canaries. The rest of the group holds its turn until the canaries have been ready for the bake
+/// period.
 pub struct RollingUpdateWorker {
     service_group:         ServiceGroup,
     topology:              Topology,
+    canary:                Option<CanaryPolicy>,
+    rollout:               RolloutPolicy,
     package_update_worker: PackageUpdateWorker,
     census_ring:           Arc<RwLock<CensusRing>>,
     butterfly:             habitat_butterfly::Server,
@@ -66,11 +91,18 @@ impl RollingUpdateWorker {
     pub fn new(service: &Service,
                census_ring: Arc<RwLock<CensusRing>>,
                butterfly: habitat_butterfly::Server,
+               failed_release: Option<PackageIdent>,
                period: Duration)
                -> Self {
+        let canary = match service.update_strategy() {
+            UpdateStrategy::Canary => Some(service.canary()),
+            _ => None,
+        };
         Self { service_group: service.service_group.clone(),
                topology: service.topology(),
-               package_update_worker: PackageUpdateWorker::new(service, period),
+               canary,
+               rollout: service.rollout(),
+               package_update_worker: PackageUpdateWorker::new(service, failed_release, period),
                census_ring,
                butterfly }
     }
@@ -271,6 +303,9 @@ impl RollingUpdateWorker {
             }
             FollowerUpdateStartEvent::UpdateTo(ident) => ident,
         };
+        // When the canaries were last seen to all be updated and ready
+        let mut baking_since = None;
+        let mut health_wait = HealthWait::default();
         loop {
             {
                 let census_group = self.census_group().await;
@@ -294,12 +329,19 @@ impl RollingUpdateWorker {
                                    leader.member_id,
                                    leader.pkg_incarnation,
                                    census_group.pkg_incarnation);
-                        } else if peer.pkg_incarnation == leader.pkg_incarnation {
-                            // It is now this followers turn. The previous peer is done updating.
-                            // The first time this condition is true the previous peer is the
-                            // rolling update leader making this condition trivially true. This
-                            // will trigger all the followers to start their updates one after
-                            // another.
+                        } else if self.previous_batch_done(&census_group,
+                                                           leader,
+                                                           peer,
+                                                           &mut health_wait)
+                                  && !self.held_by_canaries(&census_group,
+                                                            leader,
+                                                            me,
+                                                            &mut baking_since)
+                        {
+                            // It is now this followers turn. The previous batch is done updating.
+                            // For the first batch the previous batch is the rolling update leader.
+                            // This will trigger the followers to start their updates one batch
+                            // after another.
                             debug!("'{}' is in a rolling update and it is this followers turn to \
                                     update: leader='{}/{}/{}' peer='{}/{}/{}' follower='{}/{}/{}'",
                                    self.service_group,
@@ -315,8 +357,9 @@ impl RollingUpdateWorker {
                             break FollowerUpdateTurnEvent::UpdateTo(IncarnatedPackageIdent::new(leader.pkg.clone(),
                             Some(leader.pkg_incarnation)));
                         } else {
-                            // It is not this followers turn to update. The previous peer has not
-                            // updated yet.
+                            // It is not this followers turn to update. The previous batch has not
+                            // updated or become healthy yet, or the canaries of a canary update
+                            // are still baking.
                             debug!("'{}' is in a rolling update but it is not this followers \
                                     turn to update: leader='{}/{}/{}' peer='{}/{}/{}' \
                                     follower='{}/{}/{}'",
@@ -344,6 +387,118 @@ impl RollingUpdateWorker {
         }
     }
 
+    /// Determine if the members that update right before this follower are done. These are the
+    /// previous batch, or just the previous peer with a batch size of one. They must all be running
+    /// the leader's package and, when the rollout has a health timeout, report an `Ok` health
+    /// check. If they don't become healthy within the timeout the update halts here until the
+    /// leader starts another one. Members whose Supervisor doesn't report health, because it
+    /// predates health being gossiped, aren't waited for.
+    fn previous_batch_done(&self,
+                           census_group: &CensusGroup,
+                           leader: &CensusMember,
+                           peer: &CensusMember,
+                           health_wait: &mut HealthWait)
+                           -> bool {
+        let mut batch = census_group.previous_update_batch(self.rollout.batch_size());
+        if batch.is_empty() {
+            batch.push(peer);
+        }
+        if batch.iter()
+                .any(|member| member.pkg_incarnation != leader.pkg_incarnation)
+        {
+            health_wait.since = None;
+            return false;
+        }
+        let timeout = match self.rollout.health_timeout() {
+            Some(timeout) => timeout,
+            None => return true,
+        };
+        if health_wait.halted_at == Some(leader.pkg_incarnation) {
+            return false;
+        }
+        let unhealthy = batch.iter()
+                             .filter(|member| {
+                                 member.health
+                                       .map_or(false, |health| health != HealthCheckResult::Ok)
+                             })
+                             .map(|member| member.member_id.as_str())
+                             .collect::<Vec<_>>();
+        if unhealthy.is_empty() {
+            return true;
+        }
+
+        let waited_for = health_wait.since.get_or_insert_with(Instant::now).elapsed();
+        if waited_for >= timeout {
+            error!("'{}' rolling update to '{}' has halted. Members {} did not report an OK \
+                    health check within {}s of updating.",
+                   self.service_group,
+                   leader.pkg,
+                   unhealthy.join(", "),
+                   timeout.as_secs());
+            health_wait.halted_at = Some(leader.pkg_incarnation);
+        } else {
+            debug!("'{}' is waiting {}s of {}s for members {} to report an OK health check",
+                   self.service_group,
+                   waited_for.as_secs(),
+                   timeout.as_secs(),
+                   unhealthy.join(", "));
+        }
+        false
+    }
+
+    /// Determine if this follower must wait for the canaries of a canary update before taking its
+    /// turn. Every follower that is not a canary waits, since with batched updates more than one
+    /// follower can come right after the canaries. The canaries must all be running the
+    /// leader's package and be ready for the whole bake period. If any of them stops being ready
+    /// the bake period starts over.
+    fn held_by_canaries(&self,
+                        census_group: &CensusGroup,
+                        leader: &CensusMember,
+                        me: &CensusMember,
+                        baking_since: &mut Option<Instant>)
+                        -> bool {
+        let policy = match self.canary {
+            Some(policy) => policy,
+            None => return false,
+        };
+        let canaries = census_group.update_canaries(policy.size());
+        let is_canary = |member: &CensusMember| {
+            canaries.iter()
+                    .any(|canary| canary.member_id == member.member_id)
+        };
+        if is_canary(me) {
+            return false;
+        }
+
+        let healthy =
+            canaries.iter().all(|canary| {
+                               canary.pkg_incarnation == leader.pkg_incarnation && canary.ready()
+                           });
+        if !healthy {
+            if baking_since.take().is_some() {
+                warn!("'{}' canary update is on hold; not every canary is ready. The bake period \
+                       restarts once they all are.",
+                      self.service_group);
+            } else {
+                debug!("'{}' is waiting for all {} canaries to update and become ready",
+                       self.service_group,
+                       canaries.len());
+            }
+            return true;
+        }
+
+        let baked_for = baking_since.get_or_insert_with(Instant::now).elapsed();
+        if baked_for < policy.bake_period() {
+            debug!("'{}' canaries have been ready for {}s of the {}s bake period",
+                   self.service_group,
+                   baked_for.as_secs(),
+                   policy.bake_period().as_secs());
+            true
+        } else {
+            false
+        }
+    }
+
     /// Returns a reference to the services census group. The reference is behind a read write lock
     /// so the lifetime of the reference should be minimized to avoid uneccesarily holding the lock.
     async fn census_group(&self) -> RwLockReadGuardRef<'_, CensusRing, CensusGroup> {
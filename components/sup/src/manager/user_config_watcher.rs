@@ -35,7 +35,7 @@ pub trait Serviceable {
 }
 
 impl Serviceable for Service {
-    fn name(&self) -> &str { &self.pkg.name }
+    fn name(&self) -> &str { self.service_group.service() }
 
     fn user_config_path(&self) -> &UserConfigPath { &self.cfg.user_config_path }
 
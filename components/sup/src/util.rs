@@ -1 +1,4 @@
+pub mod diff;
+pub mod history;
+pub mod maintenance;
 pub mod pkg;
@@ -0,0 +1,217 @@
+//! Line based unified diffs, for showing how rendered files would
+//! change.
+
+/// How many unchanged lines are shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+impl<'a> Line<'a> {
+    fn in_old(self) -> bool { !matches!(self, Line::Added(_)) }
+
+    fn in_new(self) -> bool { !matches!(self, Line::Removed(_)) }
+}
+
+/// Returns a unified diff of the file at `path` changing from `old`
+/// to `new`, or an empty string if their lines are the same.
+pub fn unified(path: &str, old: &str, new: &str) -> String {
+    let lines = diff_lines(&old.lines().collect::<Vec<_>>(),
+                           &new.lines().collect::<Vec<_>>());
+    let changes = lines.iter()
+                       .enumerate()
+                       .filter(|(_, line)| !matches!(line, Line::Same(_)))
+                       .map(|(i, _)| i)
+                       .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut diff = format!("--- {}\n+++ {}\n", path, path);
+    let mut changes = changes.into_iter().peekable();
+    while let Some(first) = changes.next() {
+        // Changes close enough together that their context would meet
+        // share a hunk
+        let mut last = first;
+        while let Some(next) = changes.next_if(|next| *next <= last + 2 * CONTEXT_LINES + 1) {
+            last = next;
+        }
+        let start = first.saturating_sub(CONTEXT_LINES);
+        let end = (last + CONTEXT_LINES + 1).min(lines.len());
+        let hunk = &lines[start..end];
+
+        let old_start = lines[..start].iter().filter(|line| line.in_old()).count();
+        let new_start = lines[..start].iter().filter(|line| line.in_new()).count();
+        let old_len = hunk.iter().filter(|line| line.in_old()).count();
+        let new_len = hunk.iter().filter(|line| line.in_new()).count();
+        diff.push_str(&format!("@@ -{},{} +{},{} @@\n",
+                               range_start(old_start, old_len),
+                               old_len,
+                               range_start(new_start, new_len),
+                               new_len));
+        for line in hunk {
+            let (prefix, text) = match line {
+                Line::Same(text) => (' ', text),
+                Line::Removed(text) => ('-', text),
+                Line::Added(text) => ('+', text),
+            };
+            diff.push(prefix);
+            diff.push_str(text);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+/// Where a hunk's range of lines begins, given how many lines come
+/// before it. Ranges count lines from 1, except that an empty one names
+/// the line it follows.
+fn range_start(preceding: usize, len: usize) -> usize {
+    if len == 0 {
+        preceding
+    } else {
+        preceding + 1
+    }
+}
+
+/// The shortest edit turning `old` into `new`. This is Myers' diff in
+/// its linear space form, so diffing large files needs memory in
+/// proportion to their length rather than to the product of them.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Line<'a>> {
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    diff_into(old, new, &mut lines);
+
+    // Within each run of changes, show the removed lines before the
+    // added ones
+    let mut i = 0;
+    while i < lines.len() {
+        let run = lines[i..].iter()
+                            .take_while(|line| !matches!(line, Line::Same(_)))
+                            .count();
+        lines[i..i + run].sort_by_key(|line| line.in_new());
+        i += run.max(1);
+    }
+    lines
+}
+
+/// Appends the shortest edit turning `old` into `new` to `lines`, by
+/// splitting it at the middle snake and diffing each side of that.
+fn diff_into<'a>(old: &[&'a str], new: &[&'a str], lines: &mut Vec<Line<'a>>) {
+    let prefix = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..].iter()
+                              .rev()
+                              .zip(new[prefix..].iter().rev())
+                              .take_while(|(o, n)| o == n)
+                              .count();
+    let old_rest = &old[prefix..old.len() - suffix];
+    let new_rest = &new[prefix..new.len() - suffix];
+
+    lines.extend(old[..prefix].iter().copied().map(Line::Same));
+    if old_rest.is_empty() || new_rest.is_empty() {
+        lines.extend(old_rest.iter().copied().map(Line::Removed));
+        lines.extend(new_rest.iter().copied().map(Line::Added));
+    } else {
+        let (x, y) = middle_snake(old_rest, new_rest);
+        diff_into(&old_rest[..x], &new_rest[..y], lines);
+        diff_into(&old_rest[x..], &new_rest[y..], lines);
+    }
+    lines.extend(old[old.len() - suffix..].iter().copied().map(Line::Same));
+}
+
+/// Finds a point on a shortest edit from `old` to `new` that splits it
+/// into two edits which are both shorter, by searching forwards from
+/// the start and backwards from the end until the two searches meet.
+/// Both `old` and `new` must be non-empty, and their first and last
+/// lines must differ.
+fn middle_snake(old: &[&str], new: &[&str]) -> (usize, usize) {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2 + 1;
+    // forward[k] and backward[k] are the furthest x reached on diagonal
+    // k, counting x from the start and from the end respectively
+    let mut forward = vec![0; 2 * max as usize + 1];
+    let mut backward = vec![0; 2 * max as usize + 1];
+    let at = |k: isize| (k + max) as usize;
+
+    for d in 0..max {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let (start_x, start_y) = (x, x - k);
+            let mut y = start_y;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            if odd && (delta - k).abs() < d && x + backward[at(delta - k)] >= n {
+                return (start_x as usize, start_y as usize);
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            if !odd && (delta - k).abs() <= d && x + forward[at(delta - k)] >= n {
+                return ((n - x) as usize, (m - y) as usize);
+            }
+        }
+    }
+    unreachable!("the searches from either end always meet")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_diff_of_the_same_lines_is_empty() {
+        assert_eq!(unified("app.conf", "port = 80\n", "port = 80"), "");
+    }
+
+    #[test]
+    fn unified_diff_groups_nearby_changes_into_hunks() {
+        let old = (1..=20).map(|n| format!("line {}\n", n))
+                          .collect::<String>();
+        let new = old.replace("line 2\n", "line two\n")
+                     .replace("line 5\n", "")
+                     .replace("line 19\n", "line 19\nline 19.5\n");
+        assert_eq!(unified("app.conf", &old, &new),
+                   "--- app.conf\n+++ app.conf\n@@ -1,8 +1,7 @@\n line 1\n-line 2\n+line two\n \
+                    line 3\n line 4\n-line 5\n line 6\n line 7\n line 8\n@@ -17,4 +16,5 @@\n \
+                    line 17\n line 18\n line 19\n+line 19.5\n line 20\n");
+    }
+
+    #[test]
+    fn unified_diff_of_large_files() {
+        let old = (1..=100_000).map(|n| format!("line {}\n", n))
+                               .collect::<String>();
+        let new = old.replace("line 50000\n", "line fifty thousand\n");
+        assert_eq!(unified("app.conf", &old, &new),
+                   "--- app.conf\n+++ app.conf\n@@ -49997,7 +49997,7 @@\n line 49997\n line \
+                    49998\n line 49999\n-line 50000\n+line fifty thousand\n line 50001\n line \
+                    50002\n line 50003\n");
+    }
+
+    #[test]
+    fn unified_diff_of_a_new_file() {
+        assert_eq!(unified("app.conf", "", "port = 80\n"),
+                   "--- app.conf\n+++ app.conf\n@@ -0,0 +1,1 @@\n+port = 80\n");
+    }
+}
@@ -0,0 +1,43 @@
+//! A bounded history of recent events, such as a service's health
+//! checks, hook runs or job runs.
+
+use std::collections::{vec_deque,
+                       VecDeque};
+
+/// The last `LEN` entries pushed onto it, most recent first. Older
+/// entries are forgotten as new ones arrive. Nothing is persisted, so a
+/// history starts over whenever the Supervisor restarts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundedHistory<T, const LEN: usize>(VecDeque<T>);
+
+impl<T, const LEN: usize> BoundedHistory<T, LEN> {
+    pub fn push(&mut self, entry: T) {
+        self.0.push_front(entry);
+        self.0.truncate(LEN);
+    }
+
+    pub fn latest(&self) -> Option<&T> { self.0.front() }
+
+    /// The entries, most recent first.
+    pub fn iter(&self) -> vec_deque::Iter<'_, T> { self.0.iter() }
+}
+
+impl<T, const LEN: usize> Default for BoundedHistory<T, LEN> {
+    fn default() -> Self { Self(VecDeque::new()) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_history_keeps_the_most_recent_entries() {
+        let mut history = BoundedHistory::<u32, 3>::default();
+        assert_eq!(history.latest(), None);
+        for entry in 0..5 {
+            history.push(entry);
+        }
+        assert_eq!(history.latest(), Some(&4));
+        assert_eq!(history.iter().copied().collect::<Vec<_>>(), vec![4, 3, 2]);
+    }
+}
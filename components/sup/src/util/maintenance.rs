@@ -0,0 +1,26 @@
+//! Helpers for holding back updates until a maintenance window opens.
+
+use habitat_core::service::MaintenanceWindow;
+use log::debug;
+use std::time::Duration;
+use tokio::time as tokiotime;
+
+/// How often to check whether a maintenance window has opened.
+const WINDOW_CHECK_PERIOD: Duration = Duration::from_secs(30);
+
+/// Resolve once the current time falls inside one of `windows`. Resolves immediately if no
+/// windows are configured.
+pub async fn wait_for_window(windows: &[MaintenanceWindow]) {
+    let mut logged = false;
+    while !MaintenanceWindow::allows_now(windows) {
+        if !logged {
+            debug!("Holding update until a maintenance window opens ({})",
+                   windows.iter()
+                          .map(ToString::to_string)
+                          .collect::<Vec<_>>()
+                          .join(", "));
+            logged = true;
+        }
+        tokiotime::sleep(WINDOW_CHECK_PERIOD).await;
+    }
+}
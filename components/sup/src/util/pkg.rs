@@ -22,7 +22,8 @@ use habitat_core::{env as henv,
                         FS_ROOT_PATH},
                    package::{PackageIdent,
                              PackageInstall,
-                             PackageTarget},
+                             PackageTarget,
+                             VersionConstraint},
                    ChannelIdent,
                    AUTH_TOKEN_ENVVAR};
 use std::path::Path;
@@ -105,6 +106,47 @@ pub fn installed<T>(ident: T) -> Option<PackageInstall>
     PackageInstall::load(ident.as_ref(), Some(fs_root_path)).ok()
 }
 
+/// Returns the identifier of the package at the head of a channel without installing it.
+pub async fn channel_head(url: &str,
+                          ident: impl AsRef<PackageIdent>,
+                          channel: &ChannelIdent)
+                          -> Result<PackageIdent> {
+    let fs_root_path = Path::new(&*FS_ROOT_PATH);
+    let auth_token = get_auth_token();
+    let api_client = BuilderAPIClient::new(url, PRODUCT, VERSION, Some(fs_root_path))?;
+    let channel_latest_ident = api_client.show_package((ident.as_ref(),
+                                                        PackageTarget::active_target()),
+                                                       channel,
+                                                       auth_token.as_deref())
+                                         .await?;
+    Ok(channel_latest_ident)
+}
+
+/// Returns the identifier of the newest release in a channel whose version satisfies
+/// `constraint`, without installing it.
+pub async fn newest_in_channel(url: &str,
+                               ident: impl AsRef<PackageIdent>,
+                               channel: &ChannelIdent,
+                               constraint: &VersionConstraint)
+                               -> Result<Option<PackageIdent>> {
+    let fs_root_path = Path::new(&*FS_ROOT_PATH);
+    let auth_token = get_auth_token();
+    let api_client = BuilderAPIClient::new(url, PRODUCT, VERSION, Some(fs_root_path))?;
+    let releases = api_client.list_channel_package_releases((ident.as_ref(),
+                                                             PackageTarget::active_target()),
+                                                            channel,
+                                                            auth_token.as_deref())
+                             .await?;
+    Ok(releases.into_iter()
+               .filter(|release| {
+                   release.fully_qualified()
+                   && release.version
+                             .as_ref()
+                             .map_or(false, |version| constraint.matches(version))
+               })
+               .max())
+}
+
 /// Install a package but only consider packages from a channel. Do not consider any locally
 /// installed packages.
 ///
@@ -113,15 +155,8 @@ pub async fn install_channel_head(url: &str,
                                   ident: impl AsRef<PackageIdent>,
                                   channel: &ChannelIdent)
                                   -> Result<PackageInstall> {
-    let fs_root_path = Path::new(&*FS_ROOT_PATH);
-    let auth_token = get_auth_token();
-    let api_client = BuilderAPIClient::new(url, PRODUCT, VERSION, Some(fs_root_path))?;
     // Get the latest package identifier from the channel
-    let channel_latest_ident = api_client.show_package((ident.as_ref(),
-                                                        PackageTarget::active_target()),
-                                                       channel,
-                                                       auth_token.as_deref())
-                                         .await?;
+    let channel_latest_ident = channel_head(url, ident, channel).await?;
     // Ensure the latest package from the channel is installed
     install_no_ui(url, &channel_latest_ident.into(), channel).await
 }
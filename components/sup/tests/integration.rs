@@ -604,6 +604,30 @@ async fn no_restart_for_templated_post_stop_hook() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[cfg_attr(feature = "ignore_integration_tests", ignore)]
+async fn no_restart_for_templated_pre_stop_hook() -> Result<()> {
+    let test_name = "no_restart_for_templated_pre_stop_hook_without_reconfiguration_hook";
+    let package_name = "config-and-hooks-no-reconfigure";
+    let applied_config = r#"pre_stop_templated_value = "Pre Stop Hook Value""#;
+    let updated_files = vec!["hooks/pre-stop"];
+    test_for_no_restart_on_config_application(test_name,
+                                              package_name,
+                                              applied_config,
+                                              updated_files).await?;
+
+    let test_name = "no_restart_for_templated_pre_stop_hook_with_reconfiguration_hook";
+    let package_name = "config-and-hooks-with-reconfigure";
+    let applied_config = r#"pre_stop_templated_value = "Pre Stop Hook Value""#;
+    let updated_files = vec!["hooks/pre-stop"];
+    test_for_no_restart_on_config_application(test_name,
+                                              package_name,
+                                              applied_config,
+                                              updated_files).await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 #[cfg_attr(feature = "ignore_integration_tests", ignore)]
 async fn no_restart_for_templated_reconfigure_hook() -> Result<()> {